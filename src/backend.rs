@@ -0,0 +1,526 @@
+//! In-process Git backend built on the pure-Rust `gix` crate.
+//!
+//! [`GixGit`] answers the per-chapter queries (`git log -1` for the last
+//! commit touching a path, `git describe --tags --exact-match`) without
+//! spawning a process. Every other command, and any query it cannot
+//! reproduce exactly (such as format placeholders it does not know), is
+//! handed to a fallback [`SystemGit`], so output never differs from the
+//! shell backend.
+
+use crate::git::{GitRunner, SystemGit};
+use gix::ObjectId;
+use gix::objs::tree::EntryMode;
+use mdbook_preprocessor::errors::Error;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// [`GitRunner`] that reads commits and tags with `gix`.
+pub struct GixGit {
+    fallback: SystemGit,
+    /// Repository opened for the last `dir`, reused across queries.
+    repo: RefCell<Option<(PathBuf, gix::Repository)>>,
+}
+
+impl GixGit {
+    /// Read commits and tags in-process, running `fallback` for the rest.
+    pub fn new(fallback: SystemGit) -> Self {
+        GixGit {
+            fallback,
+            repo: RefCell::new(None),
+        }
+    }
+
+    /// Answer `query`, or `None` to hand it to the fallback.
+    fn answer(&self, query: &Query<'_>, dir: &Path) -> Option<Result<String, Error>> {
+        let mut slot = self.repo.borrow_mut();
+        if slot.as_ref().is_none_or(|(open, _)| open != dir) {
+            let mut repo = gix::discover(dir).ok()?;
+            repo.object_cache_size_if_unset(4 * 1024 * 1024);
+            *slot = Some((dir.to_path_buf(), repo));
+        }
+        let repo = &slot.as_ref()?.1;
+        match query {
+            Query::LastCommit {
+                format,
+                rev,
+                path,
+                opts,
+            } => {
+                let path = repo_path(repo, dir, path)?;
+                let commit = last_commit(repo, rev, &path, *opts).ok()?;
+                let out = match commit {
+                    Some(commit) => render(&commit, format).ok()?,
+                    None => String::new(),
+                };
+                Some(Ok(out.trim().to_string()))
+            }
+            Query::ExactTag(rev) => {
+                let mut names = exact_tags(repo, rev).ok()?;
+                match names.len() {
+                    0 => Some(Err(Error::msg(format!("no tag exactly matches '{rev}'")))),
+                    1 => names.pop().map(Ok),
+                    // Git ranks several matches by tag kind and date.
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl GitRunner for GixGit {
+    fn output(&self, args: &[&str], dir: &Path) -> Result<String, Error> {
+        match Query::parse(args).and_then(|q| self.answer(&q, dir)) {
+            Some(answer) => answer,
+            None => self.fallback.output(args, dir),
+        }
+    }
+}
+
+/// History flags of a `git log -1` query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Walk {
+    follow: bool,
+    no_merges: bool,
+}
+
+/// A Git command line [`GixGit`] knows how to answer.
+#[derive(Debug, PartialEq, Eq)]
+enum Query<'a> {
+    /// `log -1 [--follow] [--no-merges] --format=<format> <rev> -- <path>`
+    LastCommit {
+        format: Vec<Spec<'a>>,
+        rev: &'a str,
+        path: &'a str,
+        opts: Walk,
+    },
+    /// `describe --tags --exact-match <rev>`
+    ExactTag(&'a str),
+}
+
+impl<'a> Query<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        match args {
+            ["describe", "--tags", "--exact-match", rev] => Some(Query::ExactTag(rev)),
+            ["log", "-1", rest @ ..] => {
+                let mut opts = Walk {
+                    follow: false,
+                    no_merges: false,
+                };
+                let mut rest = rest;
+                while let [flag @ ("--follow" | "--no-merges"), tail @ ..] = rest {
+                    match *flag {
+                        "--follow" => opts.follow = true,
+                        _ => opts.no_merges = true,
+                    }
+                    rest = tail;
+                }
+                match rest {
+                    [format, rev, "--", path] => Some(Query::LastCommit {
+                        format: parse_format(format.strip_prefix("--format=")?)?,
+                        rev,
+                        path,
+                        opts,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One piece of a `--format` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Spec<'a> {
+    Literal(&'a str),
+    /// `%h`
+    Short,
+    /// `%H`
+    Long,
+    /// `%cI`
+    CommitterDate,
+    /// `%an`
+    AuthorName,
+    /// `%cn`
+    CommitterName,
+    /// `%ae`
+    AuthorEmail,
+    /// `%s`
+    Subject,
+    /// `%x00`
+    Nul,
+}
+
+/// Split `format` into [`Spec`]s, or `None` if it uses a placeholder this
+/// backend does not render.
+fn parse_format(format: &str) -> Option<Vec<Spec<'_>>> {
+    const PLACEHOLDERS: [(&str, Spec<'static>); 9] = [
+        ("%", Spec::Literal("%")),
+        ("h", Spec::Short),
+        ("H", Spec::Long),
+        ("cI", Spec::CommitterDate),
+        ("an", Spec::AuthorName),
+        ("cn", Spec::CommitterName),
+        ("ae", Spec::AuthorEmail),
+        ("s", Spec::Subject),
+        ("x00", Spec::Nul),
+    ];
+    let mut specs = Vec::new();
+    let mut rest = format;
+    while !rest.is_empty() {
+        match rest.find('%') {
+            Some(0) => {
+                let after = &rest[1..];
+                let (key, spec) = PLACEHOLDERS.iter().find(|(k, _)| after.starts_with(k))?;
+                specs.push(*spec);
+                rest = &after[key.len()..];
+            }
+            Some(i) => {
+                specs.push(Spec::Literal(&rest[..i]));
+                rest = &rest[i..];
+            }
+            None => {
+                specs.push(Spec::Literal(rest));
+                rest = "";
+            }
+        }
+    }
+    Some(specs)
+}
+
+/// `path`, given relative to `dir`, as a path from the work tree root.
+fn repo_path(repo: &gix::Repository, dir: &Path, path: &str) -> Option<PathBuf> {
+    let root = repo.workdir()?.canonicalize().ok()?;
+    let prefix = dir.canonicalize().ok()?;
+    let mut out = PathBuf::new();
+    for part in prefix.strip_prefix(&root).ok()?.join(path).components() {
+        match part {
+            Component::Normal(p) => out.push(p),
+            Component::CurDir => {}
+            Component::ParentDir if out.pop() => {}
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Mode and id of `path` in `commit`'s tree; the empty path is the tree itself.
+fn entry_at(commit: &gix::Commit<'_>, path: &Path) -> gix::Result<Option<(EntryMode, ObjectId)>> {
+    if path.as_os_str().is_empty() {
+        return Ok(Some((
+            gix::objs::tree::EntryKind::Tree.into(),
+            commit.tree_id()?.detach(),
+        )));
+    }
+    Ok(commit
+        .tree()?
+        .lookup_entry_by_path(path)?
+        .map(|e| (e.mode(), e.object_id())))
+}
+
+/// Last commit reachable from `rev` that changed `path`, as `git log -1`
+/// reports it.
+///
+/// Commits are visited newest first by committer date. Without `follow`, a
+/// merge that left `path` as one parent had it only continues along that
+/// parent (Git's default history simplification). With `follow`, Git walks
+/// every parent and never reports a merge.
+fn last_commit<'r>(
+    repo: &'r gix::Repository,
+    rev: &str,
+    path: &Path,
+    opts: Walk,
+) -> gix::Result<Option<gix::Commit<'r>>> {
+    let start = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+    let mut queue = BinaryHeap::new();
+    let mut seen = HashSet::new();
+    let mut order = 0usize;
+    let mut push = |queue: &mut BinaryHeap<_>, commit: &gix::Commit<'_>| -> gix::Result<()> {
+        if seen.insert(commit.id) {
+            order += 1;
+            queue.push((commit.time()?.seconds, Reverse(order), commit.id));
+        }
+        Ok(())
+    };
+    push(&mut queue, &start)?;
+
+    while let Some((_, _, id)) = queue.pop() {
+        let commit = repo.find_commit(id)?;
+        let here = entry_at(&commit, path)?;
+        let parents = commit
+            .parent_ids()
+            .map(|p| repo.find_commit(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let changed = match parents.as_slice() {
+            [] => here.is_some(),
+            [parent] => {
+                push(&mut queue, parent)?;
+                entry_at(parent, path)? != here
+            }
+            _ => {
+                let mut same = None;
+                if !opts.follow {
+                    for parent in &parents {
+                        if entry_at(parent, path)? == here {
+                            same = Some(parent);
+                            break;
+                        }
+                    }
+                }
+                match same {
+                    Some(parent) => push(&mut queue, parent)?,
+                    None => {
+                        for parent in &parents {
+                            push(&mut queue, parent)?;
+                        }
+                    }
+                }
+                same.is_none() && !opts.follow && !opts.no_merges
+            }
+        };
+        if changed {
+            return Ok(Some(commit));
+        }
+    }
+    Ok(None)
+}
+
+/// `commit` printed with the `--format` pieces in `format`.
+fn render(commit: &gix::Commit<'_>, format: &[Spec<'_>]) -> gix::Result<String> {
+    let mut out = String::new();
+    for spec in format {
+        match spec {
+            Spec::Literal(text) => out.push_str(text),
+            Spec::Short => out.push_str(&commit.id().shorten_or_id().to_string()),
+            Spec::Long => out.push_str(&commit.id.to_string()),
+            Spec::CommitterDate => out.push_str(&strict_iso(commit.time()?)),
+            Spec::AuthorName => out.push_str(&commit.author()?.trim().name.to_string()),
+            Spec::CommitterName => out.push_str(&commit.committer()?.trim().name.to_string()),
+            Spec::AuthorEmail => out.push_str(&commit.author()?.trim().email.to_string()),
+            Spec::Subject => out.push_str(&commit.message()?.summary().to_string()),
+            Spec::Nul => out.push('\0'),
+        }
+    }
+    Ok(out)
+}
+
+/// `time` as Git's `%cI`: strict ISO 8601 in the recorded offset.
+fn strict_iso(time: gix::date::Time) -> String {
+    chrono::FixedOffset::east_opt(time.offset)
+        .zip(chrono::DateTime::from_timestamp(time.seconds, 0))
+        .map(|(offset, utc)| {
+            utc.with_timezone(&offset)
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+        })
+        .unwrap_or_default()
+}
+
+/// Names of the tags whose target peels to the commit `rev` names.
+fn exact_tags(repo: &gix::Repository, rev: &str) -> gix::Result<Vec<String>> {
+    let target = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?.id;
+    let mut names = Vec::new();
+    for tag in repo.references()?.tags()? {
+        let Ok(mut tag) = tag else { continue };
+        if tag.peel_to_commit().is_ok_and(|c| c.id == target) {
+            names.push(tag.name().shorten().to_string());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{COMMIT_FIELDS_FORMAT, LogOptions, last_commit_fields_as};
+    use crate::test_support::git_in;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Run `git` in `dir` as `tester`, with author and committer `date`.
+    fn git_at(dir: &Path, date: &str, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=tester",
+                "-c",
+                "user.email=tester@example.com",
+            ])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn commit(dir: &Path, name: &str, body: &str, message: &str, date: &str) {
+        fs::write(dir.join(name), body).unwrap();
+        git_in(dir, &["add", name]);
+        git_at(dir, date, &["commit", "-q", "-m", message]);
+    }
+
+    /// Two branches merged with an edit to `a.md`, committed on distinct
+    /// dates in several time zones, with a lightweight and an annotated tag.
+    fn history() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let d = dir.path();
+        git_in(d, &["init", "-q", "-b", "main"]);
+        commit(
+            d,
+            "a.md",
+            "v1\n",
+            "Add a\n\nWith a body.",
+            "2024-01-01T10:00:00+02:00",
+        );
+        commit(d, "b.md", "b\n", "Add b", "2024-02-01T10:00:00-05:00");
+        git_in(d, &["checkout", "-q", "-b", "feature"]);
+        commit(
+            d,
+            "b.md",
+            "b2\n",
+            "Edit b on feature",
+            "2024-03-01T10:00:00+00:00",
+        );
+        git_in(d, &["checkout", "-q", "main"]);
+        commit(
+            d,
+            "a.md",
+            "v2\n",
+            "Edit a on main",
+            "2024-04-01T10:00:00+05:30",
+        );
+        git_in(d, &["merge", "-q", "--no-ff", "--no-commit", "feature"]);
+        fs::write(d.join("a.md"), "v3\n").unwrap();
+        git_in(d, &["add", "a.md"]);
+        git_at(
+            d,
+            "2024-05-01T10:00:00+01:00",
+            &["commit", "-q", "-m", "Merge feature"],
+        );
+        git_in(d, &["tag", "v1.0"]);
+        git_in(d, &["tag", "-a", "-m", "release", "v0.9", "HEAD~1"]);
+        dir
+    }
+
+    /// A [`GixGit`] whose fallback cannot run, so every answer is in-process.
+    fn in_process() -> GixGit {
+        GixGit::new(SystemGit::new("/nonexistent/git"))
+    }
+
+    #[test]
+    fn parses_only_supported_queries() {
+        let query = Query::parse(&[
+            "log",
+            "-1",
+            "--follow",
+            "--format=%h%x00%s",
+            "main",
+            "--",
+            "a.md",
+        ]);
+        assert_eq!(
+            query,
+            Some(Query::LastCommit {
+                format: vec![Spec::Short, Spec::Nul, Spec::Subject],
+                rev: "main",
+                path: "a.md",
+                opts: Walk {
+                    follow: true,
+                    no_merges: false,
+                },
+            })
+        );
+        assert!(Query::parse(&["log", "-1", "--format=%aN", "main", "--", "a.md"]).is_none());
+        assert!(Query::parse(&["log", "--format=%h", "main"]).is_none());
+        assert!(Query::parse(&["rev-parse", "HEAD"]).is_none());
+    }
+
+    #[test]
+    fn other_commands_run_the_fallback() {
+        let repo = history();
+        let args = ["rev-parse", "HEAD"];
+        let expected = SystemGit::default().output(&args, repo.path()).unwrap();
+        let gix = GixGit::new(SystemGit::default());
+        assert_eq!(gix.output(&args, repo.path()).unwrap(), expected);
+        assert!(in_process().output(&args, repo.path()).is_err());
+    }
+
+    #[test]
+    fn commit_fields_match_the_shell_backend() {
+        let repo = history();
+        let shell = SystemGit::default();
+        let gix = in_process();
+        for path in ["a.md", "b.md", "missing.md", "."] {
+            for follow in [false, true] {
+                for no_merges in [false, true] {
+                    let opts = LogOptions { follow, no_merges };
+                    let read = |runner: &dyn GitRunner| {
+                        last_commit_fields_as(
+                            runner,
+                            COMMIT_FIELDS_FORMAT,
+                            "refs/heads/main",
+                            path,
+                            repo.path(),
+                            opts,
+                        )
+                        .unwrap()
+                    };
+                    assert_eq!(read(&gix), read(&shell), "{path} {opts:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hash_and_date_match_the_shell_backend() {
+        let repo = history();
+        let shell = SystemGit::default();
+        let gix = in_process();
+        for rev in ["main", "HEAD~1", "feature"] {
+            let args = ["log", "-1", "--format=%h %H %cI", rev, "--", "b.md"];
+            let expected = shell.output(&args, repo.path()).unwrap();
+            assert!(!expected.is_empty());
+            assert_eq!(gix.output(&args, repo.path()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn exact_tags_match_the_shell_backend() {
+        let repo = history();
+        let shell = SystemGit::default();
+        let gix = in_process();
+        let first = git_in(repo.path(), &["rev-list", "--max-parents=0", "HEAD"]);
+        for rev in ["HEAD", "HEAD~1", first.as_str()] {
+            let args = ["describe", "--tags", "--exact-match", rev];
+            assert_eq!(
+                gix.output(&args, repo.path()).ok(),
+                shell.output(&args, repo.path()).ok(),
+                "{rev}"
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_paths_from_a_subdirectory() {
+        let repo = history();
+        let sub = repo.path().join("book");
+        fs::create_dir_all(&sub).unwrap();
+        commit(
+            repo.path(),
+            "book/c.md",
+            "c\n",
+            "Add c",
+            "2024-06-01T10:00:00+00:00",
+        );
+        let shell = SystemGit::default();
+        let gix = in_process();
+        let args = ["log", "-1", "--format=%H", "HEAD", "--", "c.md"];
+        assert_eq!(
+            gix.output(&args, &sub).unwrap(),
+            shell.output(&args, &sub).unwrap()
+        );
+    }
+}
@@ -18,8 +18,11 @@
 //! ```
 
 use mdbook_preprocessor::PreprocessorContext;
+use mdbook_preprocessor::config::Config;
 use mdbook_preprocessor::errors::Error;
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
 
 #[derive(Debug, Deserialize, Default)]
 pub struct MessageConfig {
@@ -31,25 +34,76 @@ pub struct MessageConfig {
     pub both: Option<String>,
 }
 
+/// Footer template applied to chapters whose path matches `pattern`.
+///
+/// ```toml
+/// [[preprocessor.gitinfo.template-rules]]
+/// pattern  = "api/**"
+/// template = "API revision {{hash}}"
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct TemplateRule {
+    /// Glob matched against the chapter path, relative to the book's `src` directory.
+    pub pattern: String,
+    /// Footer template used for matching chapters.
+    pub template: String,
+}
+
+/// One margin value, e.g. `"1em"`. A bare TOML `0` is accepted, as in CSS;
+/// other bare numbers are rejected because CSS needs a unit for them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawLength")]
+pub struct CssLength(pub String);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawLength {
+    Text(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl TryFrom<RawLength> for CssLength {
+    type Error = String;
+
+    fn try_from(raw: RawLength) -> Result<Self, Self::Error> {
+        let n = match raw {
+            RawLength::Text(s) => return Ok(CssLength(s)),
+            RawLength::Int(0) | RawLength::Float(0.0) => return Ok(CssLength("0".to_string())),
+            RawLength::Int(n) => n.to_string(),
+            RawLength::Float(n) => n.to_string(),
+        };
+        Err(format!(
+            "margin {n} needs a CSS unit (e.g. \"{n}em\"); only 0 may be a bare number"
+        ))
+    }
+}
+
+impl From<&str> for CssLength {
+    fn from(s: &str) -> Self {
+        CssLength(s.to_string())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum MarginSetting {
-    /// "1em"
-    One(String),
+    /// "1em", or a CSS shorthand string such as "1em 2em 3em"
+    One(CssLength),
     /// ["top", "right", "bottom", "left"] — supports 1–4 entries like CSS shorthand
-    Quad(Vec<String>),
+    Quad(Vec<CssLength>),
     /// { top = "...", right = "...", bottom = "...", left = "..." }
     Sides {
-        top: Option<String>,
-        right: Option<String>,
-        bottom: Option<String>,
-        left: Option<String>,
+        top: Option<CssLength>,
+        right: Option<CssLength>,
+        bottom: Option<CssLength>,
+        left: Option<CssLength>,
     },
 }
 
 impl Default for MarginSetting {
     fn default() -> Self {
-        MarginSetting::One("0".to_string())
+        MarginSetting::One("0".into())
     }
 }
 
@@ -79,20 +133,233 @@ impl Default for AlignSetting {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+/// A string set for both blocks or per block.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PlacementSetting {
+    /// wrapper-tag = "div"
+    One(String),
+    /// wrapper-tag = { header = "...", footer = "...", both = "..." }
+    Split {
+        header: Option<String>,
+        footer: Option<String>,
+        both: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ContributorsSource {
+    #[default]
     Git,
     File,
     Inline,
+    Env,
 }
 
-impl Default for ContributorsSource {
-    fn default() -> Self {
-        ContributorsSource::Git
+/// Order of contributors sourced from git.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContributorsOrder {
+    /// Alphabetical by username.
+    #[default]
+    Name,
+    /// Commit count (descending), then username.
+    Stable,
+}
+
+/// History the `git` contributors source reads (`contributors-scope`).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContributorsScope {
+    /// Every commit in the repository, one list for all pages.
+    #[default]
+    Book,
+    /// Commits touching the chapter's source file.
+    Chapter,
+}
+
+/// Sort order of contributors sourced from git (`contributors-sort`).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContributorsSort {
+    /// Commit count (descending), then name.
+    Commits,
+    /// Case-insensitive by name.
+    #[default]
+    Name,
+    /// Order of each contributor's first commit, oldest first.
+    FirstSeen,
+}
+
+/// Line endings of chapters after injection (`line-ending`).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// `\n` throughout.
+    Lf,
+    /// `\r\n` throughout.
+    Crlf,
+    /// Match the chapter's dominant line ending.
+    #[default]
+    Preserve,
+}
+
+/// Which footers remain visible when the book is printed (`print.html`
+/// concatenates every chapter, so per-chapter footers repeat).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FooterPrint {
+    /// Every chapter keeps its footer.
+    #[default]
+    Each,
+    /// Only the first footer is printed.
+    First,
+    /// Only the last footer is printed.
+    Last,
+    /// No footer is printed.
+    None,
+}
+
+/// How Git is queried (`backend`).
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitBackend {
+    /// Every query runs the Git executable.
+    #[default]
+    Shell,
+    /// Per-chapter commit and tag lookups are read in-process with `gix`.
+    Gix,
+}
+
+/// How the text of header/footer templates is interpreted.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateFormat {
+    /// Template text is used as written (HTML allowed).
+    #[default]
+    Text,
+    /// `**bold**`, `*em*` and `_em_` in the template text become HTML.
+    Markdown,
+}
+
+/// How `{{date}}` renders the commit date.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// `date-format` / `time-format` in the configured timezone.
+    #[default]
+    Absolute,
+    /// Relative to the build time, e.g. `3 days ago` (same as `{{relative}}`).
+    Relative,
+}
+
+/// Localised templates for `{{age}}`; `{n}` is replaced by the count.
+///
+/// Any unit left unset falls back to English.
+///
+/// ```toml
+/// [preprocessor.gitinfo.age-strings]
+/// days  = "il y a {n} jours"
+/// years = "il y a {n} ans"
+/// ```
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct AgeStrings {
+    /// Less than a minute. Default: `"just now"`.
+    #[serde(rename = "just-now")]
+    pub just_now: Option<String>,
+    /// English: `"{n} minutes ago"`.
+    pub minutes: Option<String>,
+    /// English: `"{n} hours ago"`.
+    pub hours: Option<String>,
+    /// English: `"{n} days ago"`.
+    pub days: Option<String>,
+    /// English: `"{n} months ago"`.
+    pub months: Option<String>,
+    /// English: `"{n} years ago"`.
+    pub years: Option<String>,
+}
+
+/// How e-mail addresses from `Name <email>` contributor lines are rendered.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContributorsEmails {
+    #[default]
+    Hide,
+    Show,
+    Link,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagScope {
+    #[default]
+    Repo,
+    Chapter,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagSource {
+    #[default]
+    Branch,
+    Repo,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    Github,
+    Gitlab,
+    Gitea,
+    Bitbucket,
+    Azure,
+}
+
+impl Forge {
+    /// Lowercase name, as written in `book.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Forge::Github => "github",
+            Forge::Gitlab => "gitlab",
+            Forge::Gitea => "gitea",
+            Forge::Bitbucket => "bitbucket",
+            Forge::Azure => "azure",
+        }
     }
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimaryLink {
+    #[default]
+    Commit,
+    Tag,
+}
+
+/// What `{{tag}}` shows when no tag is found.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoTagFallback {
+    /// The `no-tag-text` string.
+    #[default]
+    Text,
+    /// The branch (linked like `{{branch}}`).
+    Branch,
+    /// The short commit hash (linked like `{{hash}}`).
+    Hash,
+}
+
+/// Verbosity of the preprocessor's diagnostics on `stderr`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Warn = 0,
+    Info = 1,
+    Debug = 2,
+}
+
 /// Represents the user-defined configuration options under `[preprocessor.gitinfo]`
 /// in `book.toml`.
 ///
@@ -105,8 +372,19 @@ pub struct GitInfoConfig {
     /// Default: true (when omitted).
     pub enable: Option<bool>,
 
-    /// The formatting style of the git data (currently unused, reserved for future use).
-    pub format: Option<String>,
+    /// Verbosity of diagnostics printed to `stderr`.
+    ///
+    /// Options: "warn" (default), "info", "debug"
+    #[serde(rename = "log-level")]
+    pub log_level: Option<LogLevel>,
+
+    /// How the template text is interpreted.
+    ///
+    /// Options: "text" (default), "markdown"
+    ///
+    /// - markdown: `**…**` becomes `<strong>` and `*…*`/`_…_` become `<em>`.
+    ///   Only the template text is converted, never placeholder values.
+    pub format: Option<TemplateFormat>,
 
     /// Template string defining how git metadata is rendered.
     ///
@@ -116,6 +394,7 @@ pub struct GitInfoConfig {
     /// - `{{tag}}` → lastest tag or user defined
     /// - `{{date}}` → commit date
     /// - `{{sep}}` → separator string
+    ///
     /// (Deprecated) Old single template. If present, used as a fallback for footer_message.
     pub template: Option<String>,
 
@@ -126,6 +405,19 @@ pub struct GitInfoConfig {
     /// Message templates in a table: message.header/message.footer/message.both
     pub message: Option<MessageConfig>,
 
+    /// Per-path footer templates; the first matching rule wins, other
+    /// chapters use the resolved footer message.
+    #[serde(rename = "template-rules")]
+    pub template_rules: Option<Vec<TemplateRule>>,
+
+    /// Globs (relative to `src`) of chapters that get a header/footer,
+    /// e.g. `["reference/**"]`. Empty or unset means every chapter.
+    pub include: Option<Vec<String>>,
+
+    /// Globs (relative to `src`) of chapters that never get a header/footer
+    /// or contributors block, e.g. `["index.md"]`. Wins over `include`.
+    pub exclude: Option<Vec<String>>,
+
     /// CSS font size for the rendered footer text.
     ///
     /// Default: `"0.8em"`.
@@ -151,15 +443,129 @@ pub struct GitInfoConfig {
     #[serde(rename = "time-format")]
     pub time_format: Option<String>,
 
+    /// Whether to render the date component at all.
+    ///
+    /// Set to `false` for time-only output without blanking `date-format`.
+    /// Default: `true`.
+    #[serde(rename = "show-date")]
+    pub show_date: Option<bool>,
+
+    /// Whether to render the time component at all.
+    ///
+    /// Set to `false` for date-only output without blanking `time-format`.
+    /// Default: `true`.
+    #[serde(rename = "show-time")]
+    pub show_time: Option<bool>,
+
+    /// Fail the build when `theme/gitinfo.css` or `book.toml` cannot be written,
+    /// instead of only warning.
+    ///
+    /// Default: `false`.
+    #[serde(rename = "assets-strict")]
+    pub assets_strict: Option<bool>,
+
+    /// Render line breaks in `template`/`header`/`footer` messages as `<br>`.
+    ///
+    /// Default: `false`.
+    #[serde(rename = "template-multiline")]
+    pub template_multiline: Option<bool>,
+
+    /// Maximum length of `{{subject}}` in characters; longer subjects end in `…`.
+    /// `0` disables truncation.
+    ///
+    /// Default: `72`.
+    #[serde(rename = "subject-max-len")]
+    pub subject_max_len: Option<usize>,
+
+    /// Value of `{{dirty}}` for chapters with uncommitted local edits.
+    ///
+    /// Default: `"*"`.
+    #[serde(rename = "dirty-marker")]
+    pub dirty_marker: Option<String>,
+
+    /// Whether `{{date}}` shows the absolute date or the relative age.
+    ///
+    /// Default: `"absolute"`.
+    #[serde(rename = "date-style")]
+    pub date_style: Option<DateStyle>,
+
+    /// Localised unit templates for the `{{age}}` placeholder.
+    #[serde(rename = "age-strings")]
+    pub age_strings: Option<AgeStrings>,
+
+    /// Render `{{date}}` as `<time datetime="…">` with the ISO-8601 commit time,
+    /// for semantic HTML and machine parsing.
+    ///
+    /// Default: `false`
+    #[serde(rename = "emit-semantic")]
+    pub emit_semantic: Option<bool>,
+
     pub timezone: Option<String>, // "local" | "utc" | "source" | "fixed:+01:00" | "rfc3339"
     pub datetime_format: Option<String>, // optional: if set, overrides date/time format join
-    pub show_offset: Option<bool>, // optional: if true and no %z/%:z/%Z, append %:z
 
-    /// Git branch from which to retrieve commit history.
+    /// Append the UTC offset (`%:z`) to the rendered date when neither
+    /// `date-format` nor `time-format` prints the zone. Useful with
+    /// `timezone = "source"`, where each commit keeps its own offset.
+    ///
+    /// Default: `false`
+    pub show_offset: Option<bool>,
+
+    /// Pass `--follow` to the per-chapter `git log` queries so history is
+    /// traced across renames of the chapter file.
+    ///
+    /// Default: `true`.
+    #[serde(rename = "follow-renames")]
+    pub follow_renames: Option<bool>,
+
+    /// Path or name of the Git executable, for images where Git lives at a
+    /// non-standard location. Overrides the `GITINFO_GIT_BINARY` variable.
+    ///
+    /// Default: `"git"`
+    #[serde(rename = "git-binary")]
+    pub git_binary: Option<String>,
+
+    /// How per-chapter commit and tag lookups are made: "shell" runs the Git
+    /// executable for each one, "gix" reads the repository in-process with
+    /// the pure-Rust gix crate. Other commands always use the executable.
+    /// Needs the `gix` cargo feature (on by default); without it "gix" falls
+    /// back to "shell" with a warning.
+    ///
+    /// Default: "shell"
+    pub backend: Option<GitBackend>,
+
+    /// Skip merge commits (`git log --no-merges`) when looking up a chapter's
+    /// last commit, so date, author and hash reflect the last content change.
+    ///
+    /// Default: `false`
+    #[serde(rename = "ignore-merges")]
+    pub ignore_merges: Option<bool>,
+
+    /// Directory Git commands run in, for books nested inside a larger
+    /// repository. Relative paths are resolved against the book root.
+    ///
+    /// Default: detected with `git rev-parse --show-toplevel`, else the book root.
+    #[serde(rename = "git-root")]
+    pub git_root: Option<String>,
+
+    /// Git branch from which to retrieve commit history. `"auto"` picks the
+    /// CI branch, then the checked-out branch, then `origin/HEAD`, then
+    /// `init.defaultBranch`.
     ///
     /// Default: `"main"`.
     pub branch: Option<String>,
 
+    /// Read all commit metadata at this revision (SHA, tag or ref) instead of
+    /// the tip of `branch`, e.g. for versioned documentation snapshots.
+    ///
+    /// The revision must exist; an unknown ref is an error.
+    #[serde(rename = "pin-ref")]
+    pub pin_ref: Option<String>,
+
+    /// Git `--format` string whose output is exposed as `{{custom}}`,
+    /// e.g. `"%an <%ae>"`. Must produce a single line.
+    #[serde(rename = "custom-format")]
+    pub custom_format: Option<String>,
+
     /// Flexible align
     /// - align = "center"
     /// - align.header = "left", align.footer = "right"
@@ -169,9 +575,209 @@ pub struct GitInfoConfig {
     /// CSS option to adjust margin between body and footer
     pub margin: Option<MarginConfig>,
 
+    /// Element the header/footer is wrapped in, e.g. `"div"` or
+    /// `{ header = "div", footer = "aside" }`.
+    ///
+    /// Default: `"header"` for the header, `"footer"` for the footer
+    #[serde(rename = "wrapper-tag")]
+    pub wrapper_tag: Option<PlacementSetting>,
+
+    /// Base class of the header/footer element, replacing the class the
+    /// generated stylesheet targets. Same forms as `wrapper-tag`.
+    ///
+    /// Default: `"gitinfo-header"` / `"gitinfo-footer"`
+    #[serde(rename = "class-name")]
+    pub class_name: Option<PlacementSetting>,
+
+    /// Extra attributes on the `<header>` element, e.g. `{ role = "banner" }`.
+    /// Values are HTML-escaped.
+    #[serde(rename = "header-attrs")]
+    pub header_attrs: Option<BTreeMap<String, String>>,
+
+    /// Write `theme/gitinfo.js` and register it under `additional-js`; it
+    /// adds a tooltip with the full commit time and `data-gitinfo-*` values
+    /// to headers and footers.
+    ///
+    /// Default: `false`
+    #[serde(rename = "inject-js")]
+    pub inject_js: Option<bool>,
+
+    /// Write per-chapter metadata to `target/gitinfo/sidebar.json` (keyed by
+    /// chapter path) for a theme override to show in the sidebar.
+    ///
+    /// Default: `false`
+    #[serde(rename = "sidebar-data")]
+    pub sidebar_data: Option<bool>,
+
+    /// Put the same per-chapter metadata at the top of each chapter as a
+    /// `<!-- gitinfo:metadata {json} -->` comment, for custom renderers.
+    ///
+    /// Default: `false`
+    #[serde(rename = "embed-metadata")]
+    pub embed_metadata: Option<bool>,
+
+    /// Footer text colour for light themes, e.g. `"#555"` (adds
+    /// `theme/gitinfo.css`). Default: `#6e7781`.
+    #[serde(rename = "footer-color")]
+    pub footer_color: Option<String>,
+
+    /// Footer text colour for mdBook's dark themes (`navy`, `coal`, `ayu`).
+    /// Default: `#9ba3af`.
+    #[serde(rename = "footer-color-dark")]
+    pub footer_color_dark: Option<String>,
+
+    /// Extra attributes on the `<footer>` element, e.g.
+    /// `{ "data-section" = "meta", role = "contentinfo" }`. Values are HTML-escaped.
+    #[serde(rename = "footer-attrs")]
+    pub footer_attrs: Option<BTreeMap<String, String>>,
+
+    /// Add `data-gitinfo-version="<crate version>"` to the `<footer>` element,
+    /// to tell which release generated a page.
+    ///
+    /// Default: `false`
+    #[serde(rename = "emit-version")]
+    pub emit_version: Option<bool>,
+
+    /// Renderers the preprocessor runs for, e.g. `["html", "epub"]`. mdBook
+    /// reads this key too. Outside `html`, blocks get no `style` attribute and
+    /// `theme/gitinfo.css` is not written.
+    ///
+    /// Default: `["html"]`
+    pub renderers: Option<Vec<String>>,
+
     // explicit tag override (if set, use this instead of auto-detect)
     pub tag: Option<String>,
 
+    /// Scope used to resolve `{{tag}}` when no explicit `tag` is set.
+    ///
+    /// Options: "repo" (default), "chapter"
+    ///
+    /// - repo: one tag for the whole book, taken from the configured branch
+    /// - chapter: nearest tag to the last commit touching each chapter
+    #[serde(rename = "tag-scope")]
+    pub tag_scope: Option<TagScope>,
+
+    /// Where the book-wide `{{tag}}` comes from.
+    ///
+    /// Options: "branch" (default), "repo"
+    ///
+    /// - branch: nearest tag reachable from `branch`
+    /// - repo: newest tag in the repository, reachable or not
+    #[serde(rename = "tag-source")]
+    pub tag_source: Option<TagSource>,
+
+    /// Field `{{tag}}` falls back to when no tag is found.
+    ///
+    /// Options: "text" (default), "branch", "hash"
+    #[serde(rename = "no-tag-fallback")]
+    pub no_tag_fallback: Option<NoTagFallback>,
+
+    /// Text shown for a missing tag with `no-tag-fallback = "text"`.
+    ///
+    /// Default: `"-"`.
+    #[serde(rename = "no-tag-text")]
+    pub no_tag_text: Option<String>,
+
+    /// Only add a footer to chapters changed since the resolved tag, using
+    /// `changed-since-tag-message` as its template and the `gitinfo-changed` class.
+    ///
+    /// Default: false
+    #[serde(rename = "changed-since-tag")]
+    pub changed_since_tag: Option<bool>,
+
+    /// Footer template used in `changed-since-tag` mode.
+    ///
+    /// Default: "updated since {{tag}}"
+    #[serde(rename = "changed-since-tag-message")]
+    pub changed_since_tag_message: Option<String>,
+
+    /// Add the `gitinfo-stale` class to footers of chapters not updated for
+    /// more than `stale-days` days.
+    ///
+    /// Default: false
+    #[serde(rename = "staleness-classes")]
+    pub staleness_classes: Option<bool>,
+
+    /// Age in days after which a chapter counts as stale.
+    ///
+    /// Default: 180
+    #[serde(rename = "stale-days")]
+    pub stale_days: Option<u32>,
+
+    /// Leave chapters whose source file is not tracked by Git untouched.
+    ///
+    /// Default: `false`
+    #[serde(rename = "skip-untracked")]
+    pub skip_untracked: Option<bool>,
+
+    /// Header/footer template for chapters whose file has no commits yet,
+    /// rendered with the `gitinfo-untracked` class. Ignored with `skip-untracked`.
+    ///
+    /// Default: `"uncommitted"`
+    #[serde(rename = "untracked-text")]
+    pub untracked_text: Option<String>,
+
+    /// Pass values substituted into templates through an allowlist that keeps
+    /// only the links, badges and dates the preprocessor builds itself.
+    /// Free text is HTML-escaped either way.
+    ///
+    /// Default: `true`
+    pub sanitize: Option<bool>,
+
+    /// Insert only the blank lines Markdown needs around injected HTML
+    /// (one on each side) instead of the default extra spacing.
+    ///
+    /// Default: `false`
+    #[serde(rename = "compact-output")]
+    pub compact_output: Option<bool>,
+
+    /// Shrink the injected HTML: collapse whitespace in header, footer and
+    /// contributors markup and shorten the inline styles.
+    ///
+    /// Default: `false`
+    pub minify: Option<bool>,
+
+    /// Line endings of chapters with injected blocks.
+    ///
+    /// Options: "preserve" (default), "lf", "crlf"
+    ///
+    /// - preserve: match the chapter's dominant line ending, so CRLF files
+    ///   don't end up with mixed endings
+    /// - lf / crlf: convert the whole chapter
+    #[serde(rename = "line-ending")]
+    pub line_ending: Option<LineEnding>,
+
+    /// Re-parse each chapter after injection and warn when the footer is not
+    /// a top-level HTML block (e.g. swallowed by a trailing list).
+    ///
+    /// Default: `false`
+    #[serde(rename = "validate-markdown")]
+    pub validate_markdown: Option<bool>,
+
+    /// Footers kept in printed output: "each" (default), "first", "last", "none".
+    ///
+    /// Implemented with `@media print` rules in the generated `theme/gitinfo.css`.
+    #[serde(rename = "footer-print")]
+    pub footer_print: Option<FooterPrint>,
+
+    /// Chapter titles that get no footer, e.g. `["Changelog", "License"]`.
+    /// Useful when paths are unstable; matched exactly against the chapter name.
+    #[serde(rename = "exclude-titles")]
+    pub exclude_titles: Option<Vec<String>>,
+
+    /// Match `exclude-titles` case-insensitively.
+    ///
+    /// Default: `false`
+    #[serde(rename = "exclude-titles-ignore-case")]
+    pub exclude_titles_ignore_case: Option<bool>,
+
+    /// Give the book's landing page no footer. The landing page is the
+    /// top-level `README.md` (or `index.md`), else the first chapter.
+    ///
+    /// Default: `false`
+    #[serde(rename = "skip-index")]
+    pub skip_index: Option<bool>,
+
     /// CSS option provides a hyperlink to the respective branch and commit  
     /// in the footer
     ///
@@ -179,12 +785,69 @@ pub struct GitInfoConfig {
     /// Default: `false`.
     pub hyperlink: Option<bool>,
 
+    /// Link the commit hash. Default: the value of `hyperlink`.
+    #[serde(rename = "link-hash")]
+    pub link_hash: Option<bool>,
+
+    /// Render `{{branch}}` as a pill badge coloured by forge (adds
+    /// `theme/gitinfo.css`). Default: `false`.
+    #[serde(rename = "branch-badge")]
+    pub branch_badge: Option<bool>,
+
+    /// Link the branch name. Default: the value of `hyperlink`.
+    #[serde(rename = "link-branch")]
+    pub link_branch: Option<bool>,
+
+    /// Link the tag. Default: the value of `hyperlink`.
+    #[serde(rename = "link-tag")]
+    pub link_tag: Option<bool>,
+
+    /// Forge whose URL scheme links follow, regardless of the host name
+    /// (e.g. a self-hosted GitLab on a custom domain).
+    ///
+    /// Options: "github", "gitlab", "gitea", "bitbucket", "azure"
+    /// Default: detected from `CI_SERVER_URL` (GitLab CI) or the host name.
+    pub forge: Option<Forge>,
+
+    /// URL template for commit links, replacing the forge's scheme.
+    /// Placeholders: `{base}`, `{path}` (repository path on the host) and
+    /// `{hash}`; unknown ones are left as written.
+    #[serde(rename = "commit-url-template")]
+    pub commit_url_template: Option<String>,
+
+    /// URL template for tag links; as `commit-url-template` with `{tag}`.
+    #[serde(rename = "tag-url-template")]
+    pub tag_url_template: Option<String>,
+
+    /// URL template for branch links; as `commit-url-template` with `{branch}`.
+    #[serde(rename = "branch-url-template")]
+    pub branch_url_template: Option<String>,
+
+    /// URL template for `{{source}}`; as `commit-url-template` with `{rev}`
+    /// (branch or `pin-ref`) and `{file}` (chapter path in the repository).
+    #[serde(rename = "source-url-template")]
+    pub source_url_template: Option<String>,
+
+    /// URL template for `{{blame}}`; as `source-url-template`.
+    #[serde(rename = "blame-url-template")]
+    pub blame_url_template: Option<String>,
+
+    /// Where the commit hash links to when `hyperlink` is on.
+    ///
+    /// Options: "commit" (default), "tag"
+    ///
+    /// - commit: the commit page
+    /// - tag: the release/tag page when the chapter has a tag, else the commit page
+    #[serde(rename = "primary-link")]
+    pub primary_link: Option<PrimaryLink>,
+
     /// Git Contributor switch
     pub contributors: Option<bool>,
 
-    /// Optional title for the contributors block.
+    /// Optional title for the contributors block. Repository placeholders
+    /// such as `{{repo-name}}` or `{{tag}}` are substituted.
     ///
-    /// Default: "Contributors:"
+    /// Default: "Contributors"
     #[serde(rename = "contributors-title")]
     pub contributors_title: Option<String>,
 
@@ -196,11 +859,12 @@ pub struct GitInfoConfig {
 
     /// Where to source contributors from.
     ///
-    /// Options: "git" (default), "file", "inline"
+    /// Options: "git" (default), "file", "inline", "env"
     ///
     /// - git: derive from `git shortlog -sne --all`
     /// - file: read from CONTRIBUTORS.md (or contributors-file)
     /// - inline: only `{% contributors a b %}` tokens are used
+    /// - env: read from an environment variable (see contributors-env)
     #[serde(rename = "contributors-source")]
     pub contributors_source: Option<ContributorsSource>,
 
@@ -209,6 +873,53 @@ pub struct GitInfoConfig {
     #[serde(rename = "contributors-file")]
     pub contributors_file: Option<String>,
 
+    /// Fail the build instead of warning when contributors-file is missing.
+    /// Default: false
+    #[serde(rename = "contributors-file-required")]
+    pub contributors_file_required: Option<bool>,
+
+    /// Extra mailmap for the `git` contributors source, relative to the book
+    /// root. Git always applies the repository's own `.mailmap`; this one is
+    /// read in addition and wins where both map an identity.
+    ///
+    /// Default: none
+    #[serde(rename = "mailmap-file")]
+    pub mailmap_file: Option<String>,
+
+    /// Also list people named in `Co-authored-by:` trailers (git source
+    /// only); each trailer counts as one commit.
+    ///
+    /// Default: `false`
+    #[serde(rename = "contributors-include-coauthors")]
+    pub contributors_include_coauthors: Option<bool>,
+
+    /// Whose commits `{% contributors %}` lists with the `git` source:
+    /// the whole book's, or only those touching the page's own file.
+    ///
+    /// Default: `"book"`
+    #[serde(rename = "contributors-scope")]
+    pub contributors_scope: Option<ContributorsScope>,
+
+    /// Handlebars file (relative to the book root) replacing the bundled
+    /// contributors template. A missing or invalid file falls back to the
+    /// bundled one with a warning.
+    ///
+    /// Default: none
+    #[serde(rename = "contributors-template")]
+    pub contributors_template: Option<String>,
+
+    /// Environment variable read when contributors-source = "env".
+    /// Its value is a comma- or newline-separated list of usernames.
+    /// Default: "GITINFO_CONTRIBUTORS"
+    #[serde(rename = "contributors-env")]
+    pub contributors_env: Option<String>,
+
+    /// How e-mail addresses in `Name <email>` lines of the contributors file are shown.
+    ///
+    /// Options: "hide" (default), "show", "link" (`mailto:` link)
+    #[serde(rename = "contributors-emails")]
+    pub contributors_emails: Option<ContributorsEmails>,
+
     /// List of contributor author names to exclude.
     ///
     /// Matches against the git author name (treated as GitHub username).
@@ -218,10 +929,80 @@ pub struct GitInfoConfig {
     #[serde(rename = "contributors-exclude")]
     pub contributors_exclude: Option<Vec<String>>,
 
+    /// Also exclude the local Git identity (`git config user.name`, and the
+    /// username behind a GitHub noreply `user.email`) from contributors.
+    ///
+    /// Default: false
+    #[serde(rename = "exclude-self")]
+    pub exclude_self: Option<bool>,
+
+    /// Ordering of contributors when contributors-source = "git".
+    ///
+    /// Options: "name" (default), "stable" (commit count desc, then name)
+    ///
+    /// Superseded by `contributors-sort`, which wins when both are set.
+    #[serde(rename = "contributors-order")]
+    pub contributors_order: Option<ContributorsOrder>,
+
+    /// Sort order of contributors when contributors-source = "git", applied
+    /// before `contributors-max-visible` cuts the list.
+    ///
+    /// Options: "name" (default), "commits", "first-seen"
+    ///
+    /// - name: case-insensitive by username
+    /// - commits: commit count descending, then name
+    /// - first-seen: order of each contributor's first commit, oldest first
+    ///
+    /// With `contributors-show-counts` the default is "commits".
+    #[serde(rename = "contributors-sort")]
+    pub contributors_sort: Option<ContributorsSort>,
+
+    /// Show each contributor's commit count, e.g. "alice (42)", when
+    /// contributors-source = "git".
+    ///
+    /// Default: false
+    #[serde(rename = "contributors-show-counts")]
+    pub contributors_show_counts: Option<bool>,
+
+    /// CSS size (width and height) of contributor avatars, e.g. "48px".
+    /// Default: "32px"
+    #[serde(rename = "contributors-avatar-size")]
+    pub contributors_avatar_size: Option<String>,
+
+    /// CSS gap between contributor avatars, e.g. "4px".
+    /// Default: "8px"
+    #[serde(rename = "contributors-avatar-gap")]
+    pub contributors_avatar_gap: Option<String>,
+
     /// Maximum number of contributor avatars shown before collapsing into a "Show all" expander.
     /// Default: 24
     #[serde(rename = "contributors-max-visible")]
     pub contributors_max_visible: Option<usize>,
+
+    /// Maximum number of contributors listed inside the "Show all" expander.
+    /// Anyone beyond `max-visible + max-hidden` is summarised as "and N others".
+    /// Default: unlimited
+    #[serde(rename = "contributors-max-hidden")]
+    pub contributors_max_hidden: Option<usize>,
+
+    /// Text placed between contributor entries.
+    ///
+    /// Default: "" (entries are spaced by CSS)
+    #[serde(rename = "contributors-separator")]
+    pub contributors_separator: Option<String>,
+
+    /// Label of the "Show all" expander; `{n}` is replaced by the number of hidden contributors.
+    ///
+    /// Default: "{n}+"
+    #[serde(rename = "contributors-more-text")]
+    pub contributors_more_text: Option<String>,
+
+    /// Omit the contributors block entirely (title included) when no contributors remain
+    /// after exclusions.
+    ///
+    /// Default: true
+    #[serde(rename = "contributors-hide-if-empty")]
+    pub contributors_hide_if_empty: Option<bool>,
 }
 
 /// Load and deserialize the `[preprocessor.gitinfo]` table from `book.toml`.
@@ -264,6 +1045,56 @@ pub fn load_config(ctx: &PreprocessorContext) -> Result<GitInfoConfig, Error> {
         .ok_or_else(|| Error::msg("Missing or invalid [preprocessor.gitinfo] config"))
 }
 
+/// Merge a TOML snippet (`--config-override`) onto `[preprocessor.gitinfo]`.
+///
+/// Keys are relative to the gitinfo table. Tables are merged key by key, so
+/// `message.footer = "…"` keeps a configured `message.header`; any other
+/// value replaces the configured one.
+///
+/// # Example
+///
+/// ```
+/// use mdbook_preprocessor::config::Config;
+/// use mdbook_gitinfo::config::apply_override;
+///
+/// let mut config = Config::default();
+/// apply_override(&mut config, r#"template = "{{hash}}""#).unwrap();
+/// let template: Option<String> = config.get("preprocessor.gitinfo.template").unwrap();
+/// assert_eq!(template.as_deref(), Some("{{hash}}"));
+/// ```
+pub fn apply_override(config: &mut Config, snippet: &str) -> Result<(), Error> {
+    let table: toml::Table =
+        toml::from_str(snippet).map_err(|e| Error::msg(format!("invalid config override: {e}")))?;
+    set_leaves(config, "preprocessor.gitinfo", table)
+}
+
+fn set_leaves(config: &mut Config, prefix: &str, table: toml::Table) -> Result<(), Error> {
+    for (key, value) in table {
+        let index = format!("{prefix}.{key}");
+        match value {
+            toml::Value::Table(inner) => set_leaves(config, &index, inner)?,
+            value => config.set(&index, value)?,
+        }
+    }
+    Ok(())
+}
+
+/// Renderers listed under `renderers` in the `[preprocessor.gitinfo]` table
+/// of the `book.toml` at `path`, or `["html"]` when the file or key is missing.
+///
+/// Used to answer `mdbook-gitinfo supports <renderer>`, which gets no context.
+pub fn supported_renderers(path: &Path) -> Vec<String> {
+    Config::from_disk(path)
+        .ok()
+        .and_then(|c| {
+            c.get::<GitInfoConfig>("preprocessor.gitinfo")
+                .ok()
+                .flatten()
+        })
+        .and_then(|c| c.renderers)
+        .unwrap_or_else(|| vec!["html".to_string()])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -281,6 +1112,33 @@ mod tests {
         PreprocessorContext::new(PathBuf::from("."), config, "html".to_string())
     }
 
+    #[test]
+    fn override_merges_onto_book_config() {
+        let mut ctx = ctx(r#"
+            template = "old"
+            message = { header = "H", footer = "F" }
+        "#);
+        apply_override(
+            &mut ctx.config,
+            "template = \"new\"\nmessage.footer = \"F2\"\nhyperlink = true",
+        )
+        .unwrap();
+
+        let c = load_config(&ctx).unwrap();
+        assert_eq!(c.template.as_deref(), Some("new"));
+        assert_eq!(c.hyperlink, Some(true));
+        let message = c.message.unwrap();
+        assert_eq!(message.header.as_deref(), Some("H"));
+        assert_eq!(message.footer.as_deref(), Some("F2"));
+    }
+
+    #[test]
+    fn override_must_be_valid_toml() {
+        let mut config = Config::default();
+        let err = apply_override(&mut config, "template = ").unwrap_err();
+        assert!(err.to_string().contains("invalid config override"));
+    }
+
     #[test]
     fn parses_legacy_align() {
         let c = load_config(&ctx(r#"align = "left""#)).unwrap();
@@ -313,6 +1171,132 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_align_with_every_placement() {
+        let c = load_config(&ctx(
+            r#"align = { header = "left", footer = "right", both = "center" }"#,
+        ))
+        .unwrap();
+        let Some(AlignSetting::Split {
+            header,
+            footer,
+            both,
+        }) = c.align
+        else {
+            panic!("expected Split");
+        };
+        assert_eq!(header.as_deref(), Some("left"));
+        assert_eq!(footer.as_deref(), Some("right"));
+        assert_eq!(both.as_deref(), Some("center"));
+    }
+
+    fn margin(toml: &str) -> MarginConfig {
+        load_config(&ctx(toml))
+            .unwrap_or_else(|e| panic!("{toml}: {e}"))
+            .margin
+            .expect("margin set")
+    }
+
+    fn lengths(vs: &[CssLength]) -> Vec<&str> {
+        vs.iter().map(|v| v.0.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_margin_single_values() {
+        let m = margin(r#"margin.both = "1em""#);
+        assert!(matches!(m.both, Some(MarginSetting::One(v)) if v.0 == "1em"));
+
+        let m = margin(r#"margin.header = "0 1em 2em""#);
+        assert!(matches!(m.header, Some(MarginSetting::One(v)) if v.0 == "0 1em 2em"));
+
+        // A bare zero, as in CSS `margin: 0`.
+        let m = margin("margin.footer = 0\nmargin.both = 0.0");
+        assert!(matches!(m.footer, Some(MarginSetting::One(v)) if v.0 == "0"));
+        assert!(matches!(m.both, Some(MarginSetting::One(v)) if v.0 == "0"));
+    }
+
+    #[test]
+    fn rejects_bare_non_zero_margins() {
+        for toml in [
+            "margin.both = 1.5",
+            "margin.header = 2",
+            r#"margin.footer = ["1em", 3]"#,
+            "margin.header.top = 1",
+        ] {
+            assert!(load_config(&ctx(toml)).is_err(), "{toml}");
+        }
+    }
+
+    #[test]
+    fn parses_margin_arrays_of_one_to_four_values() {
+        for (toml, expected) in [
+            (r#"margin.both = ["1em"]"#, vec!["1em"]),
+            (r#"margin.both = ["1em", "2em"]"#, vec!["1em", "2em"]),
+            (
+                r#"margin.both = ["1em", "2em", "3em"]"#,
+                vec!["1em", "2em", "3em"],
+            ),
+            (
+                r#"margin.both = [0, "2em", 0, "1em"]"#,
+                vec!["0", "2em", "0", "1em"],
+            ),
+        ] {
+            match margin(toml).both {
+                Some(MarginSetting::Quad(vs)) => assert_eq!(lengths(&vs), expected, "{toml}"),
+                other => panic!("{toml}: expected Quad, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_margin_sides_tables() {
+        let m = margin(
+            r#"
+            margin.header.top = "5em"
+            margin.footer = { bottom = "2em", left = 0 }
+            "#,
+        );
+        let Some(MarginSetting::Sides { top, bottom, .. }) = m.header else {
+            panic!("expected Sides");
+        };
+        assert_eq!(top, Some("5em".into()));
+        assert_eq!(bottom, None);
+        let Some(MarginSetting::Sides { bottom, left, .. }) = m.footer else {
+            panic!("expected Sides");
+        };
+        assert_eq!(bottom, Some("2em".into()));
+        assert_eq!(left, Some("0".into()));
+    }
+
+    #[test]
+    fn parses_margin_split_table_form() {
+        let m = margin(
+            r#"
+            [margin]
+            both   = ["1em"]
+            header = ["0", "0", "1.25em", "0"]
+            footer = { top = "2em" }
+            "#,
+        );
+        assert!(matches!(m.both, Some(MarginSetting::Quad(_))));
+        assert!(matches!(m.header, Some(MarginSetting::Quad(ref v)) if v.len() == 4));
+        assert!(matches!(m.footer, Some(MarginSetting::Sides { .. })));
+    }
+
+    #[test]
+    fn supported_renderers_reads_book_toml() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let book = dir.path().join("book.toml");
+        assert_eq!(supported_renderers(&book), ["html"]);
+
+        std::fs::write(
+            &book,
+            "[preprocessor.gitinfo]\nrenderers = [\"html\", \"epub\"]\n",
+        )
+        .unwrap();
+        assert_eq!(supported_renderers(&book), ["html", "epub"]);
+    }
+
     #[test]
     fn message_resolution_parses() {
         let c = load_config(&ctx(r#"
@@ -0,0 +1,186 @@
+//! Per-chapter overrides from a YAML frontmatter block.
+//!
+//! Only the small subset needed for the `gitinfo` key is understood:
+//!
+//! ```yaml
+//! ---
+//! gitinfo: { footer: "Reviewed {{date}}" }
+//! ---
+//! ```
+//!
+//! or the block form
+//!
+//! ```yaml
+//! ---
+//! gitinfo:
+//!   header: "Draft"
+//!   footer: 'Reviewed {{date}}'
+//! ---
+//! ```
+
+/// Templates a chapter sets for itself under the `gitinfo` key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChapterOverrides {
+    pub header: Option<String>,
+    pub footer: Option<String>,
+}
+
+/// Split a leading `---` frontmatter block from `content`.
+///
+/// Returns the block's body and the content after the closing `---`/`...` line.
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let body = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some((&body[..offset], &body[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parse the `gitinfo` entry of a frontmatter body.
+///
+/// `Ok(None)` when the block has no `gitinfo` key (it belongs to someone
+/// else); `Err` when the key is there but its value cannot be read.
+pub fn parse_overrides(yaml: &str) -> Result<Option<ChapterOverrides>, String> {
+    let mut lines = yaml.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some(rest) = line.strip_prefix("gitinfo:") else {
+            continue;
+        };
+        let rest = rest.trim();
+        let mut pairs = Vec::new();
+        if rest.is_empty() {
+            // Block mapping: the indented lines that follow.
+            while let Some(next) = lines.next_if(|l| l.starts_with([' ', '\t']) || l.is_empty()) {
+                if !next.trim().is_empty() {
+                    pairs.push(next.trim());
+                }
+            }
+        } else {
+            let inner = rest
+                .strip_prefix('{')
+                .and_then(|r| r.strip_suffix('}'))
+                .ok_or_else(|| format!("expected a mapping after 'gitinfo:', got '{rest}'"))?;
+            pairs.extend(split_outside_quotes(inner, ',').into_iter().map(str::trim));
+        }
+
+        let mut overrides = ChapterOverrides::default();
+        for pair in pairs.into_iter().filter(|p| !p.is_empty()) {
+            let (key, value) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("expected 'key: value', got '{pair}'"))?;
+            let value = parse_scalar(value.trim())?;
+            match key.trim() {
+                "header" => overrides.header = Some(value),
+                "footer" => overrides.footer = Some(value),
+                other => return Err(format!("unknown key '{other}'")),
+            }
+        }
+        return Ok(Some(overrides));
+    }
+    Ok(None)
+}
+
+/// Split `s` on `sep`, ignoring separators inside quotes.
+fn split_outside_quotes(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) if c == sep => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// A double-quoted, single-quoted or plain YAML scalar.
+fn parse_scalar(raw: &str) -> Result<String, String> {
+    if let Some(inner) = raw.strip_prefix('"') {
+        let inner = inner
+            .strip_suffix('"')
+            .ok_or_else(|| format!("unterminated string {raw}"))?;
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                other => return Err(format!("unsupported escape \\{}", other.unwrap_or(' '))),
+            }
+        }
+        Ok(out)
+    } else if let Some(inner) = raw.strip_prefix('\'') {
+        inner
+            .strip_suffix('\'')
+            .map(|s| s.replace("''", "'"))
+            .ok_or_else(|| format!("unterminated string {raw}"))
+    } else {
+        Ok(raw.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_leading_block_only() {
+        let (yaml, rest) = split_frontmatter("---\na: 1\n---\n# Title\n").unwrap();
+        assert_eq!(yaml, "a: 1\n");
+        assert_eq!(rest, "# Title\n");
+
+        assert!(split_frontmatter("# Title\n---\na: 1\n---\n").is_none());
+        assert!(split_frontmatter("---\nunterminated\n").is_none());
+    }
+
+    #[test]
+    fn parses_flow_and_block_mappings() {
+        let flow = parse_overrides(r#"gitinfo: { footer: "Reviewed {{date}}, ok", header: x }"#);
+        assert_eq!(
+            flow.unwrap().unwrap(),
+            ChapterOverrides {
+                header: Some("x".into()),
+                footer: Some("Reviewed {{date}}, ok".into()),
+            }
+        );
+
+        let block = parse_overrides("title: T\ngitinfo:\n  footer: 'It''s {{hash}}'\nother: 1\n");
+        assert_eq!(
+            block.unwrap().unwrap().footer.as_deref(),
+            Some("It's {{hash}}")
+        );
+    }
+
+    #[test]
+    fn missing_key_is_none_and_bad_value_is_error() {
+        assert_eq!(parse_overrides("title: T\n"), Ok(None));
+        assert!(parse_overrides("gitinfo: footer").is_err());
+        assert!(parse_overrides(r#"gitinfo: { footer: "open }"#).is_err());
+        assert!(parse_overrides("gitinfo: { colour: red }").is_err());
+    }
+}
@@ -3,19 +3,95 @@
 //! This module provides helpers for interacting with a Git repository,
 //! primarily to extract metadata (commit hash, tag, timestamp, branch).
 //!
-//! All functions return [`mdbook::errors::Error`] on failure so they can be
-//! integrated directly into the `mdbook` preprocessor error flow.
+//! [`get_git_output`] reports failures as a [`GitError`]; the other helpers
+//! return [`mdbook::errors::Error`] on failure so they can be integrated
+//! directly into the `mdbook` preprocessor error flow.
 //!
 //! See also:
 //! - [`get_git_output`] — Run arbitrary Git commands and capture output.
 //! - [`verify_branch`] — Convenience wrapper to check branch existence.
+//! - [`latest_tag_for_branch`] — Newest tag reachable from a branch.
+//! - [`get_contributor_usernames_from_shortlog`] — Contributor usernames from history.
 
+use crate::config::ContributorsSort;
 use mdbook_preprocessor::errors::Error;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::OsStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+/// Environment variable naming the Git executable when `git-binary` is unset.
+pub const GIT_BINARY_ENV: &str = "GITINFO_GIT_BINARY";
+
+/// Pick the Git executable: `configured` first, then `env`, then `"git"`.
+/// Empty values count as unset.
+pub fn resolve_git_binary(configured: Option<&str>, env: Option<&str>) -> String {
+    [configured, env]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or("git")
+        .to_string()
+}
+
+/// Why a Git command produced no output, as returned by [`get_git_output`].
+///
+/// Converts into [`Error`] with `?`, so callers that only report the
+/// failure need not match on it.
+#[derive(Debug)]
+pub enum GitError {
+    /// The Git executable could not be found.
+    NotFound { binary: String },
+    /// Git could not be started for another reason (e.g. permissions).
+    Spawn(std::io::Error),
+    /// Git ran but exited unsuccessfully. `code` is `None` when it was
+    /// killed by a signal.
+    NonZero {
+        binary: String,
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// Git's output was not valid UTF-8.
+    Utf8(std::string::FromUtf8Error),
+}
+
+impl std::fmt::Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitError::NotFound { binary } => write!(f, "Git command failed: `{binary}` not found"),
+            GitError::Spawn(e) => write!(f, "Git command failed: {e}"),
+            GitError::NonZero {
+                binary,
+                command,
+                code,
+                stderr,
+            } => {
+                match code {
+                    Some(c) => write!(f, "`{binary} {command}` failed (exit code {c})")?,
+                    None => write!(f, "`{binary} {command}` failed (terminated by signal)")?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, ": {stderr}")?;
+                }
+                Ok(())
+            }
+            GitError::Utf8(e) => write!(f, "Git output is not valid UTF-8: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GitError::Spawn(e) => Some(e),
+            GitError::Utf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 /// Run a Git command and return the trimmed `stdout` output as a [`String`].
 ///
 /// This is the central utility for invoking Git. It is used by the
@@ -40,14 +116,16 @@ use std::process::{Command, Stdio};
 /// # Returns
 ///
 /// * `Ok(String)` — Trimmed `stdout` output from Git.
-/// * `Err(Error)` — If Git fails to launch or exits with non-zero status.
+/// * `Err(GitError)` — If Git fails to launch, exits with non-zero status or
+///   prints invalid UTF-8.
 ///
 /// # Errors
 ///
-/// This function returns an [`Error`] if:
-/// - The `git` binary is missing or fails to start.
-/// - The command returns a non-zero exit code.
-/// - The output cannot be decoded as UTF-8.
+/// - [`GitError::NotFound`] / [`GitError::Spawn`] — the Git executable
+///   (`GITINFO_GIT_BINARY`, else `git`) is missing or fails to start.
+/// - [`GitError::NonZero`] — the command returns a non-zero exit code; the
+///   message names the arguments and includes Git's trimmed `stderr`.
+/// - [`GitError::Utf8`] — the output cannot be decoded as UTF-8.
 ///
 /// # Example
 ///
@@ -59,22 +137,90 @@ use std::process::{Command, Stdio};
 ///     .expect("failed to get commit hash");
 /// println!("Current short commit hash: {}", hash);
 /// ```
-pub fn get_git_output<I, S>(args: I, dir: &Path) -> Result<String, Error>
+pub fn get_git_output<I, S>(args: I, dir: &Path) -> Result<String, GitError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    run_git(&SystemGit::default().binary, args, dir)
+}
+
+/// [`get_git_output`] with an explicit executable.
+fn run_git<I, S>(binary: &str, args: I, dir: &Path) -> Result<String, GitError>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let output = Command::new("git")
-        .args(args)
+    let args: Vec<S> = args.into_iter().collect();
+    let output = Command::new(binary)
+        .args(&args)
         .current_dir(dir)
         .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .output()
-        .map_err(|e| Error::msg(format!("Git command failed: {e}")))?;
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => GitError::NotFound {
+                binary: binary.to_string(),
+            },
+            _ => GitError::Spawn(e),
+        })?;
 
     if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        let stdout = String::from_utf8(output.stdout).map_err(GitError::Utf8)?;
+        Ok(stdout.trim().to_string())
     } else {
-        Err(Error::msg("Git command returned non-zero exit code"))
+        let argv: Vec<_> = args.iter().map(|a| a.as_ref().to_string_lossy()).collect();
+        Err(GitError::NonZero {
+            binary: binary.to_string(),
+            command: argv.join(" "),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// Abstraction over how Git commands are executed.
+///
+/// [`SystemGit`] runs a Git executable; tests can substitute an
+/// implementation that records or fakes the calls.
+pub trait GitRunner {
+    /// Run Git with `args` in `dir` and return trimmed `stdout`.
+    fn output(&self, args: &[&str], dir: &Path) -> Result<String, Error>;
+}
+
+/// Default [`GitRunner`] that invokes a Git executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemGit {
+    /// Executable to run, looked up on `PATH` unless it is a path.
+    pub binary: String,
+}
+
+impl SystemGit {
+    /// Run `binary` as Git.
+    pub fn new(binary: impl Into<String>) -> Self {
+        SystemGit {
+            binary: binary.into(),
+        }
+    }
+
+    /// Run the `git-binary` setting `configured`, resolved with
+    /// [`resolve_git_binary`] against `GITINFO_GIT_BINARY`.
+    pub fn from_config(configured: Option<&str>) -> Self {
+        let env = std::env::var(GIT_BINARY_ENV).ok();
+        Self::new(resolve_git_binary(configured, env.as_deref()))
+    }
+}
+
+impl Default for SystemGit {
+    /// `GITINFO_GIT_BINARY`, otherwise `git`.
+    fn default() -> Self {
+        Self::from_config(None)
+    }
+}
+
+impl GitRunner for SystemGit {
+    fn output(&self, args: &[&str], dir: &Path) -> Result<String, Error> {
+        Ok(run_git(&self.binary, args, dir)?)
     }
 }
 
@@ -82,7 +228,7 @@ where
 ///
 /// Internally runs:
 /// ```text
-/// git rev-parse --verify <branch>
+/// git rev-parse --verify --quiet refs/heads/<branch>
 /// ```
 ///
 /// This is a thin wrapper around [`get_git_output`], returning `true` if the
@@ -110,31 +256,418 @@ where
 /// }
 /// ```
 pub fn verify_branch(branch: &str, dir: &Path) -> bool {
-    get_git_output(["rev-parse", "--verify", branch], dir).is_ok()
+    verify_branch_with(&SystemGit::default(), branch, dir)
+}
+
+/// Same as [`verify_branch`], but runs Git through the given [`GitRunner`].
+pub fn verify_branch_with<R: GitRunner + ?Sized>(runner: &R, branch: &str, dir: &Path) -> bool {
+    let rev = branch_ref(branch);
+    runner
+        .output(&["rev-parse", "--verify", "--quiet", &rev], dir)
+        .is_ok()
+}
+
+/// CI variables naming the branch being built, most specific first.
+///
+/// `GITHUB_HEAD_REF` is only set for pull requests, where `GITHUB_REF_NAME`
+/// names the merge ref instead.
+pub const CI_BRANCH_VARS: &[&str] = &[
+    "GITHUB_HEAD_REF",
+    "GITHUB_REF_NAME",
+    "CI_MERGE_REQUEST_SOURCE_BRANCH_NAME",
+    "CI_COMMIT_BRANCH",
+    "BITBUCKET_BRANCH",
+];
+
+/// Resolve `branch = "auto"` to the first candidate that exists locally:
+///
+/// 1. the CI branch variables in [`CI_BRANCH_VARS`], read through `env`
+/// 2. `git symbolic-ref --short HEAD` (fails on a detached HEAD)
+/// 3. the branch `refs/remotes/origin/HEAD` points to
+/// 4. `git config init.defaultBranch`
+///
+/// and `"main"` when none does.
+pub fn resolve_auto_branch<R: GitRunner + ?Sized>(
+    runner: &R,
+    dir: &Path,
+    env: impl Fn(&str) -> Option<String>,
+) -> String {
+    let symref = |name: &str| {
+        runner
+            .output(&["symbolic-ref", "--quiet", "--short", name], dir)
+            .ok()
+    };
+    let ci = CI_BRANCH_VARS.iter().filter_map(|k| env(k));
+    let head = std::iter::once_with(|| symref("HEAD")).flatten();
+    let origin = std::iter::once_with(|| {
+        symref("refs/remotes/origin/HEAD")
+            .and_then(|r| r.strip_prefix("origin/").map(str::to_string))
+    })
+    .flatten();
+    let configured =
+        std::iter::once_with(|| runner.output(&["config", "init.defaultBranch"], dir).ok())
+            .flatten();
+
+    ci.chain(head)
+        .chain(origin)
+        .chain(configured)
+        .map(|b| b.trim().to_string())
+        .find(|b| !b.is_empty() && verify_branch_with(runner, b, dir))
+        .unwrap_or_else(|| "main".to_string())
+}
+
+/// Fully qualify a branch name as `refs/heads/<branch>`.
+///
+/// A bare name like `main` is ambiguous when a tag of the same name exists,
+/// and Git resolves tags before branches. Names that already start with
+/// `refs/` are returned unchanged.
+pub fn branch_ref(branch: &str) -> String {
+    if branch.starts_with("refs/") {
+        branch.to_string()
+    } else {
+        format!("refs/heads/{branch}")
+    }
+}
+
+/// Check that `rev` (a branch, tag, SHA or other revision) names a commit.
+///
+/// Internally runs:
+/// ```text
+/// git rev-parse --verify --quiet <rev>^{commit}
+/// ```
+pub fn verify_rev<R: GitRunner + ?Sized>(runner: &R, rev: &str, dir: &Path) -> bool {
+    let spec = format!("{rev}^{{commit}}");
+    runner
+        .output(&["rev-parse", "--verify", "--quiet", &spec], dir)
+        .is_ok()
+}
+
+/// Run `git log -1 --format=<format> refs/heads/<branch> -- <path>` and return
+/// the output for the last commit on `branch` touching `path`.
+pub fn last_commit_format<R: GitRunner + ?Sized>(
+    runner: &R,
+    format: &str,
+    branch: &str,
+    path: &str,
+    dir: &Path,
+) -> Result<String, Error> {
+    last_commit_format_at(runner, format, &branch_ref(branch), path, dir)
+}
+
+/// Same as [`last_commit_format`], but starting from an arbitrary revision
+/// (`rev` is passed to Git as-is, e.g. a pinned release SHA).
+pub fn last_commit_format_at<R: GitRunner + ?Sized>(
+    runner: &R,
+    format: &str,
+    rev: &str,
+    path: &str,
+    dir: &Path,
+) -> Result<String, Error> {
+    last_commit_format_with(runner, format, rev, path, dir, LogOptions::default())
+}
+
+/// History options for the per-path `git log -1` queries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Pass `--follow` so history is traced across renames of the path.
+    pub follow: bool,
+    /// Pass `--no-merges` so the last non-merge commit is reported.
+    pub no_merges: bool,
+}
+
+/// Same as [`last_commit_format_at`], with the flags selected by `opts`.
+pub fn last_commit_format_with<R: GitRunner + ?Sized>(
+    runner: &R,
+    format: &str,
+    rev: &str,
+    path: &str,
+    dir: &Path,
+    opts: LogOptions,
+) -> Result<String, Error> {
+    let fmt = format!("--format={format}");
+    let mut args = vec!["log", "-1"];
+    if opts.follow {
+        args.push("--follow");
+    }
+    if opts.no_merges {
+        args.push("--no-merges");
+    }
+    args.extend([fmt.as_str(), rev, "--", path]);
+    runner.output(&args, dir)
+}
+
+/// `git log` format of [`CommitFields`]: one NUL-separated slot per field.
+pub const COMMIT_FIELDS_FORMAT: &str = "%h%x00%H%x00%cI%x00%an%x00%cn%x00%ae%x00%s";
+
+/// Metadata of the last commit touching a path, read in a single `git log`.
+///
+/// Fields are empty strings when Git prints nothing (e.g. a file that was
+/// never committed).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommitFields {
+    /// `%h` — abbreviated hash
+    pub short: String,
+    /// `%H` — full hash
+    pub long: String,
+    /// `%cI` — committer date, strict ISO 8601
+    pub date: String,
+    /// `%an` — author name
+    pub author: String,
+    /// `%cn` — committer name
+    pub committer: String,
+    /// `%ae` — author e-mail
+    pub email: String,
+    /// `%s` — subject line
+    pub subject: String,
+}
+
+impl CommitFields {
+    /// Split output of [`COMMIT_FIELDS_FORMAT`]; missing slots stay empty.
+    pub fn parse(raw: &str) -> Self {
+        let mut f = raw.split('\0').map(str::to_string);
+        let mut next = || f.next().unwrap_or_default();
+        CommitFields {
+            short: next(),
+            long: next(),
+            date: next(),
+            author: next(),
+            committer: next(),
+            email: next(),
+            subject: next(),
+        }
+    }
+}
+
+/// All [`CommitFields`] of the last commit touching `path` at `rev`.
+///
+/// Internally runs:
+/// ```text
+/// git log -1 --format=%h%x00%H%x00%cI%x00%an%x00%cn%x00%ae%x00%s <rev> -- <path>
+/// ```
+pub fn last_commit_fields<R: GitRunner + ?Sized>(
+    runner: &R,
+    rev: &str,
+    path: &str,
+    dir: &Path,
+) -> Result<CommitFields, Error> {
+    last_commit_fields_as(
+        runner,
+        COMMIT_FIELDS_FORMAT,
+        rev,
+        path,
+        dir,
+        LogOptions::default(),
+    )
+}
+
+/// Same as [`last_commit_fields`] with a custom `format` using the slot layout
+/// of [`COMMIT_FIELDS_FORMAT`] (e.g. with unneeded slots left empty) and the
+/// history [`LogOptions`].
+pub fn last_commit_fields_as<R: GitRunner + ?Sized>(
+    runner: &R,
+    format: &str,
+    rev: &str,
+    path: &str,
+    dir: &Path,
+    opts: LogOptions,
+) -> Result<CommitFields, Error> {
+    last_commit_format_with(runner, format, rev, path, dir, opts)
+        .map(|raw| CommitFields::parse(&raw))
+}
+
+/// Whether `path` is tracked in the index of the repository at `dir`.
+///
+/// Internally runs:
+/// ```text
+/// git ls-files --error-unmatch -- <path>
+/// ```
+///
+/// Any failure (untracked path, not a repository, Git missing) yields `false`.
+pub fn is_tracked<R: GitRunner + ?Sized>(runner: &R, path: &str, dir: &Path) -> bool {
+    runner
+        .output(&["ls-files", "--error-unmatch", "--", path], dir)
+        .is_ok()
+}
+
+/// Whether `path` has uncommitted changes in the work tree or index.
+///
+/// Internally runs:
+/// ```text
+/// git status --porcelain -- <path>
+/// ```
+///
+/// Any output (modified, staged or untracked) counts as dirty; a failing
+/// Git call counts as clean.
+pub fn is_dirty<R: GitRunner + ?Sized>(runner: &R, path: &str, dir: &Path) -> bool {
+    runner
+        .output(&["status", "--porcelain", "--", path], dir)
+        .is_ok_and(|out| !out.trim().is_empty())
+}
+
+/// Top-level directory of the work tree containing `dir`.
+///
+/// Internally runs:
+/// ```text
+/// git rev-parse --show-toplevel
+/// ```
+///
+/// Returns `None` outside a work tree or when Git is unavailable.
+pub fn toplevel<R: GitRunner + ?Sized>(runner: &R, dir: &Path) -> Option<PathBuf> {
+    runner
+        .output(&["rev-parse", "--show-toplevel"], dir)
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Tag pointing exactly at `commit`, if any.
+///
+/// Internally runs:
+/// ```text
+/// git describe --tags --exact-match <commit>
+/// ```
+pub fn exact_tag<R: GitRunner + ?Sized>(runner: &R, commit: &str, dir: &Path) -> Option<String> {
+    runner
+        .output(&["describe", "--tags", "--exact-match", commit], dir)
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+/// Contributor names for the local Git identity, used by `exclude-self`.
+///
+/// Internally runs:
+/// ```text
+/// git config user.name
+/// git config user.email
+/// ```
+///
+/// Yields `user.name` and, for a GitHub noreply `user.email`, the username
+/// it encodes. Unset values are skipped, so the result may be empty.
+pub fn self_identities<R: GitRunner + ?Sized>(runner: &R, dir: &Path) -> Vec<String> {
+    let config = |key: &str| {
+        runner
+            .output(&["config", key], dir)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let mut names: Vec<String> = config("user.name").into_iter().collect();
+    if let Some(username) = config("user.email").and_then(|e| github_username_from_email(&e))
+        && !names.contains(&username)
+    {
+        names.push(username);
+    }
+    names
 }
 
 /// Return the latest tag name, preferring tags reachable from the given branch's HEAD.
 /// Falls back to global (by creator date) when describe fails.
 /// Returns "No tags found" if not tag found
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+/// use mdbook_gitinfo::latest_tag_for_branch;
+///
+/// let tag = latest_tag_for_branch("main", Path::new("."));
+/// // Either a tag name or the "No tags found" sentinel; never empty.
+/// assert!(!tag.trim().is_empty());
+/// ```
 pub fn latest_tag_for_branch(branch: &str, dir: &std::path::Path) -> String {
+    latest_tag_for_branch_with(&SystemGit::default(), branch, dir)
+}
+
+/// Same as [`latest_tag_for_branch`], but runs Git through the given [`GitRunner`].
+pub fn latest_tag_for_branch_with<R: GitRunner + ?Sized>(
+    runner: &R,
+    branch: &str,
+    dir: &Path,
+) -> String {
     // Prefer a tag reachable from branch HEAD
-    if let Ok(t) = get_git_output(["describe", "--tags", "--abbrev=0", branch], dir) {
-        if !t.trim().is_empty() {
-            return t;
-        }
+    let rev = branch_ref(branch);
+    if let Ok(t) = runner.output(&["describe", "--tags", "--abbrev=0", &rev], dir)
+        && !t.trim().is_empty()
+    {
+        return t;
     }
 
     // Fallback: newest tag by creator date
-    match get_git_output(["tag", "--sort=-creatordate"], dir) {
-        Ok(list) => {
-            if let Some(first) = list.lines().find(|l| !l.trim().is_empty()) {
-                return first.trim().to_string();
-            }
-        }
-        Err(_) => {}
+    latest_tag_in_repo(runner, dir)
+}
+
+/// Return the newest tag in the repository by creator date, whether or not
+/// any branch reaches it (e.g. tags on release branches). Tags created in the
+/// same second are ordered by version, highest first.
+/// Returns "No tags found" if there are none.
+///
+/// Internally runs:
+/// ```text
+/// git tag --sort=-v:refname --sort=-creatordate
+/// ```
+pub fn latest_tag_in_repo<R: GitRunner + ?Sized>(runner: &R, dir: &Path) -> String {
+    // With several `--sort` keys the last one is the primary key.
+    runner
+        .output(&["tag", "--sort=-v:refname", "--sort=-creatordate"], dir)
+        .ok()
+        .and_then(|list| {
+            list.lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "No tags found".to_string())
+}
+
+/// Return the nearest tag reachable from a specific commit.
+///
+/// Internally runs:
+/// ```text
+/// git describe --tags --abbrev=0 <commit>
+/// ```
+///
+/// Unlike [`latest_tag_for_branch`] there is no global fallback: a commit
+/// that predates every tag yields `None`.
+pub fn nearest_tag_for_commit<R: GitRunner + ?Sized>(
+    runner: &R,
+    commit: &str,
+    dir: &Path,
+) -> Option<String> {
+    if commit.trim().is_empty() {
+        return None;
     }
+    runner
+        .output(&["describe", "--tags", "--abbrev=0", commit], dir)
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+}
 
-    "No tags found".to_string()
+/// Return the paths changed on `branch` since `since` (i.e. in `since..branch`).
+///
+/// Internally runs:
+/// ```text
+/// git log --format= --name-only --relative <since>..refs/heads/<branch>
+/// ```
+///
+/// Paths are relative to `dir`, matching the paths the preprocessor passes
+/// to its per-chapter `git log` calls.
+pub fn files_changed_since<R: GitRunner + ?Sized>(
+    runner: &R,
+    since: &str,
+    branch: &str,
+    dir: &Path,
+) -> Result<HashSet<String>, Error> {
+    let range = format!("{since}..{}", branch_ref(branch));
+    let raw = runner.output(
+        &["log", "--format=", "--name-only", "--relative", &range],
+        dir,
+    )?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
 }
 
 /// Extract a GitHub username from a GitHub noreply email address.
@@ -183,11 +716,84 @@ fn is_plausible_github_username(u: &str) -> bool {
 /// 2) Otherwise, fallback to extracting a username from GitHub noreply email.
 ///
 /// Returns a unique, sorted list of inferred GitHub usernames.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+/// use mdbook_gitinfo::get_contributor_usernames_from_shortlog;
+///
+/// let users = get_contributor_usernames_from_shortlog(Path::new("."))
+///     .expect("running inside a git checkout");
+/// // Usernames are de-duplicated and sorted.
+/// assert!(users.windows(2).all(|w| w[0] < w[1]));
+/// ```
 pub fn get_contributor_usernames_from_shortlog(dir: &Path) -> Result<Vec<String>, Error> {
-    let raw = get_git_output(["shortlog", "-sne", "--all"], dir)
+    get_contributor_usernames_from_shortlog_with(&SystemGit::default(), dir)
+}
+
+/// Same as [`get_contributor_usernames_from_shortlog`], but runs Git through
+/// the given [`GitRunner`].
+pub fn get_contributor_usernames_from_shortlog_with<R: GitRunner + ?Sized>(
+    runner: &R,
+    dir: &Path,
+) -> Result<Vec<String>, Error> {
+    Ok(
+        get_contributor_counts_from_shortlog(runner, dir, None, None)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect(),
+    )
+}
+
+/// Like [`get_contributor_usernames_from_shortlog`], but keeps the commit
+/// count of each username (summed over all identities mapping to it).
+///
+/// Git applies the repository's `.mailmap`; `mailmap_file` adds another
+/// one, passed as `-c mailmap.file=<path>`. With `path`, only commits
+/// touching it count. Returned in username order.
+pub fn get_contributor_counts_from_shortlog<R: GitRunner + ?Sized>(
+    runner: &R,
+    dir: &Path,
+    mailmap_file: Option<&Path>,
+    path: Option<&str>,
+) -> Result<Vec<(String, usize)>, Error> {
+    let mut args = mailmap_args(mailmap_file);
+    args.extend(["shortlog", "-sne", "--all"].map(String::from));
+    args.extend(pathspec_args(path));
+    let raw = run_args(runner, &args, dir)
         .map_err(|e| Error::msg(format!("unable to get contributors: {e}")))?;
+    Ok(parse_shortlog(&raw))
+}
+
+/// Leading `-c mailmap.file=<path>` arguments when a mailmap other than
+/// the repository's `.mailmap` is configured; Git reads both.
+fn mailmap_args(mailmap_file: Option<&Path>) -> Vec<String> {
+    match mailmap_file {
+        Some(path) => vec!["-c".to_string(), format!("mailmap.file={}", path.display())],
+        None => Vec::new(),
+    }
+}
+
+/// Trailing `-- <path>` arguments limiting history to one file.
+fn pathspec_args(path: Option<&str>) -> Vec<String> {
+    path.map(|p| vec!["--".to_string(), p.to_string()])
+        .unwrap_or_default()
+}
 
-    let mut set = BTreeSet::<String>::new();
+/// Run owned `args` through `runner`.
+fn run_args<R: GitRunner + ?Sized>(
+    runner: &R,
+    args: &[String],
+    dir: &Path,
+) -> Result<String, Error> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    runner.output(&args, dir)
+}
+
+/// Parse `git shortlog -sne` output into `(username, commit count)` pairs.
+fn parse_shortlog(raw: &str) -> Vec<(String, usize)> {
+    let mut counts = BTreeMap::<String, usize>::new();
 
     for line in raw.lines() {
         // Expected: "  42  Name <email>"
@@ -198,7 +804,7 @@ pub fn get_contributor_usernames_from_shortlog(dir: &Path) -> Result<Vec<String>
 
         // Split count from rest
         let mut parts = line.splitn(2, char::is_whitespace);
-        let _count_str = parts.next().unwrap_or("");
+        let count: usize = parts.next().unwrap_or("").parse().unwrap_or(0);
         let rest = parts.next().unwrap_or("").trim();
         if rest.is_empty() {
             continue;
@@ -212,33 +818,672 @@ pub fn get_contributor_usernames_from_shortlog(dir: &Path) -> Result<Vec<String>
             (rest, None)
         };
 
-        // 1) Prefer author name (if plausible)
-        if !name.is_empty() && is_plausible_github_username(name) {
-            set.insert(name.to_string());
-            continue;
+        if let Some(u) = contributor_username(name, email) {
+            *counts.entry(u).or_default() += count;
+        }
+    }
+
+    counts.into_iter().collect()
+}
+
+/// Username for an author identity: the name if it looks like a GitHub
+/// username, else the username of a GitHub noreply e-mail.
+fn contributor_username(name: &str, email: Option<&str>) -> Option<String> {
+    // 1) Prefer author name (if plausible)
+    if !name.is_empty() && is_plausible_github_username(name) {
+        return Some(name.to_string());
+    }
+
+    // 2) Fallback to email-derived username (GitHub noreply only)
+    email
+        .and_then(github_username_from_email)
+        .filter(|u| is_plausible_github_username(u))
+}
+
+/// Co-authors from `Co-authored-by:` trailers, as `(username, commit count)`
+/// pairs in username order.
+///
+/// Internally runs:
+/// ```text
+/// git log --all --format=%(trailers:key=Co-authored-by,valueonly)
+/// ```
+///
+/// Trailer values (`Name <email>`) map to usernames as in
+/// [`get_contributor_counts_from_shortlog`]; mailmaps are not applied.
+/// With `path`, only commits touching it count.
+pub fn get_coauthor_counts<R: GitRunner + ?Sized>(
+    runner: &R,
+    dir: &Path,
+    path: Option<&str>,
+) -> Result<Vec<(String, usize)>, Error> {
+    let mut args: Vec<String> = [
+        "log",
+        "--all",
+        "--format=%(trailers:key=Co-authored-by,valueonly)",
+    ]
+    .map(String::from)
+    .to_vec();
+    args.extend(pathspec_args(path));
+    let raw = run_args(runner, &args, dir)
+        .map_err(|e| Error::msg(format!("unable to get co-authors: {e}")))?;
+    Ok(parse_coauthors(&raw))
+}
+
+/// Parse one `Name <email>` trailer value per line into counts.
+fn parse_coauthors(raw: &str) -> Vec<(String, usize)> {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for line in raw.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let (name, email) = match line.rsplit_once('<') {
+            Some((n, e)) => (n.trim(), Some(e.trim_end_matches('>').trim())),
+            None => (line, None),
+        };
+        if let Some(u) = contributor_username(name, email) {
+            *counts.entry(u).or_default() += 1;
         }
+    }
+    counts.into_iter().collect()
+}
 
-        // 2) Fallback to email-derived username (GitHub noreply only)
-        if let Some(email) = email {
-            if let Some(u) = github_username_from_email(email) {
-                if is_plausible_github_username(&u) {
-                    set.insert(u);
+/// Add `extra` counts to `counts`, keeping username order.
+pub fn merge_contributor_counts(
+    counts: Vec<(String, usize)>,
+    extra: Vec<(String, usize)>,
+) -> Vec<(String, usize)> {
+    let mut merged: BTreeMap<String, usize> = counts.into_iter().collect();
+    for (name, n) in extra {
+        *merged.entry(name).or_default() += n;
+    }
+    merged.into_iter().collect()
+}
+
+/// Contributor usernames in the order of their first commit, oldest first.
+///
+/// Internally runs:
+/// ```text
+/// git log --all --reverse --format=%aN%x00%aE
+/// ```
+///
+/// Identities are mailmapped and map to usernames as in
+/// [`get_contributor_counts_from_shortlog`], whose `path` filter applies too.
+pub fn get_contributors_first_seen<R: GitRunner + ?Sized>(
+    runner: &R,
+    dir: &Path,
+    mailmap_file: Option<&Path>,
+    path: Option<&str>,
+) -> Result<Vec<String>, Error> {
+    let mut args = mailmap_args(mailmap_file);
+    args.extend(["log", "--all", "--reverse", "--format=%aN%x00%aE"].map(String::from));
+    args.extend(pathspec_args(path));
+    let raw = run_args(runner, &args, dir)
+        .map_err(|e| Error::msg(format!("unable to get contributors: {e}")))?;
+    Ok(parse_first_seen(&raw))
+}
+
+/// Parse `name\0email` lines (oldest first) into unique usernames.
+fn parse_first_seen(raw: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    raw.lines()
+        .filter_map(|line| {
+            let (name, email) = line.split_once('\0').unwrap_or((line, ""));
+            contributor_username(name.trim(), Some(email.trim()))
+        })
+        .filter(|u| seen.insert(u.clone()))
+        .collect()
+}
+
+/// Order `(username, commit count)` pairs for `contributors-sort`.
+///
+/// `first_seen` is only read for [`ContributorsSort::FirstSeen`]; names
+/// missing from it follow the others, sorted by name.
+pub fn sort_contributors(
+    counts: Vec<(String, usize)>,
+    first_seen: &[String],
+    sort: ContributorsSort,
+) -> Vec<String> {
+    let by_name = |a: &String, b: &String| {
+        a.to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(b))
+    };
+    match sort {
+        ContributorsSort::Commits => stable_contributor_order(counts),
+        ContributorsSort::Name => {
+            let mut names: Vec<String> = counts.into_iter().map(|(name, _)| name).collect();
+            names.sort_by(by_name);
+            names
+        }
+        ContributorsSort::FirstSeen => {
+            let rank: BTreeMap<&str, usize> = first_seen
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.as_str(), i))
+                .collect();
+            let mut names: Vec<String> = counts.into_iter().map(|(name, _)| name).collect();
+            names.sort_by(|a, b| {
+                let (ra, rb) = (rank.get(a.as_str()), rank.get(b.as_str()));
+                match (ra, rb) {
+                    (Some(x), Some(y)) => x.cmp(y),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => by_name(a, b),
                 }
-            }
+            });
+            names
         }
     }
+}
 
-    Ok(set.into_iter().collect())
+/// Order contributors by commit count (descending), then name (ascending).
+///
+/// The result depends only on the input set, not on the order Git listed it
+/// in, so builds are reproducible across Git versions.
+pub fn stable_contributor_order(mut counts: Vec<(String, usize)>) -> Vec<String> {
+    counts.sort_by(|(an, ac), (bn, bc)| bc.cmp(ac).then_with(|| an.cmp(bn)));
+    counts.into_iter().map(|(name, _)| name).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::git_in;
+    use std::cell::RefCell;
     use std::path::PathBuf;
 
+    /// Records every argument vector and directory and answers with a canned result.
+    #[derive(Default)]
+    struct RecordingGit {
+        calls: RefCell<Vec<Vec<String>>>,
+        dirs: RefCell<Vec<PathBuf>>,
+    }
+
+    impl GitRunner for RecordingGit {
+        fn output(&self, args: &[&str], dir: &Path) -> Result<String, Error> {
+            self.dirs.borrow_mut().push(dir.to_path_buf());
+            self.calls
+                .borrow_mut()
+                .push(args.iter().map(|a| a.to_string()).collect());
+            Ok("ok".to_string())
+        }
+    }
+
+    /// Answers `git config <key>` from a fixed table.
+    struct ConfigGit(&'static [(&'static str, &'static str)]);
+
+    impl GitRunner for ConfigGit {
+        fn output(&self, args: &[&str], _dir: &Path) -> Result<String, Error> {
+            match args {
+                ["config", key] => self
+                    .0
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.to_string())
+                    .ok_or_else(|| Error::msg("unset")),
+                _ => Err(Error::msg("unexpected call")),
+            }
+        }
+    }
+
+    #[test]
+    fn self_identities_reads_name_and_noreply_username() {
+        let git = ConfigGit(&[
+            ("user.name", "Jane Doe"),
+            ("user.email", "123+janedoe@users.noreply.github.com"),
+        ]);
+        assert_eq!(
+            self_identities(&git, Path::new(".")),
+            ["Jane Doe", "janedoe"]
+        );
+
+        let git = ConfigGit(&[("user.name", "janedoe"), ("user.email", "jd@example.com")]);
+        assert_eq!(self_identities(&git, Path::new(".")), ["janedoe"]);
+
+        assert!(self_identities(&ConfigGit(&[]), Path::new(".")).is_empty());
+    }
+
+    #[test]
+    fn branch_ref_qualifies_bare_names_only() {
+        assert_eq!(branch_ref("main"), "refs/heads/main");
+        assert_eq!(branch_ref("feature/x"), "refs/heads/feature/x");
+        assert_eq!(branch_ref("refs/heads/dev"), "refs/heads/dev");
+    }
+
+    #[test]
+    fn verify_branch_uses_refs_heads_form() {
+        let git = RecordingGit::default();
+        assert!(verify_branch_with(&git, "main", Path::new(".")));
+
+        let calls = git.calls.borrow();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0],
+            ["rev-parse", "--verify", "--quiet", "refs/heads/main"]
+        );
+    }
+
+    #[test]
+    fn last_commit_format_uses_refs_heads_form() {
+        let git = RecordingGit::default();
+        let out = last_commit_format(&git, "%h", "main", "src/intro.md", Path::new("."));
+        assert_eq!(out.unwrap(), "ok");
+
+        let calls = git.calls.borrow();
+        assert_eq!(
+            calls[0],
+            [
+                "log",
+                "-1",
+                "--format=%h",
+                "refs/heads/main",
+                "--",
+                "src/intro.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_rev_peels_to_commit() {
+        let git = RecordingGit::default();
+        assert!(verify_rev(&git, "v1.0.0", Path::new(".")));
+
+        let calls = git.calls.borrow();
+        assert_eq!(
+            calls[0],
+            ["rev-parse", "--verify", "--quiet", "v1.0.0^{commit}"]
+        );
+    }
+
+    #[test]
+    fn last_commit_format_at_passes_rev_verbatim() {
+        let git = RecordingGit::default();
+        last_commit_format_at(&git, "%H", "abc123", "src/intro.md", Path::new(".")).unwrap();
+
+        let calls = git.calls.borrow();
+        assert_eq!(
+            calls[0],
+            ["log", "-1", "--format=%H", "abc123", "--", "src/intro.md"]
+        );
+    }
+
+    #[test]
+    fn is_tracked_uses_error_unmatch() {
+        let git = RecordingGit::default();
+        assert!(is_tracked(&git, "src/intro.md", Path::new(".")));
+
+        let calls = git.calls.borrow();
+        assert_eq!(
+            calls[0],
+            ["ls-files", "--error-unmatch", "--", "src/intro.md"]
+        );
+    }
+
+    #[test]
+    fn last_commit_fields_uses_one_nul_separated_log() {
+        let git = RecordingGit::default();
+        let fields =
+            last_commit_fields(&git, "refs/heads/main", "src/a.md", Path::new(".")).unwrap();
+
+        assert_eq!(
+            git.calls.borrow()[0],
+            [
+                "log",
+                "-1",
+                "--format=%h%x00%H%x00%cI%x00%an%x00%cn%x00%ae%x00%s",
+                "refs/heads/main",
+                "--",
+                "src/a.md"
+            ]
+        );
+        assert_eq!(fields.short, "ok");
+        assert_eq!(fields.subject, "");
+    }
+
+    #[test]
+    fn log_option_flags_precede_format() {
+        let git = RecordingGit::default();
+        let opts = LogOptions {
+            follow: true,
+            no_merges: true,
+        };
+        last_commit_format_with(&git, "%H", "HEAD", "b.md", Path::new("."), opts).unwrap();
+        assert_eq!(
+            git.calls.borrow()[0],
+            [
+                "log",
+                "-1",
+                "--follow",
+                "--no-merges",
+                "--format=%H",
+                "HEAD",
+                "--",
+                "b.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn commit_fields_split_on_nul() {
+        let fields = CommitFields::parse("abc\0abcdef\0D\0Ann\0Cal\0a@x\0Fix it");
+        assert_eq!(fields.long, "abcdef");
+        assert_eq!(fields.committer, "Cal");
+        assert_eq!(fields.subject, "Fix it");
+    }
+
+    #[test]
+    fn commit_fields_empty_output_is_all_empty() {
+        assert_eq!(CommitFields::parse(""), CommitFields::default());
+    }
+
+    #[test]
+    fn is_dirty_uses_porcelain_status() {
+        let git = RecordingGit::default();
+        assert!(is_dirty(&git, "src/intro.md", Path::new(".")));
+        assert_eq!(
+            git.calls.borrow()[0],
+            ["status", "--porcelain", "--", "src/intro.md"]
+        );
+    }
+
+    #[test]
+    fn exact_tag_uses_exact_match() {
+        let git = RecordingGit::default();
+        assert_eq!(
+            exact_tag(&git, "abc123", Path::new(".")).as_deref(),
+            Some("ok")
+        );
+        assert_eq!(
+            git.calls.borrow()[0],
+            ["describe", "--tags", "--exact-match", "abc123"]
+        );
+    }
+
+    #[test]
+    fn toplevel_runs_show_toplevel_in_dir() {
+        let git = RecordingGit::default();
+        assert_eq!(toplevel(&git, Path::new("book")), Some(PathBuf::from("ok")));
+
+        assert_eq!(git.calls.borrow()[0], ["rev-parse", "--show-toplevel"]);
+        assert_eq!(git.dirs.borrow()[0], Path::new("book"));
+    }
+
+    #[test]
+    fn parse_shortlog_sums_identities_per_username() {
+        let raw = "    5\talice <alice@example.com>\n\
+                   \x20   2\tBob Smith <123+bob@users.noreply.github.com>\n\
+                   \x20   3\talice <alice@work.example>\n";
+        assert_eq!(
+            parse_shortlog(raw),
+            vec![("alice".to_string(), 8), ("bob".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn coauthors_merge_into_shortlog_counts() {
+        let raw = "carol <carol@example.com>\n\n\
+                   Bob Smith <123+bob@users.noreply.github.com>\n\
+                   carol <carol@example.com>\n\
+                   Not A Handle <x@example.com>\n";
+        let coauthors = parse_coauthors(raw);
+        assert_eq!(
+            coauthors,
+            vec![("bob".to_string(), 1), ("carol".to_string(), 2)]
+        );
+        let shortlog = vec![("alice".to_string(), 4), ("bob".to_string(), 2)];
+        assert_eq!(
+            merge_contributor_counts(shortlog, coauthors),
+            vec![
+                ("alice".to_string(), 4),
+                ("bob".to_string(), 3),
+                ("carol".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn stable_order_is_independent_of_input_order() {
+        let lines = [
+            "    3\tcarol <carol@example.com>",
+            "    7\talice <alice@example.com>",
+            "    3\tbob <bob@example.com>",
+            "    1\tdave <dave@example.com>",
+        ];
+        let forward = lines.join("\n");
+        let reversed: Vec<&str> = lines.iter().rev().copied().collect();
+        let reversed = reversed.join("\n");
+
+        let a = stable_contributor_order(parse_shortlog(&forward));
+        let b = stable_contributor_order(parse_shortlog(&reversed));
+
+        assert_eq!(a, vec!["alice", "bob", "carol", "dave"]);
+        assert_eq!(a, b);
+    }
+
+    fn synthetic_counts() -> Vec<(String, usize)> {
+        [("carol", 3), ("Alice", 7), ("bob", 3), ("dave", 1)]
+            .map(|(n, c)| (n.to_string(), c))
+            .to_vec()
+    }
+
+    #[test]
+    fn sort_contributors_by_commits() {
+        let sorted = sort_contributors(synthetic_counts(), &[], ContributorsSort::Commits);
+        assert_eq!(sorted, ["Alice", "bob", "carol", "dave"]);
+    }
+
+    #[test]
+    fn sort_contributors_by_name_ignores_case() {
+        let sorted = sort_contributors(synthetic_counts(), &[], ContributorsSort::Name);
+        assert_eq!(sorted, ["Alice", "bob", "carol", "dave"]);
+
+        let counts = vec![("bob".to_string(), 1), ("Bob".to_string(), 1)];
+        let sorted = sort_contributors(counts, &[], ContributorsSort::Name);
+        assert_eq!(sorted, ["Bob", "bob"]);
+    }
+
+    #[test]
+    fn sort_contributors_by_first_seen() {
+        let first_seen = parse_first_seen(
+            "dave\0dave@example.com\nA Person\x001+carol@users.noreply.github.com\ndave\0d@x\nAlice\0a@x\n",
+        );
+        assert_eq!(first_seen, ["dave", "carol", "Alice"]);
+
+        let sorted =
+            sort_contributors(synthetic_counts(), &first_seen, ContributorsSort::FirstSeen);
+        // bob is missing from the log, so comes last.
+        assert_eq!(sorted, ["dave", "carol", "Alice", "bob"]);
+    }
+
+    /// A repository on `main` with one commit.
+    fn repo_with_commit() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        git_in(dir.path(), &["init", "-q", "-b", "main"]);
+        git_in(dir.path(), &["commit", "-q", "--allow-empty", "-m", "init"]);
+        dir
+    }
+
+    #[test]
+    fn auto_branch_prefers_ci_variable_on_detached_head() {
+        let dir = repo_with_commit();
+        let git = |args: &[&str]| git_in(dir.path(), args);
+        git(&["branch", "feature"]);
+        git(&["checkout", "-q", "--detach"]);
+
+        let ci = |vars: &'static [(&'static str, &'static str)]| {
+            move |k: &str| {
+                vars.iter()
+                    .find(|(v, _)| *v == k)
+                    .map(|(_, b)| b.to_string())
+            }
+        };
+        let resolve = |env| resolve_auto_branch(&SystemGit::default(), dir.path(), env);
+        assert_eq!(resolve(ci(&[("GITHUB_REF_NAME", "feature")])), "feature");
+        // Pull requests: the head branch wins over the merge ref.
+        assert_eq!(
+            resolve(ci(&[
+                ("GITHUB_HEAD_REF", "feature"),
+                ("GITHUB_REF_NAME", "1/merge")
+            ])),
+            "feature"
+        );
+        // Unknown and empty values fall through to the default.
+        assert_eq!(
+            resolve(ci(&[("GITHUB_HEAD_REF", ""), ("CI_COMMIT_BRANCH", "gone")])),
+            "main"
+        );
+    }
+
+    #[test]
+    fn auto_branch_uses_local_refs_without_ci() {
+        let dir = repo_with_commit();
+        let git = |args: &[&str]| git_in(dir.path(), args);
+        let resolve = || resolve_auto_branch(&SystemGit::default(), dir.path(), |_| None);
+
+        git(&["checkout", "-q", "-b", "dev"]);
+        assert_eq!(resolve(), "dev");
+
+        git(&["branch", "trunk"]);
+        git(&["update-ref", "refs/remotes/origin/trunk", "HEAD"]);
+        git(&[
+            "symbolic-ref",
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/trunk",
+        ]);
+        git(&["checkout", "-q", "--detach"]);
+        assert_eq!(resolve(), "trunk");
+
+        git(&["symbolic-ref", "--delete", "refs/remotes/origin/HEAD"]);
+        git(&["config", "init.defaultBranch", "dev"]);
+        assert_eq!(resolve(), "dev");
+
+        git(&["config", "init.defaultBranch", "missing"]);
+        assert_eq!(resolve(), "main");
+    }
+
+    #[test]
+    fn repo_tag_source_sees_tags_off_the_branch() {
+        let dir = repo_with_commit();
+        let git = |args: &[&str]| git_in(dir.path(), args);
+        let commit = |msg: &str| git(&["commit", "-q", "--allow-empty", "-m", msg]);
+        assert_eq!(
+            latest_tag_in_repo(&SystemGit::default(), dir.path()),
+            "No tags found"
+        );
+
+        git(&["tag", "v1.0"]);
+        git(&["checkout", "-q", "-b", "release"]);
+        commit("release fix");
+        git(&["tag", "v1.1"]);
+        git(&["checkout", "-q", "main"]);
+        commit("main work");
+
+        assert_eq!(latest_tag_for_branch("main", dir.path()), "v1.0");
+        assert_eq!(
+            latest_tag_in_repo(&SystemGit::default(), dir.path()),
+            "v1.1"
+        );
+        assert_eq!(latest_tag_for_branch("release", dir.path()), "v1.1");
+    }
+
     #[test]
     fn returns_error_on_invalid_git_command() {
         let result = get_git_output(["non-existent-command"], &PathBuf::from("."));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn error_names_arguments_and_stderr() {
+        let dir = tempfile::TempDir::new().unwrap();
+        get_git_output(["init", "-q"], dir.path()).unwrap();
+
+        let err = get_git_output(["rev-parse", "--verify", "no-such-branch"], dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("git rev-parse --verify no-such-branch"),
+            "{err}"
+        );
+        assert!(err.contains("fatal"), "{err}");
+    }
+
+    #[test]
+    fn missing_binary_is_not_found() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = run_git("definitely-not-a-git-binary", ["status"], dir.path()).unwrap_err();
+        assert!(
+            matches!(&err, GitError::NotFound { binary } if binary == "definitely-not-a-git-binary"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn system_git_runs_its_own_binary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = SystemGit::new("definitely-not-a-git-binary")
+            .output(&["status"], dir.path())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("definitely-not-a-git-binary"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn non_zero_error_names_the_configured_binary() {
+        let err = GitError::NonZero {
+            binary: "/opt/git/bin/git".to_string(),
+            command: "rev-parse HEAD".to_string(),
+            code: Some(128),
+            stderr: String::new(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "`/opt/git/bin/git rev-parse HEAD` failed (exit code 128)"
+        );
+    }
+
+    #[test]
+    fn failed_command_is_non_zero_with_code_and_stderr() {
+        let dir = repo_with_commit();
+        let err =
+            get_git_output(["rev-parse", "--verify", "no-such-branch"], dir.path()).unwrap_err();
+        match err {
+            GitError::NonZero {
+                binary,
+                command,
+                code,
+                stderr,
+            } => {
+                assert_eq!(binary, "git");
+                assert_eq!(command, "rev-parse --verify no-such-branch");
+                assert_eq!(code, Some(128));
+                assert!(stderr.contains("fatal"), "{stderr}");
+            }
+            other => panic!("expected NonZero, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_output_is_utf8_error() {
+        let dir = repo_with_commit();
+        std::fs::write(dir.path().join("bytes.bin"), b"\xff\xfe").unwrap();
+        let blob = get_git_output(["hash-object", "-w", "bytes.bin"], dir.path()).unwrap();
+        let err = get_git_output(["cat-file", "blob", &blob], dir.path()).unwrap_err();
+        assert!(matches!(err, GitError::Utf8(_)), "{err:?}");
+
+        let converted: Error = err.into();
+        assert!(converted.to_string().contains("UTF-8"), "{converted}");
+    }
+
+    #[test]
+    fn git_binary_prefers_config_over_env() {
+        assert_eq!(resolve_git_binary(None, None), "git");
+        assert_eq!(
+            resolve_git_binary(None, Some("/opt/git/bin/git")),
+            "/opt/git/bin/git"
+        );
+        assert_eq!(
+            resolve_git_binary(Some("git-wrapper"), Some("/opt/git/bin/git")),
+            "git-wrapper"
+        );
+        assert_eq!(resolve_git_binary(Some(" "), Some("git2")), "git2");
+    }
 }
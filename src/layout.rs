@@ -1,7 +1,17 @@
-use crate::config::{AlignSetting, GitInfoConfig, MarginConfig, MarginSetting};
+use crate::config::{
+    AlignSetting, CssLength, GitInfoConfig, MarginConfig, MarginSetting, PlacementSetting,
+    TemplateRule,
+};
+use crate::logging;
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
 
 pub fn resolve_messages(cfg: &GitInfoConfig) -> (String, String) {
-    let default = "{{date}}{{sep}}commit: {{hash}}".to_string();
+    // `{{sep}}` is the only gap in the default, so keep a space without one.
+    let default = if cfg.separator.as_deref() == Some("") {
+        "{{date}} commit: {{hash}}".to_string()
+    } else {
+        "{{date}}{{sep}}commit: {{hash}}".to_string()
+    };
     let both = cfg.message.as_ref().and_then(|m| m.both.clone());
 
     let header = cfg
@@ -23,6 +33,82 @@ pub fn resolve_messages(cfg: &GitInfoConfig) -> (String, String) {
     (header, footer)
 }
 
+/// Compiled `template-rules`, checked in declaration order.
+pub struct TemplateRules {
+    rules: Vec<(GlobMatcher, String)>,
+}
+
+impl TemplateRules {
+    /// Compile the configured rules. Invalid patterns are reported and skipped.
+    pub fn new(rules: &[TemplateRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|r| match Glob::new(&r.pattern) {
+                Ok(g) => Some((g.compile_matcher(), r.template.clone())),
+                Err(e) => {
+                    logging::warn(format_args!(
+                        "ignoring template rule with invalid pattern '{}': {e}",
+                        r.pattern
+                    ));
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Template of the first rule matching `chapter_path`, if any.
+    pub fn template_for(&self, chapter_path: &str) -> Option<&str> {
+        let path = chapter_path.replace('\\', "/");
+        self.rules
+            .iter()
+            .find(|(m, _)| m.is_match(&path))
+            .map(|(_, t)| t.as_str())
+    }
+
+    /// Every rule's template, in rule order.
+    pub fn templates(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(_, t)| t.as_str())
+    }
+}
+
+/// Compiled `include`/`exclude` chapter globs.
+pub struct ChapterFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl ChapterFilter {
+    /// Compile both lists. Invalid patterns are reported and skipped.
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        fn build(kind: &str, patterns: &[String]) -> GlobSet {
+            let mut set = GlobSetBuilder::new();
+            for p in patterns {
+                match Glob::new(p) {
+                    Ok(g) => {
+                        set.add(g);
+                    }
+                    Err(e) => {
+                        logging::warn(format_args!("ignoring invalid {kind} pattern '{p}': {e}"))
+                    }
+                }
+            }
+            set.build().unwrap_or_else(|_| GlobSet::empty())
+        }
+        Self {
+            include: (!include.is_empty()).then(|| build("include", include)),
+            exclude: build("exclude", exclude),
+        }
+    }
+
+    /// Whether `chapter_path` is decorated: not excluded, and included when
+    /// an include list is set.
+    pub fn allows(&self, chapter_path: &str) -> bool {
+        let path = chapter_path.replace('\\', "/");
+        !self.exclude.is_match(&path) && self.include.as_ref().is_none_or(|i| i.is_match(&path))
+    }
+}
+
 pub fn resolve_align(a: &Option<AlignSetting>) -> (String, String) {
     match a {
         Some(AlignSetting::One(s)) => (s.clone(), s.clone()),
@@ -33,46 +119,67 @@ pub fn resolve_align(a: &Option<AlignSetting>) -> (String, String) {
         }) => {
             let both_v = both.clone().unwrap_or_else(|| "center".to_string());
             let h = header.clone().unwrap_or_else(|| both_v.clone());
-            let f = footer.clone().unwrap_or_else(|| both_v);
+            let f = footer.clone().unwrap_or(both_v);
             (h, f)
         }
         None => ("center".into(), "center".into()),
     }
 }
 
+/// Header and footer value of a [`PlacementSetting`]; unset or blank
+/// entries use `defaults`.
+pub fn resolve_placement(
+    setting: &Option<PlacementSetting>,
+    defaults: (&str, &str),
+) -> (String, String) {
+    let (header, footer) = match setting {
+        Some(PlacementSetting::One(s)) => (Some(s.as_str()), Some(s.as_str())),
+        Some(PlacementSetting::Split {
+            header,
+            footer,
+            both,
+        }) => (
+            header.as_deref().or(both.as_deref()),
+            footer.as_deref().or(both.as_deref()),
+        ),
+        None => (None, None),
+    };
+    let pick = |v: Option<&str>, default: &str| {
+        v.map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    };
+    (pick(header, defaults.0), pick(footer, defaults.1))
+}
+
+/// Expand a margin setting to `[top, right, bottom, left]`, following CSS
+/// shorthand for 1–4 values; a single string may itself be a shorthand.
 fn margin_from_setting(ms: &MarginSetting, fallback: [&str; 4]) -> [String; 4] {
+    let shorthand = |vs: &[&str]| -> [String; 4] {
+        let [t, r, b, l] = match *vs {
+            [] => fallback,
+            [v] => [v, v, v, v],
+            [tb, rl] => [tb, rl, tb, rl],
+            [t, rl, b] => [t, rl, b, rl],
+            [t, r, b, l, ..] => [t, r, b, l],
+        };
+        [t, r, b, l].map(str::to_string)
+    };
     match ms {
-        MarginSetting::One(v) => [v.clone(), v.clone(), v.clone(), v.clone()],
-        MarginSetting::Quad(vs) => match vs.len() {
-            0 => fallback.map(|s| s.to_string()),
-            1 => {
-                let v = vs[0].clone();
-                [v.clone(), v.clone(), v.clone(), v]
-            }
-            2 => {
-                let vtb = vs[0].clone();
-                let vrl = vs[1].clone();
-                [vtb.clone(), vrl.clone(), vtb, vrl]
-            }
-            3 => {
-                let t = vs[0].clone();
-                let rl = vs[1].clone();
-                let b = vs[2].clone();
-                [t, rl.clone(), b, rl]
-            }
-            _ => [vs[0].clone(), vs[1].clone(), vs[2].clone(), vs[3].clone()],
-        },
+        MarginSetting::One(v) => shorthand(&v.0.split_whitespace().collect::<Vec<_>>()),
+        MarginSetting::Quad(vs) => shorthand(&vs.iter().map(|v| v.0.as_str()).collect::<Vec<_>>()),
         MarginSetting::Sides {
             top,
             right,
             bottom,
             left,
-        } => [
-            top.clone().unwrap_or_else(|| fallback[0].to_string()),
-            right.clone().unwrap_or_else(|| fallback[1].to_string()),
-            bottom.clone().unwrap_or_else(|| fallback[2].to_string()),
-            left.clone().unwrap_or_else(|| fallback[3].to_string()),
-        ],
+        } => {
+            let side = |v: &Option<CssLength>, i: usize| {
+                v.as_ref().map_or(fallback[i], |v| v.0.as_str()).to_string()
+            };
+            [side(top, 0), side(right, 1), side(bottom, 2), side(left, 3)]
+        }
     }
 }
 
@@ -80,22 +187,163 @@ pub fn resolve_margins(m: &Option<MarginConfig>) -> ([String; 4], [String; 4]) {
     let default_header = ["0", "0", "2em", "0"];
     let default_footer = ["0", "0", "2em", "0"];
 
+    // `both` fills whatever `header`/`footer` leave unset, including the
+    // whole block when it is not configured at all.
     let both = m.as_ref().and_then(|mm| mm.both.as_ref());
-    let base = both
-        .map(|b| margin_from_setting(b, ["0", "0", "0", "0"]))
-        .unwrap_or(["0".into(), "0".into(), "0".into(), "0".into()]);
+    let base = both.map(|b| margin_from_setting(b, ["0", "0", "0", "0"]));
+    let fallback = |default: [&str; 4]| -> [String; 4] {
+        base.clone().unwrap_or_else(|| default.map(str::to_string))
+    };
 
-    let header = m
-        .as_ref()
-        .and_then(|mm| mm.header.as_ref())
-        .map(|h| margin_from_setting(h, [&base[0], &base[1], &base[2], &base[3]]))
-        .unwrap_or_else(|| default_header.map(|s| s.to_string()));
+    let header = match m.as_ref().and_then(|mm| mm.header.as_ref()) {
+        Some(h) => {
+            let b = fallback(["0"; 4]);
+            margin_from_setting(h, [&b[0], &b[1], &b[2], &b[3]])
+        }
+        None => fallback(default_header),
+    };
 
-    let footer = m
-        .as_ref()
-        .and_then(|mm| mm.footer.as_ref())
-        .map(|f| margin_from_setting(f, [&base[0], &base[1], &base[2], &base[3]]))
-        .unwrap_or_else(|| default_footer.map(|s| s.to_string()));
+    let footer = match m.as_ref().and_then(|mm| mm.footer.as_ref()) {
+        Some(f) => {
+            let b = fallback(["0"; 4]);
+            margin_from_setting(f, [&b[0], &b[1], &b[2], &b[3]])
+        }
+        None => fallback(default_footer),
+    };
 
     (header, footer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, template: &str) -> TemplateRule {
+        TemplateRule {
+            pattern: pattern.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn margins_follow_css_shorthand() {
+        let cfg = |header: MarginSetting, footer: MarginSetting| {
+            Some(MarginConfig {
+                header: Some(header),
+                footer: Some(footer),
+                both: None,
+            })
+        };
+        let quad = |vs: &[&str]| MarginSetting::Quad(vs.iter().map(|&v| v.into()).collect());
+
+        let (header, footer) = resolve_margins(&cfg(
+            MarginSetting::One("1em 2em 3em".into()),
+            quad(&["1em", "2em", "3em"]),
+        ));
+        assert_eq!(header, ["1em", "2em", "3em", "2em"]);
+        assert_eq!(footer, header);
+
+        let (header, footer) =
+            resolve_margins(&cfg(MarginSetting::One("0 1em".into()), quad(&["1em"])));
+        assert_eq!(header, ["0", "1em", "0", "1em"]);
+        assert_eq!(footer, ["1em"; 4]);
+
+        let (header, footer) = resolve_margins(&Some(MarginConfig {
+            header: Some(MarginSetting::Sides {
+                top: Some("5em".into()),
+                right: None,
+                bottom: None,
+                left: None,
+            }),
+            footer: Some(quad(&[])),
+            both: Some(MarginSetting::One("1em".into())),
+        }));
+        assert_eq!(header, ["5em", "1em", "1em", "1em"]);
+        assert_eq!(footer, ["1em"; 4]);
+    }
+
+    #[test]
+    fn margin_both_alone_applies_to_both_blocks() {
+        let both = |setting: MarginSetting| {
+            resolve_margins(&Some(MarginConfig {
+                header: None,
+                footer: None,
+                both: Some(setting),
+            }))
+        };
+
+        let (header, footer) = both(MarginSetting::One("1em".into()));
+        assert_eq!(header, ["1em"; 4]);
+        assert_eq!(footer, ["1em"; 4]);
+
+        let (header, footer) = both(MarginSetting::One("1em 2em 3em".into()));
+        assert_eq!(header, ["1em", "2em", "3em", "2em"]);
+        assert_eq!(footer, header);
+
+        let (header, footer) = both(MarginSetting::Quad(vec![
+            "0".into(),
+            "1em".into(),
+            "2em".into(),
+        ]));
+        assert_eq!(header, ["0", "1em", "2em", "1em"]);
+        assert_eq!(footer, header);
+
+        let (header, footer) = resolve_margins(&None);
+        assert_eq!(header, ["0", "0", "2em", "0"]);
+        assert_eq!(footer, header);
+    }
+
+    #[test]
+    fn template_rules_first_match_wins() {
+        let rules = TemplateRules::new(&[rule("api/**", "api"), rule("**/*.md", "any")]);
+        assert_eq!(rules.template_for("api/client.md"), Some("api"));
+        assert_eq!(rules.template_for("guide/intro.md"), Some("any"));
+    }
+
+    #[test]
+    fn template_rules_without_match_fall_back() {
+        let rules = TemplateRules::new(&[rule("api/**", "api"), rule("[", "broken")]);
+        assert_eq!(rules.template_for("tutorial/setup.md"), None);
+        assert_eq!(rules.template_for("api\\nested\\x.md"), Some("api"));
+    }
+
+    #[test]
+    fn chapter_filter_exclude_wins_over_include() {
+        let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let all = ChapterFilter::new(&[], &strings(&["index.md"]));
+        assert!(all.allows("guide.md"));
+        assert!(!all.allows("index.md"));
+
+        let filter = ChapterFilter::new(
+            &strings(&["reference/**"]),
+            &strings(&["reference/drafts/**"]),
+        );
+        assert!(filter.allows("reference/api.md"));
+        assert!(filter.allows("reference\\nested\\deep.md"));
+        assert!(!filter.allows("reference/drafts/wip.md"));
+        assert!(!filter.allows("guide.md"));
+    }
+
+    #[test]
+    fn placement_setting_falls_back_per_block() {
+        let defaults = ("header", "footer");
+        assert_eq!(
+            resolve_placement(&None, defaults),
+            ("header".into(), "footer".into())
+        );
+        assert_eq!(
+            resolve_placement(&Some(PlacementSetting::One("div".into())), defaults),
+            ("div".into(), "div".into())
+        );
+        let split = PlacementSetting::Split {
+            header: Some(" ".into()),
+            footer: Some("aside".into()),
+            both: None,
+        };
+        assert_eq!(
+            resolve_placement(&Some(split), defaults),
+            ("header".into(), "aside".into())
+        );
+    }
+}
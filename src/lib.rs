@@ -4,15 +4,38 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "gix")]
+pub mod backend;
+mod chapters;
 pub mod config;
+mod frontmatter;
 pub mod git;
+mod layout;
+pub mod logging;
+mod metadata;
+pub mod processor;
+pub mod renderer;
+mod repo;
+pub mod summary;
 pub mod theme;
+pub mod timefmt;
+
+#[cfg(test)]
+mod test_support;
 
 #[doc(inline)]
 pub use config::GitInfoConfig;
 
 #[doc(inline)]
-pub use git::{get_git_output, verify_branch};
+pub use git::{
+    get_contributor_usernames_from_shortlog, get_git_output, latest_tag_for_branch, verify_branch,
+};
+
+#[doc(inline)]
+pub use processor::GitInfo;
+
+#[doc(inline)]
+pub use summary::build_summary;
 
 #[doc(inline)]
 pub use theme::ensure_gitinfo_assets;
@@ -0,0 +1,137 @@
+//! Diagnostics emitted by the preprocessor.
+//!
+//! Every message goes through this module so they share one prefix
+//! ([`DEFAULT_PREFIX`] unless overridden with [`set_prefix`], e.g. when the
+//! crate is embedded in another tool). Messages go to `stderr` (mdBook
+//! reserves `stdout` for the book JSON). Warnings are always shown; `info`
+//! and `debug` lines only appear when the `log-level` option asks for them.
+
+use crate::config::LogLevel;
+use std::fmt::Display;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Prefix used for every diagnostic unless [`set_prefix`] is called.
+pub const DEFAULT_PREFIX: &str = "[mdbook-gitinfo]";
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Warn as u8);
+static PREFIX: RwLock<String> = RwLock::new(String::new());
+
+/// Set the process-wide verbosity threshold.
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Current verbosity threshold.
+pub fn level() -> LogLevel {
+    match LEVEL.load(Ordering::Relaxed) {
+        x if x == LogLevel::Debug as u8 => LogLevel::Debug,
+        x if x == LogLevel::Info as u8 => LogLevel::Info,
+        _ => LogLevel::Warn,
+    }
+}
+
+/// Replace the prefix put in front of every message.
+pub fn set_prefix(prefix: impl Into<String>) {
+    if let Ok(mut p) = PREFIX.write() {
+        *p = prefix.into();
+    }
+}
+
+/// Current message prefix.
+pub fn prefix() -> String {
+    match PREFIX.read() {
+        Ok(p) if !p.is_empty() => p.clone(),
+        _ => DEFAULT_PREFIX.to_string(),
+    }
+}
+
+/// Format `msg` for output at `level`, or `None` if `threshold` filters it out.
+pub fn format_line(threshold: LogLevel, level: LogLevel, msg: impl Display) -> Option<String> {
+    if level > threshold {
+        return None;
+    }
+    let prefix = prefix();
+    Some(match level {
+        LogLevel::Warn => format!("{prefix} Warning: {msg}"),
+        LogLevel::Info | LogLevel::Debug => format!("{prefix} {msg}"),
+    })
+}
+
+fn emit(level: LogLevel, msg: impl Display) {
+    if let Some(line) = format_line(self::level(), level, msg) {
+        eprintln!("{line}");
+    }
+}
+
+/// Emit a warning (always shown).
+pub fn warn(msg: impl Display) {
+    emit(LogLevel::Warn, msg);
+}
+
+/// Emit an informational message (shown at `log-level = "info"` and above).
+pub fn info(msg: impl Display) {
+    emit(LogLevel::Info, msg);
+}
+
+/// Emit a debug message (shown at `log-level = "debug"` only).
+pub fn debug(msg: impl Display) {
+    emit(LogLevel::Debug, msg);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Held by tests that read or change the process-wide prefix.
+    static PREFIX_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn debug_lines_are_hidden_at_default_verbosity() {
+        let line = format_line(LogLevel::default(), LogLevel::Debug, "contributors(file)");
+        assert!(line.is_none());
+        let line = format_line(LogLevel::default(), LogLevel::Info, "contributors(file)");
+        assert!(line.is_none());
+    }
+
+    #[test]
+    fn debug_lines_appear_when_requested() {
+        let _guard = PREFIX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let line = format_line(LogLevel::Debug, LogLevel::Debug, "contributors(file)");
+        assert_eq!(line.as_deref(), Some("[mdbook-gitinfo] contributors(file)"));
+    }
+
+    #[test]
+    fn warnings_are_always_shown() {
+        assert!(format_line(LogLevel::Warn, LogLevel::Warn, "w").is_some());
+        assert!(format_line(LogLevel::Debug, LogLevel::Warn, "w").is_some());
+    }
+
+    #[test]
+    fn all_levels_share_the_unified_prefix() {
+        let _guard = PREFIX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for level in [LogLevel::Warn, LogLevel::Info, LogLevel::Debug] {
+            let line = format_line(LogLevel::Debug, level, "msg").unwrap();
+            assert!(line.starts_with("[mdbook-gitinfo] "), "{line}");
+        }
+        assert_eq!(
+            format_line(LogLevel::Warn, LogLevel::Warn, "msg").as_deref(),
+            Some("[mdbook-gitinfo] Warning: msg")
+        );
+    }
+
+    #[test]
+    fn set_prefix_replaces_the_prefix_until_cleared() {
+        let _guard = PREFIX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_prefix("[my-tool]");
+        let warn = format_line(LogLevel::Warn, LogLevel::Warn, "msg");
+        let info = format_line(LogLevel::Info, LogLevel::Info, "msg");
+        set_prefix("");
+        let restored = format_line(LogLevel::Warn, LogLevel::Warn, "msg");
+
+        assert_eq!(warn.as_deref(), Some("[my-tool] Warning: msg"));
+        assert_eq!(info.as_deref(), Some("[my-tool] msg"));
+        assert_eq!(restored.as_deref(), Some("[mdbook-gitinfo] Warning: msg"));
+    }
+}
@@ -1,31 +1,27 @@
 //! mdbook-gitinfo: inject Git metadata into mdBook chapters.
 
-mod chapters;
-mod layout;
-mod processor;
-mod renderer;
-mod repo;
-mod theme;
-mod timefmt;
+pub use mdbook_gitinfo::{config, git, logging, renderer, timefmt};
 
-pub use mdbook_gitinfo::{config, git};
-
-use clap::{ArgMatches, Command, arg, command};
+use clap::{ArgAction, ArgMatches, Command, arg, command};
+use mdbook_gitinfo::processor::GitInfo;
 use mdbook_preprocessor::errors::Error;
 use mdbook_preprocessor::{MDBOOK_VERSION, Preprocessor, parse_input};
-use processor::GitInfo;
+use std::path::Path;
 use std::{io, process};
 
-fn handle_preprocessing(pre: &dyn Preprocessor) -> Result<(), Error> {
-    let (ctx, book) = parse_input(io::stdin())?;
+fn handle_preprocessing(pre: &dyn Preprocessor, overrides: &[String]) -> Result<(), Error> {
+    let (mut ctx, book) = parse_input(io::stdin())?;
+    for snippet in overrides {
+        config::apply_override(&mut ctx.config, snippet)?;
+    }
 
     if ctx.mdbook_version != MDBOOK_VERSION {
-        eprintln!(
-            "Warning: The '{}' plugin was built against version {} of mdbook, but we're being called from version {}",
+        logging::warn(format_args!(
+            "The '{}' plugin was built against version {} of mdbook, but we're being called from version {}",
             pre.name(),
             MDBOOK_VERSION,
             ctx.mdbook_version
-        );
+        ));
     }
 
     let processed_book = pre.run(&ctx, book)?;
@@ -50,6 +46,10 @@ fn handle_supports(pre: &dyn Preprocessor, sub_args: &ArgMatches) -> ! {
 fn main() {
     let matches = command!("mdbook-gitinfo")
         .about("An mdBook preprocessor that injects Git metadata into the book")
+        .arg(
+            arg!(--"config-override" <TOML> "TOML merged onto [preprocessor.gitinfo], e.g. 'template = \"{{hash}}\"'")
+                .action(ArgAction::Append),
+        )
         .subcommand(
             Command::new("supports")
                 .arg(arg!(<renderer> "Renderer to check"))
@@ -57,13 +57,18 @@ fn main() {
         )
         .get_matches();
 
-    let pre = GitInfo::new();
+    let pre = GitInfo::new().with_renderers(config::supported_renderers(Path::new("book.toml")));
 
     if let Some(sub_args) = matches.subcommand_matches("supports") {
         handle_supports(&pre, sub_args);
     }
 
-    if let Err(e) = handle_preprocessing(&pre) {
+    let overrides: Vec<String> = matches
+        .get_many::<String>("config-override")
+        .unwrap_or_default()
+        .cloned()
+        .collect();
+    if let Err(e) = handle_preprocessing(&pre, &overrides) {
         eprintln!("{}", e);
         process::exit(1);
     }
@@ -0,0 +1,97 @@
+//! Structured per-chapter metadata for consumers other than the page itself.
+//!
+//! - `sidebar-data = true`: the preprocessor cannot edit mdBook's sidebar, so
+//!   it writes the metadata as a JSON file keyed by chapter source path
+//!   (relative to `src`), for a theme script to pick up.
+//! - `embed-metadata = true`: `Chapter` has no room for extra fields, so the
+//!   JSON goes into an HTML comment at the top of the chapter, where a custom
+//!   renderer can read it back.
+
+use mdbook_preprocessor::errors::Error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Location of the sidebar file, relative to the book root.
+pub const SIDEBAR_DATA_PATH: &str = "target/gitinfo/sidebar.json";
+
+/// Opening of the embedded metadata comment; the JSON follows on the same line.
+pub const METADATA_MARKER: &str = "<!-- gitinfo:metadata ";
+
+/// Metadata of one chapter. Values are raw (not HTML-escaped or linked);
+/// `hash` is empty for chapters without commits and `tag` is `null` when
+/// no tag was found.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ChapterMetadata {
+    pub title: String,
+    pub number: String,
+    pub hash: String,
+    pub long: String,
+    /// Committer date as printed by Git (`%cI`).
+    pub date: String,
+    pub age: String,
+    pub author: String,
+    pub subject: String,
+    pub branch: String,
+    pub tag: Option<String>,
+}
+
+/// Write `entries` to [`SIDEBAR_DATA_PATH`] under `root`, creating the
+/// directory as needed.
+pub fn write_sidebar_data(
+    root: &Path,
+    entries: &BTreeMap<String, ChapterMetadata>,
+) -> Result<(), Error> {
+    let path = root.join(SIDEBAR_DATA_PATH);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(entries)?;
+    fs::write(&path, json + "\n")?;
+    Ok(())
+}
+
+/// Put `meta` as `<!-- gitinfo:metadata {json} -->` at the top of `content`,
+/// replacing the comment of a previous run.
+///
+/// `>` is written as `\u003e` so no value can close the comment early.
+pub fn embed_metadata(content: &str, meta: &ChapterMetadata) -> String {
+    let json = serde_json::to_string(meta)
+        .expect("string fields always serialize")
+        .replace('>', "\\u003e");
+    let rest = match content.strip_prefix(METADATA_MARKER) {
+        Some(old) => old
+            .split_once("-->")
+            .map_or(content, |(_, after)| after.trim_start_matches('\n')),
+        None => content,
+    };
+    format!("{METADATA_MARKER}{json} -->\n\n{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_metadata_round_trips_and_is_replaced() {
+        let meta = ChapterMetadata {
+            title: "Intro".into(),
+            subject: "close --> early".into(),
+            ..Default::default()
+        };
+        let once = embed_metadata("# Intro\n", &meta);
+        let twice = embed_metadata(&once, &meta);
+        assert_eq!(once, twice);
+        assert!(once.ends_with("-->\n\n# Intro\n"));
+
+        let json = once
+            .strip_prefix(METADATA_MARKER)
+            .and_then(|s| s.split_once(" -->"))
+            .unwrap()
+            .0;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(value["subject"], "close --> early");
+        assert_eq!(value["tag"], serde_json::Value::Null);
+    }
+}
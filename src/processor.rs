@@ -1,80 +1,189 @@
 use crate::chapters::decorate_chapters;
-use crate::config::{ContributorsSource, load_config};
+use crate::config::{
+    ContributorsOrder, ContributorsScope, ContributorsSort, ContributorsSource, DateStyle,
+    FooterPrint, Forge, GitBackend, LineEnding, NoTagFallback, PrimaryLink, TagScope, TagSource,
+    TemplateFormat, load_config,
+};
+use crate::frontmatter::{ChapterOverrides, parse_overrides, split_frontmatter};
 use crate::git;
-use crate::layout::{resolve_align, resolve_margins, resolve_messages};
+use crate::layout::{
+    ChapterFilter, TemplateRules, resolve_align, resolve_margins, resolve_messages,
+    resolve_placement,
+};
+use crate::logging;
+use crate::metadata::{ChapterMetadata, SIDEBAR_DATA_PATH, embed_metadata, write_sidebar_data};
 use crate::renderer::{
-    GITINFO_CSS, render_contributors_hbs, render_template, style_block, wrap_block,
+    ChapterGitInfo, ContributorsOptions, ContributorsTemplate, GITINFO_CSS, GITINFO_JS, RepoMeta,
+    TemplateValues, Wrapper, branch_badge, contributors_css_vars, escape_html, footer_color_css,
+    inline_markdown, is_valid_tag_name, minify_css, minify_html, multiline_breaks,
+    print_footer_css, render_template, render_template_sanitized, style_block, time_element,
+    truncate_with_ellipsis, wrap_block,
+};
+use crate::repo::{
+    RepoLinks, UrlTemplates, forge_host, repo_name, resolve_forge, resolve_repo_base,
+};
+use crate::theme::{ensure_gitinfo_assets, ensure_gitinfo_js};
+use crate::timefmt::{
+    build_now, days_since, format_commit_datetime, format_datetime, formats_with_offset,
+    parse_commit_datetime, relative_to,
 };
-use crate::repo::{resolve_repo_base, tag_url};
-use crate::theme::ensure_gitinfo_assets;
-use crate::timefmt::format_commit_datetime;
 use mdbook_preprocessor::book::Book;
 use mdbook_preprocessor::errors::Error;
 use mdbook_preprocessor::{Preprocessor, PreprocessorContext};
+use std::cell::{OnceCell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
 use std::{fs, path::PathBuf};
 
-pub struct GitInfo;
+/// Produces the footer body for a chapter instead of the footer template.
+pub type FooterFn = dyn Fn(&ChapterGitInfo) -> String;
+
+/// The `gitinfo` mdBook preprocessor.
+///
+/// ```no_run
+/// use mdbook_gitinfo::GitInfo;
+///
+/// let pre = GitInfo::new().with_footer_fn(|info| format!("rev {}", info.hash));
+/// # let _ = pre;
+/// ```
+pub struct GitInfo {
+    runner: Option<Box<dyn git::GitRunner>>,
+    footer_fn: Option<Box<FooterFn>>,
+    renderers: Vec<String>,
+}
+
+impl Default for GitInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl GitInfo {
     pub fn new() -> Self {
-        GitInfo
+        GitInfo {
+            runner: None,
+            footer_fn: None,
+            renderers: vec!["html".to_string()],
+        }
+    }
+
+    /// Run the Git queries through `runner` instead of the configured
+    /// `git-binary`.
+    pub fn with_runner(runner: impl git::GitRunner + 'static) -> Self {
+        GitInfo {
+            runner: Some(Box::new(runner)),
+            ..Self::new()
+        }
+    }
+
+    /// Renderers reported as supported (default `["html"]`); see
+    /// [`crate::config::supported_renderers`].
+    pub fn with_renderers(mut self, renderers: Vec<String>) -> Self {
+        self.renderers = renderers;
+        self
+    }
+
+    /// Render every footer body with `f` instead of the configured templates.
+    /// The result is still wrapped in the usual `<footer>` element.
+    pub fn with_footer_fn(mut self, f: impl Fn(&ChapterGitInfo) -> String + 'static) -> Self {
+        self.footer_fn = Some(Box::new(f));
+        self
+    }
+}
+
+/// Split a fence line (```` ``` ```` or `~~~`, up to three spaces of
+/// indentation) into its character, run length and info string.
+fn fence_marker(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    let first = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == first).count();
+    let info = trimmed[len..].trim();
+    // ```` ```a``` ```` is inline code, not a fence
+    (len >= 3 && !(first == '`' && info.contains('`'))).then_some((first, len, info))
+}
+
+/// Tracks fenced code blocks across consecutive lines.
+#[derive(Default)]
+struct FenceTracker {
+    open: Option<(char, usize)>,
+}
+
+impl FenceTracker {
+    /// Feed the next line; `true` when it belongs to a fenced block,
+    /// including the opening and closing fence lines.
+    fn in_code(&mut self, line: &str) -> bool {
+        let marker = fence_marker(line);
+        match (self.open, marker) {
+            (None, Some((ch, len, _))) => {
+                self.open = Some((ch, len));
+                true
+            }
+            (None, None) => false,
+            // A closing fence has no info string, so a ```` ```rust ```` line
+            // inside a longer or different fence stays part of the code.
+            (Some((ch, len)), Some((c, n, info))) if c == ch && n >= len && info.is_empty() => {
+                self.open = None;
+                true
+            }
+            (Some(_), _) => true,
+        }
+    }
+
+    /// The fence that would close the block still open, if any.
+    fn closing_fence(&self) -> Option<String> {
+        self.open.map(|(ch, len)| ch.to_string().repeat(len))
+    }
+}
+
+/// Byte offset of the first `needle` on a line outside fenced code, so a
+/// marker quoted in a code sample is never mistaken for a real one.
+fn find_outside_fences(content: &str, needle: &str) -> Option<usize> {
+    let mut fences = FenceTracker::default();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if !fences.in_code(line)
+            && let Some(i) = line.find(needle)
+        {
+            return Some(offset + i);
+        }
+        offset += line.len();
     }
+    None
 }
 
 /// Extract all `{% contributors ... %}` tokens and replace them with rendered HTML.
-/// Ignores fence blocks
+/// Fenced and indented code is left byte-for-byte as it was.
 ///
 /// Token forms:
 /// - `{% contributors %}`
 /// - `{% contributors a b c %}` (only honoured when contributors-source = "inline")
+///
+/// Other sources render through `global_renderer`, called per token so the
+/// block is only built for pages that use it.
+///
+/// With `compact`, the HTML gets exactly one blank line on each side (needed
+/// for Markdown to treat it as a raw HTML block), reusing blank lines already
+/// present around the token instead of adding more.
 fn replace_contributors_tokens(
     input: &str,
     source: ContributorsSource,
-    contributors_html_global: &str,
+    global_renderer: &dyn Fn() -> String,
     inline_renderer: &dyn Fn(&[String]) -> String,
+    compact: bool,
 ) -> String {
     let mut out = String::with_capacity(input.len());
+    // compact: a blank line is still owed after the last emitted HTML block
+    let mut blank_owed = false;
 
-    // fenced code tracking
-    let mut in_fence = false;
-    let mut fence_ch: char = '\0'; // '`' or '~'
-    let mut fence_len: usize = 0;
+    let mut fences = FenceTracker::default();
 
     for line in input.split_inclusive('\n') {
-        // Detect fenced blocks (``` or ~~~), allowing leading spaces/tabs
-        let trimmed = line.trim_start_matches(|c| c == ' ' || c == '\t');
-
-        if !trimmed.is_empty() {
-            let first = trimmed.chars().next().unwrap();
-            if first == '`' || first == '~' {
-                let mut count = 0usize;
-                for c in trimmed.chars() {
-                    if c == first {
-                        count += 1;
-                    } else {
-                        break;
-                    }
-                }
-                if count >= 3 {
-                    if !in_fence {
-                        in_fence = true;
-                        fence_ch = first;
-                        fence_len = count;
-                        out.push_str(line);
-                        continue;
-                    } else if first == fence_ch && count >= fence_len {
-                        in_fence = false;
-                        fence_ch = '\0';
-                        fence_len = 0;
-                        out.push_str(line);
-                        continue;
-                    }
-                }
-            }
+        if std::mem::take(&mut blank_owed) && !line.trim().is_empty() {
+            out.push('\n');
         }
 
-        // If inside a fenced code block, do not evaluate tokens.
-        if in_fence {
+        // Fenced code (fence lines included) is copied verbatim.
+        if fences.in_code(line) {
             out.push_str(line);
             continue;
         }
@@ -101,28 +210,39 @@ fn replace_contributors_tokens(
                 let html = match source {
                     ContributorsSource::Inline => {
                         if args.is_empty() {
-                            eprintln!(
-                                "[mdbook-gitinfo] Warning: contributors-source is 'inline' but no usernames provided in '{{% contributors %}}'"
-                            );
+                            logging::warn(format_args!(
+                                "contributors-source is 'inline' but no usernames provided in '{{% contributors %}}'"
+                            ));
                             String::new()
                         } else {
                             inline_renderer(&args)
                         }
                     }
-                    ContributorsSource::Git | ContributorsSource::File => {
+                    ContributorsSource::Git
+                    | ContributorsSource::File
+                    | ContributorsSource::Env => {
                         if !args.is_empty() {
-                            eprintln!(
-                                "[mdbook-gitinfo] Warning: inline contributors list ignored because contributors-source is not 'inline'"
-                            );
+                            logging::warn(format_args!(
+                                "inline contributors list ignored because contributors-source is not 'inline'"
+                            ));
                         }
-                        contributors_html_global.to_string()
+                        global_renderer()
                     }
                 };
 
                 // Emit as a raw HTML block with blank lines around it
-                out.push_str("\n");
-                out.push_str(html.trim());
-                out.push_str("\n\n");
+                if !compact {
+                    out.push('\n');
+                    out.push_str(html.trim());
+                    out.push_str("\n\n");
+                } else {
+                    if !out.is_empty() && !out.ends_with("\n\n") {
+                        out.push('\n');
+                    }
+                    out.push_str(html.trim());
+                    out.push('\n');
+                    blank_owed = true;
+                }
                 continue;
             }
         }
@@ -134,7 +254,21 @@ fn replace_contributors_tokens(
     out
 }
 
-fn parse_contributors_file(path: &std::path::Path) -> Vec<String> {
+/// Split a `Name <email>` contributor line into its name and e-mail.
+/// Lines without a well-formed `<...>` suffix are returned as a bare name.
+fn split_name_email(line: &str) -> (String, Option<String>) {
+    if let Some(rest) = line.strip_suffix('>')
+        && let Some((name, email)) = rest.rsplit_once('<')
+    {
+        let email = email.trim();
+        if !email.is_empty() && !name.trim().is_empty() {
+            return (name.trim().to_string(), Some(email.to_string()));
+        }
+    }
+    (line.to_string(), None)
+}
+
+fn parse_contributors_file(path: &std::path::Path) -> Vec<(String, Option<String>)> {
     let Ok(raw) = fs::read_to_string(path) else {
         return vec![];
     };
@@ -146,10 +280,621 @@ fn parse_contributors_file(path: &std::path::Path) -> Vec<String> {
                 .or_else(|| l.strip_prefix("* "))
                 .unwrap_or(l)
         })
-        .map(|l| l.trim().to_string())
+        .map(|l| split_name_email(l.trim()))
+        .collect()
+}
+
+/// Split a comma- or newline-separated contributors list (as passed via
+/// `contributors-source = "env"`) into usernames.
+fn parse_contributors_env(value: &str) -> Vec<String> {
+    value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .map(str::to_string)
         .collect()
 }
 
+/// Resolve the tag shown for a single chapter.
+///
+/// In `repo` scope every chapter shares `repo_tag`. In `chapter` scope the
+/// nearest tag to the chapter's last commit is used instead; lookups are
+/// memoized by commit hash since many chapters share the same commit.
+fn chapter_tag(
+    scope: TagScope,
+    repo_tag: &str,
+    commit: &str,
+    cache: &RefCell<HashMap<String, String>>,
+    runner: &dyn git::GitRunner,
+    dir: &Path,
+) -> String {
+    if scope == TagScope::Repo || commit.is_empty() {
+        return repo_tag.to_string();
+    }
+    if let Some(t) = cache.borrow().get(commit) {
+        return t.clone();
+    }
+    let tag = git::nearest_tag_for_commit(runner, commit, dir)
+        .unwrap_or_else(|| "No tags found".to_string());
+    cache.borrow_mut().insert(commit.to_string(), tag.clone());
+    tag
+}
+
+/// URL the commit hash links to in hyperlink mode.
+///
+/// With `primary-link = "tag"` the hash points at the release page of the
+/// chapter's tag when there is one, and at the commit otherwise.
+fn hash_link_url(
+    primary: PrimaryLink,
+    links: &RepoLinks,
+    long_hash: &str,
+    tag: Option<&str>,
+) -> String {
+    match (primary, tag) {
+        (PrimaryLink::Tag, Some(t)) => links.tag(t),
+        _ => links.commit(long_hash),
+    }
+}
+
+/// Which references are rendered as links (`link-hash`, `link-tag`,
+/// `link-branch`).
+#[derive(Debug, Clone, Copy)]
+struct LinkKinds {
+    hash: bool,
+    tag: bool,
+    branch: bool,
+}
+
+/// `{{ref}}`: the tag the commit carries exactly, else its short hash, else
+/// the branch, linked to the matching page when `links` is given and that
+/// kind of reference is linked.
+fn best_ref(
+    exact_tag: Option<&str>,
+    short: &str,
+    long: &str,
+    branch: &str,
+    links: Option<&RepoLinks>,
+    kinds: LinkKinds,
+) -> String {
+    let (text, url) = match (exact_tag, short.is_empty()) {
+        (Some(tag), _) => (tag, links.filter(|_| kinds.tag).map(|l| l.tag(tag))),
+        (None, false) => (short, links.filter(|_| kinds.hash).map(|l| l.commit(long))),
+        (None, true) => (
+            branch,
+            links.filter(|_| kinds.branch).map(|l| l.branch(branch)),
+        ),
+    };
+    match url {
+        Some(url) if !text.is_empty() => link_html(&url, &escape_html(text)),
+        _ => escape_html(text),
+    }
+}
+
+/// `<a>` to `url` around `text_html`, which must already be HTML.
+fn link_html(url: &str, text_html: &str) -> String {
+    format!(r#"<a href="{}">{}</a>"#, escape_html(url), text_html)
+}
+
+/// Strip `gitinfo` frontmatter from every chapter and collect its templates
+/// by chapter path. Malformed blocks are stripped with a warning and ignored.
+fn take_frontmatter_overrides(book: &mut Book) -> HashMap<PathBuf, ChapterOverrides> {
+    let mut overrides = HashMap::new();
+    book.for_each_chapter_mut(|ch| {
+        let Some((yaml, rest)) = split_frontmatter(&ch.content) else {
+            return;
+        };
+        let parsed = match parse_overrides(yaml) {
+            Ok(None) => return,
+            Ok(Some(o)) => Some(o),
+            Err(e) => {
+                logging::warn(format_args!(
+                    "ignoring gitinfo frontmatter in '{}': {e}",
+                    ch.name
+                ));
+                None
+            }
+        };
+        ch.content = rest.to_string();
+        if let (Some(o), Some(path)) = (parsed, &ch.path) {
+            overrides.insert(path.clone(), o);
+        }
+    });
+    overrides
+}
+
+/// Whether `title` is listed in `exclude-titles` (exact match, optionally ignoring case).
+fn title_excluded(titles: &[String], title: &str, ignore_case: bool) -> bool {
+    let title = title.trim();
+    if ignore_case {
+        let title = title.to_lowercase();
+        titles.iter().any(|t| t.trim().to_lowercase() == title)
+    } else {
+        titles.iter().any(|t| t.trim() == title)
+    }
+}
+
+/// Source path of the book's landing page (`skip-index`): the top-level
+/// `README.md` or `index.md`, which mdBook renders as `index.html`, else the
+/// first chapter.
+fn index_chapter_path(book: &Book) -> Option<PathBuf> {
+    let paths: Vec<&PathBuf> = book.chapters().filter_map(|ch| ch.path.as_ref()).collect();
+    let is_landing = |p: &&&PathBuf| {
+        p.parent().is_none_or(|d| d.as_os_str().is_empty())
+            && p.file_name().is_some_and(|n| {
+                n.eq_ignore_ascii_case("README.md") || n.eq_ignore_ascii_case("index.md")
+            })
+    };
+    paths
+        .iter()
+        .find(is_landing)
+        .or(paths.first())
+        .map(|p| p.to_path_buf())
+}
+
+/// Reject `custom-format` strings that would make Git print several lines:
+/// literal line breaks, `%n`, `%x0a`/`%x0d` in either case, and the
+/// multi-line body and notes placeholders `%b`, `%B` and `%N`.
+fn validate_custom_format(format: &str) -> Result<(), Error> {
+    let multi_line = |spec: &str| {
+        // `%+b`, `%-b` and `% b` only change how an empty value is spaced.
+        let spec = spec.trim_start_matches(['+', '-', ' ']);
+        let hex = spec.get(..3).map(str::to_ascii_lowercase);
+        spec.starts_with(['n', 'b', 'B', 'N']) || matches!(hex.as_deref(), Some("x0a" | "x0d"))
+    };
+    let mut rest = format;
+    let mut bad = format.contains(['\n', '\r']);
+    while let Some(i) = rest.find('%') {
+        let spec = &rest[i + 1..];
+        if let Some(after) = spec.strip_prefix('%') {
+            rest = after;
+            continue;
+        }
+        bad |= multi_line(spec);
+        rest = spec;
+    }
+    if bad {
+        return Err(Error::msg(format!(
+            "custom-format must produce a single line, got {format:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// `{{tag}}` when no tag was found, per `no-tag-fallback`.
+fn missing_tag_display(
+    fallback: NoTagFallback,
+    text: &str,
+    branch_disp: &str,
+    hash_disp: &str,
+) -> String {
+    match fallback {
+        NoTagFallback::Text => text,
+        NoTagFallback::Branch => branch_disp,
+        NoTagFallback::Hash => hash_disp,
+    }
+    .to_string()
+}
+
+/// Footer class flagging pages not updated for more than `stale_days`.
+fn staleness_class(enabled: bool, stale_days: u32, age_days: Option<i64>) -> &'static str {
+    match age_days {
+        Some(d) if enabled && d > i64::from(stale_days) => "gitinfo-stale",
+        _ => "",
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Placement {
+    Header,
+    Footer,
+}
+
+impl Placement {
+    fn markers(self) -> (&'static str, &'static str) {
+        match self {
+            Placement::Header => ("<!-- gitinfo:header -->", "<!-- /gitinfo:header -->"),
+            Placement::Footer => ("<!-- gitinfo:footer -->", "<!-- /gitinfo:footer -->"),
+        }
+    }
+}
+
+/// Insert `html` between stable markers at the top (header) or bottom (footer)
+/// of `content`. A block left by a previous run is replaced in place, so
+/// running the preprocessor repeatedly never stacks blocks.
+///
+/// With `compact`, only as many newlines are added as needed to leave a
+/// single blank line between the block and the page content.
+///
+/// Markers inside fenced code are ignored, and a fence left open at the end
+/// of the chapter is closed before the footer is appended.
+fn upsert_marked_block(content: &str, placement: Placement, html: &str, compact: bool) -> String {
+    let (start, end) = placement.markers();
+    let block = format!("{start}\n{html}\n{end}");
+
+    if let Some(s) = find_outside_fences(content, start)
+        && let Some(e) = content[s..].find(end)
+    {
+        let e = s + e + end.len();
+        return format!("{}{}{}", &content[..s], block, &content[e..]);
+    }
+
+    match placement {
+        Placement::Header => {
+            let gap = if compact && content.starts_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            format!("{block}{gap}{content}")
+        }
+        Placement::Footer => {
+            // A chapter ending inside an unclosed fence would swallow the
+            // footer into the code block; close it first.
+            let mut fences = FenceTracker::default();
+            for line in content.split_inclusive('\n') {
+                fences.in_code(line);
+            }
+            let closed;
+            let content = match fences.closing_fence() {
+                Some(fence) => {
+                    let nl = if content.ends_with('\n') { "" } else { "\n" };
+                    closed = format!("{content}{nl}{fence}\n");
+                    closed.as_str()
+                }
+                None => content,
+            };
+            let prefix = match (compact, content.ends_with("\n\n"), content.ends_with('\n')) {
+                (true, true, _) => "",
+                (true, false, true) | (false, true, _) => "\n",
+                _ => "\n\n",
+            };
+            format!("{content}{prefix}{block}\n")
+        }
+    }
+}
+
+/// Whether CRLF line endings outnumber bare LF ones in `content`.
+fn is_crlf_dominant(content: &str) -> bool {
+    let crlf = content.matches("\r\n").count();
+    crlf > content.matches('\n').count() - crlf
+}
+
+/// Convert every chapter to LF for injection, which inserts `\n` only, and
+/// return per chapter (in visiting order) whether it goes back to CRLF.
+fn normalise_line_endings(book: &mut Book, mode: LineEnding) -> Vec<bool> {
+    let mut to_crlf = Vec::new();
+    book.for_each_chapter_mut(|ch| {
+        let crlf = match mode {
+            LineEnding::Lf => false,
+            LineEnding::Crlf => true,
+            LineEnding::Preserve => is_crlf_dominant(&ch.content),
+        };
+        if mode != LineEnding::Preserve || crlf {
+            ch.content = ch.content.replace("\r\n", "\n");
+        }
+        to_crlf.push(crlf);
+    });
+    to_crlf
+}
+
+/// Undo [`normalise_line_endings`] for chapters marked in `to_crlf`.
+fn restore_line_endings(book: &mut Book, to_crlf: &[bool]) {
+    let mut flags = to_crlf.iter();
+    book.for_each_chapter_mut(|ch| {
+        if flags.next().copied().unwrap_or(false) {
+            ch.content = ch.content.replace('\n', "\r\n");
+        }
+    });
+}
+
+/// Whether the injected footer block of `content` parses as a top-level
+/// HTML block rather than ending up inside a list, blockquote or paragraph.
+///
+/// Content without a footer is trivially fine.
+fn footer_is_top_level(content: &str) -> bool {
+    use pulldown_cmark::{Event, Parser, Tag};
+
+    let (marker, _) = Placement::Footer.markers();
+    let mut depth = 0usize;
+    let mut in_html_block = false;
+    for event in Parser::new(content) {
+        match event {
+            Event::Start(Tag::HtmlBlock) => in_html_block = true,
+            Event::End(pulldown_cmark::TagEnd::HtmlBlock) => in_html_block = false,
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Html(html) | Event::InlineHtml(html) if html.contains(marker) => {
+                return in_html_block && depth == 0;
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Resolved contributors settings shared by every render of the block.
+struct ContributorsView<'a> {
+    title: &'a str,
+    message: Option<&'a str>,
+    excluded: &'a BTreeSet<String>,
+    max_visible: usize,
+    max_hidden: Option<usize>,
+    options: ContributorsOptions<'a>,
+    /// `contributors-template`, used instead of the bundled template.
+    template_file: Option<&'a Path>,
+    /// Compiled on the first render, so runs without contributors never
+    /// touch Handlebars.
+    template: OnceCell<Result<ContributorsTemplate, String>>,
+    /// `minify`: collapse whitespace in the rendered block.
+    minify: bool,
+}
+
+/// Which per-chapter Git outputs the configured templates and features use,
+/// so unreferenced ones are never queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NeededFields {
+    short: bool,
+    long: bool,
+    date: bool,
+    author: bool,
+    committer: bool,
+    email: bool,
+    subject: bool,
+    custom: bool,
+    dirty: bool,
+    exact_tag: bool,
+}
+
+impl NeededFields {
+    const ALL: Self = NeededFields {
+        short: true,
+        long: true,
+        date: true,
+        author: true,
+        committer: true,
+        email: true,
+        subject: true,
+        custom: true,
+        dirty: true,
+        exact_tag: true,
+    };
+
+    /// Fields referenced by any of `templates`, plus those `features` imply.
+    fn analyse<'t>(templates: impl IntoIterator<Item = &'t str>, features: FieldFeatures) -> Self {
+        let mut used = BTreeSet::new();
+        for t in templates {
+            let mut rest = t;
+            while let Some(start) = rest.find("{{") {
+                let after = &rest[start + 2..];
+                let Some(end) = after.find("}}") else { break };
+                let key = &after[..end];
+                used.insert(key.split_once('|').map_or(key, |(k, _)| k));
+                rest = &after[end + 2..];
+            }
+        }
+        let uses = |keys: &[&str]| keys.iter().any(|k| used.contains(k));
+
+        let tag = uses(&["tag"]);
+        let reference = uses(&["ref"]);
+        let hash = uses(&["hash"]) || (tag && features.tag_falls_back_to_hash) || reference;
+        NeededFields {
+            short: hash,
+            long: uses(&["long"])
+                || (hash && features.link_hash)
+                || (tag && features.chapter_tags)
+                || reference,
+            date: uses(&["date", "days-since", "age", "relative"]) || features.staleness,
+            author: uses(&["author"]),
+            committer: uses(&["committer"]),
+            email: uses(&["email"]),
+            subject: uses(&["subject"]),
+            custom: uses(&["custom"]),
+            dirty: uses(&["dirty"]),
+            exact_tag: reference,
+        }
+    }
+
+    /// [`git::COMMIT_FIELDS_FORMAT`] with the slots of unneeded fields left
+    /// empty. `None` when nothing is needed. The short hash is always read
+    /// alongside anything else, as its absence marks an uncommitted file.
+    fn format(&self) -> Option<String> {
+        let any = self.short
+            || self.long
+            || self.date
+            || self.author
+            || self.committer
+            || self.email
+            || self.subject;
+        let slots = [
+            (any, "%h"),
+            (self.long, "%H"),
+            (self.date, "%cI"),
+            (self.author, "%an"),
+            (self.committer, "%cn"),
+            (self.email, "%ae"),
+            (self.subject, "%s"),
+        ];
+        slots.iter().any(|(n, _)| *n).then(|| {
+            slots
+                .iter()
+                .map(|(n, f)| if *n { *f } else { "" })
+                .collect::<Vec<_>>()
+                .join("%x00")
+        })
+    }
+}
+
+/// Features besides placeholders that consume commit fields.
+#[derive(Debug, Default, Clone, Copy)]
+struct FieldFeatures {
+    link_hash: bool,
+    chapter_tags: bool,
+    tag_falls_back_to_hash: bool,
+    staleness: bool,
+}
+
+/// [`git::CommitFields`] per `(rev, path)`, so chapters sharing a file cost one
+/// `git log` call. Lives for a single `run`.
+struct CommitCache {
+    format: Option<String>,
+    log_opts: git::LogOptions,
+    entries: RefCell<HashMap<(String, String), git::CommitFields>>,
+}
+
+impl CommitCache {
+    fn new(needed: NeededFields, log_opts: git::LogOptions) -> Self {
+        CommitCache {
+            format: needed.format(),
+            log_opts,
+            entries: RefCell::default(),
+        }
+    }
+
+    /// Whether [`CommitCache::get`] asks Git at all; when it does, an empty
+    /// short hash means the path has no commits.
+    fn queries_git(&self) -> bool {
+        self.format.is_some()
+    }
+
+    fn get(
+        &self,
+        runner: &dyn git::GitRunner,
+        rev: &str,
+        path: &str,
+        dir: &Path,
+    ) -> git::CommitFields {
+        let Some(format) = &self.format else {
+            return git::CommitFields::default();
+        };
+        let key = (rev.to_string(), path.to_string());
+        if let Some(info) = self.entries.borrow().get(&key) {
+            return info.clone();
+        }
+        let info = git::last_commit_fields_as(runner, format, rev, path, dir, self.log_opts)
+            .unwrap_or_default();
+        self.entries.borrow_mut().insert(key, info.clone());
+        info
+    }
+}
+
+/// Directory Git runs in: `git-root` resolved against the book root, or the
+/// detected work tree top level, falling back to the book root itself.
+fn resolve_git_root(
+    runner: &dyn git::GitRunner,
+    configured: Option<&str>,
+    book_root: &Path,
+) -> PathBuf {
+    match configured.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(dir) => book_root.join(dir),
+        None => git::toplevel(runner, book_root).unwrap_or_else(|| book_root.to_path_buf()),
+    }
+}
+
+/// Location of the book root relative to `git_root`, so chapter paths can be
+/// passed to Git from there. Books outside `git_root` get their absolute path.
+fn book_prefix(git_root: &Path, book_root: &Path) -> PathBuf {
+    let canon = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let (git_root, book_root) = (canon(git_root), canon(book_root));
+    book_root
+        .strip_prefix(&git_root)
+        .map(Path::to_path_buf)
+        .unwrap_or(book_root)
+}
+
+/// Split `users` into visible, listed-hidden and an overflow count.
+///
+/// The first `max_visible` users are shown inline; up to `max_hidden` more
+/// (unbounded when `None`) are listed in the expander, and the remainder is
+/// only counted.
+fn split_contributors(
+    users: &[String],
+    max_visible: usize,
+    max_hidden: Option<usize>,
+) -> (&[String], &[String], usize) {
+    let (visible, rest) = users.split_at(max_visible.min(users.len()));
+    let listed = max_hidden.map_or(rest.len(), |m| m.min(rest.len()));
+    let (hidden, overflow) = rest.split_at(listed);
+    (visible, hidden, overflow.len())
+}
+
+impl ContributorsView<'_> {
+    /// Drop excluded and repeated users, split at `max_visible`/`max_hidden`
+    /// and render the block.
+    /// Template failures are reported and yield an empty block.
+    fn render(&self, users: Vec<String>) -> String {
+        self.render_with(users, self.options)
+    }
+
+    /// The `contributors-template` file if it loads and compiles, else the
+    /// bundled template.
+    fn compile_template(&self) -> Result<ContributorsTemplate, Error> {
+        if let Some(path) = self.template_file {
+            let custom = fs::read_to_string(path)
+                .map_err(Error::from)
+                .and_then(|src| ContributorsTemplate::from_source(&src));
+            match custom {
+                Ok(template) => return Ok(template),
+                Err(e) => logging::warn(format_args!(
+                    "unable to use contributors template {}: {e}; using the built-in one",
+                    path.display()
+                )),
+            }
+        }
+        ContributorsTemplate::new()
+    }
+
+    /// [`Self::render`] with other options, e.g. per-chapter commit counts.
+    fn render_with(&self, users: Vec<String>, options: ContributorsOptions) -> String {
+        let mut seen = HashSet::new();
+        let filtered: Vec<String> = users
+            .into_iter()
+            .filter(|u| !self.excluded.contains(u) && seen.insert(u.clone()))
+            .collect();
+        let (visible, hidden, overflow) =
+            split_contributors(&filtered, self.max_visible, self.max_hidden);
+
+        let rendered = self
+            .template
+            .get_or_init(|| self.compile_template().map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| Error::msg(e.clone()))
+            .and_then(|t| {
+                t.render(
+                    self.title,
+                    self.message,
+                    visible,
+                    hidden,
+                    overflow,
+                    &options,
+                )
+            });
+        match rendered {
+            Ok(html) if self.minify => minify_html(&html),
+            Ok(html) => html,
+            Err(e) => {
+                logging::warn(format_args!("unable to render contributors template: {e}"));
+                String::new()
+            }
+        }
+    }
+}
+
+/// The [`git::GitRunner`] for `backend`, running `system` where needed.
+fn backend_runner(backend: GitBackend, system: git::SystemGit) -> Box<dyn git::GitRunner> {
+    match backend {
+        GitBackend::Shell => Box::new(system),
+        #[cfg(feature = "gix")]
+        GitBackend::Gix => Box::new(crate::backend::GixGit::new(system)),
+        #[cfg(not(feature = "gix"))]
+        GitBackend::Gix => {
+            logging::warn(format_args!(
+                "backend = \"gix\" needs the gix feature; using the shell backend"
+            ));
+            Box::new(system)
+        }
+    }
+}
+
 impl Preprocessor for GitInfo {
     fn name(&self) -> &str {
         "gitinfo"
@@ -160,6 +905,18 @@ impl Preprocessor for GitInfo {
         if !cfg.enable.unwrap_or(true) {
             return Ok(book);
         }
+        logging::set_level(cfg.log_level.unwrap_or_default());
+        let configured_runner;
+        let runner: &dyn git::GitRunner = match &self.runner {
+            Some(runner) => runner.as_ref(),
+            None => {
+                configured_runner = backend_runner(
+                    cfg.backend.unwrap_or_default(),
+                    git::SystemGit::from_config(cfg.git_binary.as_deref()),
+                );
+                configured_runner.as_ref()
+            }
+        };
 
         let contributors_enabled = cfg.contributors.unwrap_or(false);
         let contributors_source = cfg.contributors_source.unwrap_or_default();
@@ -167,9 +924,33 @@ impl Preprocessor for GitInfo {
             .contributors_file
             .clone()
             .unwrap_or_else(|| "CONTRIBUTORS.md".to_string());
+        let footer_print = cfg.footer_print.unwrap_or_default();
+        let badge = cfg.branch_badge.unwrap_or(false);
+        // Only the html renderer gets inline styles and the CSS asset.
+        let html_output = ctx.renderer == "html";
+        logging::debug(format_args!("rendering for '{}'", ctx.renderer));
+        let footer_colors = footer_color_css(
+            cfg.footer_color.as_deref(),
+            cfg.footer_color_dark.as_deref(),
+        );
         // Generate assets and update book.toml once per run (no per-chapter side effects)
-        if contributors_enabled {
-            ensure_gitinfo_assets(ctx, GITINFO_CSS);
+        let wants_css = contributors_enabled
+            || badge
+            || footer_print != FooterPrint::Each
+            || !footer_colors.is_empty();
+        if html_output && wants_css {
+            let css = format!(
+                "{GITINFO_CSS}{}{}{footer_colors}",
+                contributors_css_vars(
+                    cfg.contributors_avatar_size.as_deref(),
+                    cfg.contributors_avatar_gap.as_deref(),
+                ),
+                print_footer_css(footer_print)
+            );
+            ensure_gitinfo_assets(ctx, &css, cfg.assets_strict.unwrap_or(false))?;
+        }
+        if html_output && cfg.inject_js.unwrap_or(false) {
+            ensure_gitinfo_js(ctx, GITINFO_JS, cfg.assets_strict.unwrap_or(false))?;
         }
         let contributors_title = cfg
             .contributors_title
@@ -185,7 +966,7 @@ impl Preprocessor for GitInfo {
             .map(str::trim)
             .filter(|s| !s.is_empty());
 
-        let excluded_contributors: std::collections::BTreeSet<String> = cfg
+        let mut excluded_contributors: BTreeSet<String> = cfg
             .contributors_exclude
             .clone()
             .unwrap_or_default()
@@ -197,128 +978,495 @@ impl Preprocessor for GitInfo {
         let show_header = cfg.header.unwrap_or(false);
         let show_footer = cfg.footer.unwrap_or(true);
         let (header_tmpl, footer_tmpl) = resolve_messages(&cfg);
+        let template_rules = TemplateRules::new(cfg.template_rules.as_deref().unwrap_or_default());
+        let chapter_filter = ChapterFilter::new(
+            cfg.include.as_deref().unwrap_or_default(),
+            cfg.exclude.as_deref().unwrap_or_default(),
+        );
         let font_size = cfg.font_size.unwrap_or_else(|| "0.8em".to_string());
         let (align_header, align_footer) = resolve_align(&cfg.align);
+        let (tag_header, tag_footer) =
+            resolve_placement(&cfg.wrapper_tag, (Wrapper::HEADER.tag, Wrapper::FOOTER.tag));
+        let [tag_header, tag_footer] = [
+            (tag_header, Wrapper::HEADER.tag),
+            (tag_footer, Wrapper::FOOTER.tag),
+        ]
+        .map(|(tag, default)| {
+            if is_valid_tag_name(&tag) {
+                tag
+            } else {
+                logging::warn(format_args!(
+                    "ignoring wrapper-tag '{tag}', using '{default}'"
+                ));
+                default.to_string()
+            }
+        });
+        let (class_header, class_footer) = resolve_placement(
+            &cfg.class_name,
+            (Wrapper::HEADER.class, Wrapper::FOOTER.class),
+        );
+        let header_wrapper = Wrapper {
+            tag: &tag_header,
+            class: &class_header,
+        };
+        let footer_wrapper = Wrapper {
+            tag: &tag_footer,
+            class: &class_footer,
+        };
         let (margin_header, margin_footer) = resolve_margins(&cfg.margin);
         let separator = cfg.separator.unwrap_or_else(|| " • ".to_string());
         let date_format = cfg.date_format.as_deref().unwrap_or("%Y-%m-%d");
         let time_format = cfg.time_format.as_deref().unwrap_or("%H:%M:%S");
-        let mut branch = cfg.branch.unwrap_or_else(|| "main".to_string());
+        let show_date = cfg.show_date.unwrap_or(true);
+        let show_time = cfg.show_time.unwrap_or(true);
+        let offset_formats = cfg
+            .show_offset
+            .unwrap_or(false)
+            .then(|| formats_with_offset(date_format, time_format, show_time));
+        let (date_format, time_format) = match &offset_formats {
+            Some((date, time)) => (date.as_str(), time.as_str()),
+            None => (date_format, time_format),
+        };
+        let age_strings = cfg.age_strings.clone().unwrap_or_default();
+        let date_style = cfg.date_style.unwrap_or_default();
+        let subject_max_len = cfg.subject_max_len.unwrap_or(72);
+        let dirty_marker = cfg.dirty_marker.clone().unwrap_or_else(|| "*".to_string());
+        // One "now" for every time computation, pinned under SOURCE_DATE_EPOCH.
+        let now = build_now(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref());
+        let build_date = format_datetime(
+            now.fixed_offset(),
+            cfg.timezone.as_deref(),
+            date_format,
+            time_format,
+            show_date,
+            show_time,
+        );
+        let template_multiline = cfg.template_multiline.unwrap_or(false);
+        let header_attrs = cfg.header_attrs.clone().unwrap_or_default();
+        let mut footer_attrs = cfg.footer_attrs.clone().unwrap_or_default();
+        if cfg.emit_version.unwrap_or(false) {
+            footer_attrs.insert(
+                "data-gitinfo-version".to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            );
+        }
+        let emit_semantic = cfg.emit_semantic.unwrap_or(false);
         let hyperlink = cfg.hyperlink.unwrap_or(false);
-        let repo_base = if hyperlink {
-            resolve_repo_base(&ctx.root)
+        let link_hash = cfg.link_hash.unwrap_or(hyperlink);
+        let link_branch = cfg.link_branch.unwrap_or(hyperlink);
+        let link_tag = cfg.link_tag.unwrap_or(hyperlink);
+        let git_root = resolve_git_root(runner, cfg.git_root.as_deref(), &ctx.root);
+        let mut branch = match cfg.branch {
+            Some(b) if b == "auto" => {
+                let b = git::resolve_auto_branch(runner, &git_root, |k| std::env::var(k).ok());
+                logging::debug(format_args!("branch = \"auto\" resolved to '{b}'"));
+                b
+            }
+            Some(b) => b,
+            None => "main".to_string(),
+        };
+        if cfg.exclude_self.unwrap_or(false) {
+            let me = git::self_identities(runner, &git_root);
+            logging::debug(format_args!("exclude-self: {me:?}"));
+            excluded_contributors.extend(me);
+        }
+        let repo_base = if hyperlink || link_hash || link_branch || link_tag || badge {
+            resolve_repo_base(runner, &git_root)
         } else {
             None
         };
-        let resolved_tag =
-            if let Some(t) = cfg.tag.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
-                t.to_string()
-            } else {
-                git::latest_tag_for_branch(&branch, &ctx.root)
+        let forge = repo_base
+            .as_deref()
+            .and_then(|b| resolve_forge(cfg.forge, b));
+        let links = repo_base.as_deref().map(|base| RepoLinks {
+            base,
+            forge,
+            templates: UrlTemplates {
+                commit: cfg.commit_url_template.as_deref(),
+                tag: cfg.tag_url_template.as_deref(),
+                branch: cfg.branch_url_template.as_deref(),
+                source: cfg.source_url_template.as_deref(),
+                blame: cfg.blame_url_template.as_deref(),
+            },
+        });
+        let primary_link = cfg.primary_link.unwrap_or_default();
+        let pin_ref = cfg
+            .pin_ref
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        if let Some(r) = pin_ref
+            && !git::verify_rev(runner, r, &git_root)
+        {
+            return Err(Error::msg(format!("pin-ref '{r}' does not name a commit")));
+        }
+        let custom_format = cfg
+            .custom_format
+            .as_deref()
+            .filter(|f| !f.trim().is_empty());
+        if let Some(f) = custom_format {
+            validate_custom_format(f)?;
+        }
+        let tag_override = cfg.tag.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty());
+        let resolved_tag = if let Some(t) = tag_override {
+            t.to_string()
+        } else if let Some(r) = pin_ref {
+            git::nearest_tag_for_commit(runner, r, &git_root)
+                .unwrap_or_else(|| "No tags found".to_string())
+        } else {
+            match cfg.tag_source.unwrap_or_default() {
+                TagSource::Branch => git::latest_tag_for_branch_with(runner, &branch, &git_root),
+                TagSource::Repo => git::latest_tag_in_repo(runner, &git_root),
+            }
+        };
+        // An explicit tag pins every chapter, whatever the scope.
+        let tag_scope = if tag_override.is_some() {
+            TagScope::Repo
+        } else {
+            cfg.tag_scope.unwrap_or_default()
+        };
+        let chapter_tags: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+        let no_tag_fallback = cfg.no_tag_fallback.unwrap_or_default();
+        let no_tag_text = cfg.no_tag_text.clone().unwrap_or_else(|| "-".to_string());
+        let changed_since_tag = cfg.changed_since_tag.unwrap_or(false);
+        let staleness_classes = cfg.staleness_classes.unwrap_or(false);
+        let stale_days = cfg.stale_days.unwrap_or(180);
+        let skip_untracked = cfg.skip_untracked.unwrap_or(false);
+        let untracked_text = cfg
+            .untracked_text
+            .clone()
+            .unwrap_or_else(|| "uncommitted".to_string());
+        let sanitize = cfg.sanitize.unwrap_or(true);
+        let markdown = cfg.format.unwrap_or_default() == TemplateFormat::Markdown;
+        let compact_output = cfg.compact_output.unwrap_or(false);
+        let minify = cfg.minify.unwrap_or(false);
+        let minified = |html: String| if minify { minify_html(&html) } else { html };
+        // Inline styles are the same on every page.
+        let block_style = |align: &str, margin: &[String; 4]| {
+            let style = style_block(&font_size, align, margin);
+            match (html_output, minify) {
+                (false, _) => String::new(),
+                (true, true) => minify_css(&style),
+                (true, false) => style,
+            }
+        };
+        let header_style = block_style(&align_header, &margin_header);
+        let footer_style = block_style(&align_footer, &margin_footer);
+        let validate_markdown = cfg.validate_markdown.unwrap_or(false);
+        let exclude_titles = cfg.exclude_titles.clone().unwrap_or_default();
+        let exclude_titles_ignore_case = cfg.exclude_titles_ignore_case.unwrap_or(false);
+        let index_path = if cfg.skip_index.unwrap_or(false) {
+            index_chapter_path(&book)
+        } else {
+            None
+        };
+        let changed_tmpl = cfg
+            .changed_since_tag_message
+            .clone()
+            .unwrap_or_else(|| "updated since {{tag}}".to_string());
+
+        let chapter_overrides = take_frontmatter_overrides(&mut book);
+
+        let mut templates = Vec::new();
+        if show_header {
+            templates.push(header_tmpl.as_str());
+            templates.extend(
+                chapter_overrides
+                    .values()
+                    .filter_map(|o| o.header.as_deref()),
+            );
+        }
+        if show_footer {
+            templates.extend([footer_tmpl.as_str(), changed_tmpl.as_str()]);
+            templates.extend(template_rules.templates());
+            templates.extend(
+                chapter_overrides
+                    .values()
+                    .filter_map(|o| o.footer.as_deref()),
+            );
+        }
+        let uses_repo_name =
+            contributors_enabled || templates.iter().any(|t| t.contains("{{repo-name}}"));
+        let repo_name = if uses_repo_name {
+            let base = repo_base
+                .clone()
+                .or_else(|| resolve_repo_base(runner, &git_root));
+            repo_name(base.as_deref(), &git_root)
+        } else {
+            String::new()
+        };
+        let mut needed = NeededFields::analyse(
+            templates,
+            FieldFeatures {
+                link_hash: link_hash && repo_base.is_some(),
+                chapter_tags: tag_scope == TagScope::Chapter,
+                tag_falls_back_to_hash: no_tag_fallback == NoTagFallback::Hash,
+                staleness: staleness_classes && show_footer,
+            },
+        );
+        if self.footer_fn.is_some() && show_footer {
+            // A footer function may read any field of `ChapterGitInfo`.
+            needed = NeededFields {
+                custom: needed.custom,
+                exact_tag: needed.exact_tag,
+                ..NeededFields::ALL
+            };
+        }
+        let sidebar_data = html_output && cfg.sidebar_data.unwrap_or(false);
+        let embed_meta = cfg.embed_metadata.unwrap_or(false);
+        if sidebar_data || embed_meta {
+            needed = NeededFields {
+                short: true,
+                long: true,
+                date: true,
+                author: true,
+                subject: true,
+                ..needed
             };
+        }
+        let sidebar_entries = RefCell::new(BTreeMap::new());
+        let log_opts = git::LogOptions {
+            follow: cfg.follow_renames.unwrap_or(true),
+            no_merges: cfg.ignore_merges.unwrap_or(false),
+        };
+        let commit_cache = CommitCache::new(needed, log_opts);
 
-        if !git::verify_branch(&branch, &ctx.root) {
-            eprintln!(
-                "[mdbook-gitinfo] Warning: Branch '{}' not found, falling back to 'main'",
+        if !git::verify_branch_with(runner, &branch, &git_root) {
+            logging::warn(format_args!(
+                "Branch '{}' not found, falling back to 'main'",
                 branch
-            );
+            ));
             branch = "main".to_string();
         }
+        // Revision all metadata is read from: the pinned ref, or the branch tip.
+        let log_rev = pin_ref
+            .map(str::to_string)
+            .unwrap_or_else(|| git::branch_ref(&branch));
 
-        // Pre-compute the global contributors HTML for non-inline sources.
-        // Inline source is resolved per token instance (args).
-        let contributors_html_global: Option<String> = if contributors_enabled {
-            match contributors_source {
-                ContributorsSource::Git => {
-                    match git::get_contributor_usernames_from_shortlog(&ctx.root) {
-                        Ok(users) => {
-                            let filtered: Vec<String> = users
-                                .into_iter()
-                                .filter(|u| !excluded_contributors.contains(u))
-                                .collect();
-                            let visible: Vec<String> = filtered
-                                .iter()
-                                .take(contributors_max_visible)
-                                .cloned()
-                                .collect();
-                            let hidden: Vec<String> = filtered
-                                .iter()
-                                .skip(contributors_max_visible)
-                                .cloned()
-                                .collect();
-
-                            match render_contributors_hbs(
-                                contributors_title,
-                                contributors_message,
-                                &visible,
-                                &hidden,
-                            ) {
-                                Ok(html) => Some(html),
-                                Err(e) => {
-                                    eprintln!(
-                                        "[mdbook-gitinfo] Warning: unable to render contributors template: {e}"
-                                    );
-                                    Some(String::new())
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "[mdbook-gitinfo] Warning: unable to get contributors from git: {e}"
-                            );
-                            Some(String::new())
-                        }
-                    }
-                }
+        // Chapters changed since the tag, computed once for the whole range.
+        // `None` means no filtering (mode off, or no tag to compare against).
+        let changed_paths: Option<HashSet<String>> = if !changed_since_tag {
+            None
+        } else if resolved_tag.is_empty() || resolved_tag.contains("No tags found") {
+            logging::warn(format_args!(
+                "changed-since-tag is set but no tag was found; adding footers to every chapter"
+            ));
+            None
+        } else {
+            match git::files_changed_since(runner, &resolved_tag, &branch, &git_root) {
+                Ok(paths) => Some(paths),
+                Err(e) => {
+                    logging::warn(format_args!(
+                        "unable to list changes since '{}': {e}",
+                        resolved_tag
+                    ));
+                    None
+                }
+            }
+        };
+
+        // Repository-level metadata for the contributors template.
+        let repo_head = if contributors_enabled {
+            git::last_commit_format_at(runner, "%h%x00%H%x00%cI", &log_rev, ".", &git_root)
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let mut repo_fields = repo_head.split('\0');
+        let repo_hash = repo_fields.next().unwrap_or_default().to_string();
+        let repo_long = repo_fields.next().unwrap_or_default().to_string();
+        let repo_date = repo_fields
+            .next()
+            .map(|raw| {
+                format_commit_datetime(
+                    raw,
+                    cfg.timezone.as_deref(),
+                    date_format,
+                    time_format,
+                    show_date,
+                    show_time,
+                )
+            })
+            .unwrap_or_default();
+
+        // The file source is read up front so its e-mails can feed the renderer.
+        let contributors_path = ctx.root.join(&contributors_file);
+        let file_source =
+            contributors_enabled && matches!(contributors_source, ContributorsSource::File);
+        if file_source
+            && cfg.contributors_file_required.unwrap_or(false)
+            && !contributors_path.is_file()
+        {
+            return Err(Error::msg(format!(
+                "contributors-file-required is set but {} does not exist",
+                contributors_path.display()
+            )));
+        }
+        let file_entries = if file_source {
+            parse_contributors_file(&contributors_path)
+        } else {
+            Vec::new()
+        };
+        let contributor_emails: BTreeMap<String, String> = file_entries
+            .iter()
+            .filter_map(|(name, email)| Some((name.clone(), email.clone()?)))
+            .collect();
+
+        let show_counts = cfg.contributors_show_counts.unwrap_or(false);
+        let contributors_sort = cfg
+            .contributors_sort
+            .unwrap_or(match cfg.contributors_order {
+                Some(ContributorsOrder::Stable) => ContributorsSort::Commits,
+                Some(ContributorsOrder::Name) => ContributorsSort::Name,
+                None if show_counts => ContributorsSort::Commits,
+                None => ContributorsSort::Name,
+            });
+        let mailmap_file = cfg.mailmap_file.as_ref().map(|f| ctx.root.join(f));
+        let include_coauthors = cfg.contributors_include_coauthors.unwrap_or(false);
+        let git_source =
+            contributors_enabled && matches!(contributors_source, ContributorsSource::Git);
+        let chapter_scope =
+            git_source && cfg.contributors_scope.unwrap_or_default() == ContributorsScope::Chapter;
+        // Git contributors of `path` (all files for `None`) in display order,
+        // with commit counts when they are shown. Sorted before
+        // `contributors-max-visible` cuts the list, so the most relevant
+        // people stay inline.
+        let git_contributors =
+            |path: Option<&str>| -> Result<(Vec<String>, BTreeMap<String, usize>), Error> {
+                let mailmap = mailmap_file.as_deref();
+                let mut counts =
+                    git::get_contributor_counts_from_shortlog(runner, &git_root, mailmap, path)?;
+                if include_coauthors {
+                    // Co-authors missing from shortlog join the list; known names add up.
+                    counts = git::merge_contributor_counts(
+                        counts,
+                        git::get_coauthor_counts(runner, &git_root, path)?,
+                    );
+                }
+                let first_seen = if contributors_sort == ContributorsSort::FirstSeen {
+                    git::get_contributors_first_seen(runner, &git_root, mailmap, path)?
+                } else {
+                    Vec::new()
+                };
+                let shown = if show_counts {
+                    counts.iter().cloned().collect()
+                } else {
+                    BTreeMap::new()
+                };
+                Ok((
+                    git::sort_contributors(counts, &first_seen, contributors_sort),
+                    shown,
+                ))
+            };
+        let mut book_contributors = (git_source && !chapter_scope).then(|| git_contributors(None));
+        let contributor_counts = match &mut book_contributors {
+            Some(Ok((_, counts))) => std::mem::take(counts),
+            _ => BTreeMap::new(),
+        };
+
+        // Inline names are forge usernames: with `hyperlink` they link to
+        // profiles on the forge hosting the repository.
+        let profile_base = repo_base
+            .as_deref()
+            .filter(|_| hyperlink && matches!(contributors_source, ContributorsSource::Inline))
+            .and_then(forge_host);
+
+        // The title is escaped by the Handlebars template, so plain values suffice.
+        let contributors_title = render_template(
+            contributors_title,
+            &TemplateValues {
+                hash: &repo_hash,
+                long: &repo_long,
+                date: &repo_date,
+                sep: &separator,
+                branch: &branch,
+                tag: &resolved_tag,
+                build_date: &build_date,
+                repo_name: &repo_name,
+                ..Default::default()
+            },
+        );
+        let contributors_template = cfg.contributors_template.as_ref().map(|f| ctx.root.join(f));
+        let contributors = ContributorsView {
+            title: &contributors_title,
+            message: contributors_message,
+            excluded: &excluded_contributors,
+            max_visible: contributors_max_visible,
+            max_hidden: cfg.contributors_max_hidden,
+            template_file: contributors_template.as_deref(),
+            template: OnceCell::new(),
+            minify,
+            options: ContributorsOptions {
+                separator: cfg.contributors_separator.as_deref().unwrap_or(""),
+                more_text: cfg.contributors_more_text.as_deref().unwrap_or("{n}+"),
+                hide_if_empty: cfg.contributors_hide_if_empty.unwrap_or(true),
+                emails: &contributor_emails,
+                email_mode: cfg.contributors_emails.unwrap_or_default(),
+                counts: &contributor_counts,
+                profile_base: profile_base.as_deref(),
+                meta: RepoMeta {
+                    hash: &repo_hash,
+                    long: &repo_long,
+                    date: &repo_date,
+                    branch: &branch,
+                    tag: &resolved_tag,
+                },
+            },
+        };
+
+        // Pre-compute the global contributors HTML for non-inline sources.
+        // Inline source is resolved per token instance (args).
+        let contributors_html_global: Option<String> = if contributors_enabled {
+            match contributors_source {
+                ContributorsSource::Git => match book_contributors {
+                    Some(Ok((users, _))) => Some(contributors.render(users)),
+                    Some(Err(e)) => {
+                        logging::warn(format_args!("unable to get contributors from git: {e}"));
+                        Some(String::new())
+                    }
+                    // Chapter scope: rendered per page.
+                    None => Some(String::new()),
+                },
                 ContributorsSource::File => {
-                    let file_path = ctx.root.join(&contributors_file);
-                    let users = parse_contributors_file(&file_path);
+                    let file_path = &contributors_path;
+                    let users: Vec<String> =
+                        file_entries.iter().map(|(name, _)| name.clone()).collect();
                     if users.is_empty() {
-                        eprintln!(
-                            "[mdbook-gitinfo] Warning: contributors-source is 'file' but no usernames found in {}",
+                        logging::warn(format_args!(
+                            "contributors-source is 'file' but no usernames found in {}",
                             file_path.display()
-                        );
+                        ));
                     }
-                    eprintln!(
-                        "[mdbook-gitinfo] contributors(file): path={} raw_lines={}",
+                    logging::debug(format_args!(
+                        "contributors(file): path={} raw_lines={}",
                         file_path.display(),
                         users.len(),
-                    );
-                    let filtered: Vec<String> = users
-                        .into_iter()
-                        .filter(|u| !excluded_contributors.contains(u))
-                        .collect();
-
-                    let visible: Vec<String> = filtered
+                    ));
+                    let filtered = users
                         .iter()
-                        .take(contributors_max_visible)
-                        .cloned()
-                        .collect();
-                    let hidden: Vec<String> = filtered
-                        .iter()
-                        .skip(contributors_max_visible)
-                        .cloned()
-                        .collect();
-
-                    eprintln!(
-                        "[mdbook-gitinfo] contributors(file): path={} filtered={}",
+                        .filter(|u| !excluded_contributors.contains(*u))
+                        .count();
+                    logging::debug(format_args!(
+                        "contributors(file): path={} filtered={}",
                         file_path.display(),
-                        filtered.len()
-                    );
-                    match render_contributors_hbs(
-                        contributors_title,
-                        contributors_message,
-                        &visible,
-                        &hidden,
-                    ) {
-                        Ok(html) => Some(html),
-                        Err(e) => {
-                            eprintln!(
-                                "[mdbook-gitinfo] Warning: unable to render contributors template: {e}"
-                            );
-                            Some(String::new())
-                        }
+                        filtered
+                    ));
+                    Some(contributors.render(users))
+                }
+                ContributorsSource::Env => {
+                    let var = cfg
+                        .contributors_env
+                        .as_deref()
+                        .unwrap_or("GITINFO_CONTRIBUTORS");
+                    let users = std::env::var(var)
+                        .map(|v| parse_contributors_env(&v))
+                        .unwrap_or_default();
+                    if users.is_empty() {
+                        logging::warn(format_args!(
+                            "contributors-source is 'env' but ${var} is unset or empty"
+                        ));
                     }
+                    Some(contributors.render(users))
                 }
                 ContributorsSource::Inline => {
                     // Inline is per-token; global HTML is empty.
@@ -329,138 +1477,402 @@ impl Preprocessor for GitInfo {
             None
         };
 
-        let content_dir = ctx.config.book.src.clone();
+        // Chapter paths are handed to Git relative to `git_root`.
+        let content_dir = book_prefix(&git_root, &ctx.root).join(&ctx.config.book.src);
 
-        book.for_each_mut(|item| {
+        let to_crlf = normalise_line_endings(&mut book, cfg.line_ending.unwrap_or_default());
+
+        // `decorate_chapters` recurses into sub-chapters itself, so only walk
+        // the top level here; `Book::for_each_mut` would visit them again.
+        book.items.iter_mut().for_each(|item| {
             decorate_chapters(item, &|ch| {
                 if let Some(path) = &ch.path {
-                    let full_path = PathBuf::from(&content_dir).join(path);
+                    let full_path = content_dir.join(path);
                     let path_str = full_path.to_string_lossy().replace('\\', "/");
+                    let included = chapter_filter.allows(&path.to_string_lossy());
+                    let overrides = chapter_overrides.get(path);
 
-                    let short_hash = git::get_git_output(
-                        ["log", "-1", "--format=%h", &branch, "--", &path_str],
-                        &ctx.root,
-                    ).unwrap_or_default();
-
-                    let long_hash = git::get_git_output(
-                        ["log", "-1", "--format=%H", &branch, "--", &path_str],
-                        &ctx.root,
-                    ).unwrap_or_default();
-
-                    let tag = resolved_tag.clone();
-
-                    let raw_date = git::get_git_output(
-                        ["log", "-1", "--format=%cI", &branch, "--", &path_str],
-                        &ctx.root,
-                    ).unwrap_or_default();
-
-                    let formatted_date = format_commit_datetime(
-                        &raw_date,
-                        cfg.timezone.as_deref(),
-                        date_format,
-                        time_format,
-                    );
-
-                    let (hash_disp, branch_disp) = if let (true, Some(base)) = (hyperlink, repo_base.as_ref()) {
-                        let commit_url = format!("{}/commit/{}", base, long_hash);
-                        let branch_url = format!("{}/tree/{}", base, branch);
-                        (
-                            format!(r#"<a href="{}">{}</a>"#, commit_url, short_hash),
-                            format!(r#"<a href="{}">{}</a>"#, branch_url, branch),
-                        )
-                    } else {
-                        (short_hash.clone(), branch.clone())
-                    };
-
-                    let tag_disp = if !tag.is_empty() && !tag.contains("No tags found") && hyperlink {
-                        if let Some(base) = repo_base.as_ref() {
-                            let url = tag_url(base, &tag);
-                            format!(r#"<a href="{}">{}</a>"#, url, tag)
-                        } else {
-                            tag.clone()
-                        }
-                    } else {
-                        "-".to_string()
-                    };
-
-                    if contributors_enabled {
+                    if contributors_enabled && included {
                         let html_global = contributors_html_global.as_deref().unwrap_or("");
-
-                        let inline_renderer = |args: &[String]| -> String {
-                            let filtered: Vec<String> = args.iter()
-                                .cloned()
-                                .filter(|u| !excluded_contributors.contains(u))
-                                .collect();
-
-                            let visible: Vec<String> = filtered.iter().take(contributors_max_visible).cloned().collect();
-                            let hidden: Vec<String> = filtered.iter().skip(contributors_max_visible).cloned().collect();
-
-                            match render_contributors_hbs(contributors_title, contributors_message, &visible, &hidden) {
-                                Ok(h) => h,
-                                Err(e) => {
-                                    eprintln!("[mdbook-gitinfo] Warning: unable to render contributors template: {e}");
-                                    String::new()
-                                }
+                        // Chapter scope asks Git once, on the first token of the page.
+                        let chapter_html = OnceCell::new();
+                        let global_renderer = || -> String {
+                            if !chapter_scope {
+                                return html_global.to_string();
                             }
+                            chapter_html
+                                .get_or_init(|| match git_contributors(Some(&path_str)) {
+                                    Ok((users, counts)) => contributors.render_with(
+                                        users,
+                                        ContributorsOptions {
+                                            counts: &counts,
+                                            ..contributors.options
+                                        },
+                                    ),
+                                    Err(e) => {
+                                        logging::warn(format_args!(
+                                            "unable to get contributors of {path_str} from git: {e}"
+                                        ));
+                                        String::new()
+                                    }
+                                })
+                                .clone()
                         };
 
+                        let inline_renderer =
+                            |args: &[String]| -> String { contributors.render(args.to_vec()) };
+
                         ch.content = replace_contributors_tokens(
                             &ch.content,
                             contributors_source,
-                            html_global,
+                            &global_renderer,
                             &inline_renderer,
+                            compact_output,
                         );
                     } else {
-                        // If contributors disabled, strip tokens entirely.
+                        // If contributors disabled or the chapter is excluded, strip tokens entirely.
                         ch.content = replace_contributors_tokens(
                             &ch.content,
                             contributors_source,
-                            "",
+                            &String::new,
                             &|_args| String::new(),
+                            compact_output,
                         );
                     }
 
-                    let render = |tmpl: &str| {
-                        render_template(
-                            tmpl,
-                            &hash_disp,
+                    if !included {
+                        logging::debug(format_args!("skipping excluded chapter {path_str}"));
+                        return;
+                    }
+
+                    // Contributors-only setups: nothing below is rendered, so
+                    // skip the per-chapter Git queries.
+                    if !show_header && !show_footer && !sidebar_data && !embed_meta {
+                        return;
+                    }
+
+                    if skip_untracked && !git::is_tracked(runner, &path_str, &git_root) {
+                        logging::debug(format_args!("skipping untracked chapter {path_str}"));
+                        return;
+                    }
+
+                    let commit = commit_cache.get(runner, &log_rev, &path_str, &git_root);
+                    let uncommitted = commit_cache.queries_git() && commit.short.is_empty();
+                    if uncommitted && skip_untracked {
+                        logging::debug(format_args!("skipping uncommitted chapter {path_str}"));
+                        return;
+                    }
+                    let short_hash = commit.short;
+                    let long_hash = commit.long;
+                    let raw_date = commit.date;
+
+                    let tag = chapter_tag(
+                        tag_scope,
+                        &resolved_tag,
+                        &long_hash,
+                        &chapter_tags,
+                        runner,
+                        &git_root,
+                    );
+
+                    // Free text from Git, the book or the file system is escaped
+                    // here, so values only ever carry the markup built below.
+                    let branch_html = escape_html(&branch);
+                    let tag_html = escape_html(&tag);
+                    let title_html = escape_html(&ch.name);
+                    let repo_name_html = escape_html(&repo_name);
+                    let author = escape_html(&commit.author);
+                    let committer = escape_html(&commit.committer);
+                    let email = escape_html(&commit.email);
+                    let subject =
+                        escape_html(&truncate_with_ellipsis(&commit.subject, subject_max_len));
+
+                    let dirty = if needed.dirty && git::is_dirty(runner, &path_str, &git_root) {
+                        dirty_marker.as_str()
+                    } else {
+                        ""
+                    };
+
+                    let custom = custom_format
+                        .filter(|_| needed.custom)
+                        .map(|f| {
+                            git::last_commit_format_with(
+                                runner, f, &log_rev, &path_str, &git_root, log_opts,
+                            )
+                            .unwrap_or_default()
+                        })
+                        .filter(|out| {
+                            let single = !out.contains(['\n', '\r']);
+                            if !single {
+                                logging::warn(format_args!(
+                                    "custom-format printed several lines for {path_str}; leaving {{{{custom}}}} empty"
+                                ));
+                            }
+                            single
+                        })
+                        .map(|out| escape_html(&out))
+                        .unwrap_or_default();
+
+                    let commit_dt = parse_commit_datetime(&raw_date);
+                    let age_days = days_since(&raw_date, now);
+                    let days_since = age_days.map(|d| d.to_string()).unwrap_or_default();
+                    let age = commit_dt
+                        .map(|dt| relative_to(dt, now, &age_strings))
+                        .unwrap_or_default();
+
+                    let formatted_date = match (date_style, commit_dt) {
+                        (_, None) => "unknown".to_string(),
+                        (DateStyle::Relative, Some(_)) => age.clone(),
+                        (DateStyle::Absolute, Some(dt)) => format_datetime(
+                            dt,
+                            cfg.timezone.as_deref(),
+                            date_format,
+                            time_format,
+                            show_date,
+                            show_time,
+                        ),
+                    };
+                    let date_disp = if emit_semantic {
+                        time_element(&raw_date, &formatted_date)
+                    } else {
+                        formatted_date
+                    };
+
+                    let has_tag = !tag.is_empty() && !tag.contains("No tags found");
+
+                    let hash_disp = if let (true, Some(links)) = (link_hash, links.as_ref()) {
+                        let url = hash_link_url(
+                            primary_link,
+                            links,
                             &long_hash,
-                            &tag_disp,
-                            &formatted_date,
-                            &separator,
-                            &branch_disp,
-                        )
+                            has_tag.then_some(tag.as_str()),
+                        );
+                        link_html(&url, &escape_html(&short_hash))
+                    } else {
+                        escape_html(&short_hash)
+                    };
+
+                    let branch_text = if badge {
+                        branch_badge(&branch_html, forge.map(Forge::name))
+                    } else {
+                        branch_html
+                    };
+                    let branch_disp = if let (true, Some(links)) = (link_branch, links.as_ref()) {
+                        let branch_url = links.branch(&branch);
+                        link_html(&branch_url, &branch_text)
+                    } else {
+                        branch_text
+                    };
+
+                    let tag_disp = if !has_tag {
+                        missing_tag_display(no_tag_fallback, &no_tag_text, &branch_disp, &hash_disp)
+                    } else if let (true, Some(links)) = (link_tag, links.as_ref()) {
+                        link_html(&links.tag(&tag), &tag_html)
+                    } else {
+                        tag_html
+                    };
+
+                    let exact_tag = if needed.exact_tag && !long_hash.is_empty() {
+                        git::exact_tag(runner, &long_hash, &git_root)
+                    } else {
+                        None
+                    };
+                    let reference = best_ref(
+                        exact_tag.as_deref(),
+                        &short_hash,
+                        &long_hash,
+                        &branch,
+                        links.as_ref(),
+                        LinkKinds {
+                            hash: link_hash,
+                            tag: link_tag,
+                            branch: link_branch,
+                        },
+                    );
+                    let (source, blame) = match links.as_ref().filter(|_| hyperlink) {
+                        Some(links) => {
+                            let rev = pin_ref.unwrap_or(&branch);
+                            (
+                                escape_html(&links.source(rev, &path_str)),
+                                escape_html(&links.blame(rev, &path_str)),
+                            )
+                        }
+                        None => (String::new(), String::new()),
+                    };
+
+                    let chapter_number = ch
+                        .number
+                        .as_ref()
+                        .map(|n| n.to_string().trim_end_matches('.').to_string())
+                        .unwrap_or_default();
+                    let metadata = (sidebar_data || embed_meta).then(|| ChapterMetadata {
+                        title: ch.name.clone(),
+                        number: chapter_number.clone(),
+                        hash: short_hash.clone(),
+                        long: long_hash.clone(),
+                        date: raw_date.clone(),
+                        age: age.clone(),
+                        author: commit.author.clone(),
+                        subject: commit.subject.clone(),
+                        branch: branch.clone(),
+                        tag: has_tag.then(|| tag.clone()),
+                    });
+                    let values = TemplateValues {
+                        hash: &hash_disp,
+                        long: &long_hash,
+                        tag: &tag_disp,
+                        date: &date_disp,
+                        sep: &separator,
+                        branch: &branch_disp,
+                        chapter_title: &title_html,
+                        chapter_number: &chapter_number,
+                        days_since: &days_since,
+                        custom: &custom,
+                        age: &age,
+                        author: &author,
+                        committer: &committer,
+                        email: &email,
+                        subject: &subject,
+                        dirty,
+                        build_date: &build_date,
+                        reference: &reference,
+                        source: &source,
+                        blame: &blame,
+                        repo_name: &repo_name_html,
+                        no_tag: !has_tag,
+                        ..Default::default()
+                    };
+                    let render = |tmpl: &str| {
+                        let converted;
+                        let tmpl = if markdown {
+                            converted = inline_markdown(tmpl);
+                            converted.as_str()
+                        } else {
+                            tmpl
+                        };
+                        let text = if sanitize {
+                            render_template_sanitized(tmpl, &values)
+                        } else {
+                            render_template(tmpl, &values)
+                        };
+                        if template_multiline {
+                            multiline_breaks(&text)
+                        } else {
+                            text
+                        }
                     };
 
                     if show_header {
-                        let style = style_block(&font_size, &align_header, &margin_header);
-                        let html  = wrap_block(true, &style, &render(&header_tmpl));
-                        let insertion = format!("{}\n\n", html);
-                        if !ch.content.starts_with(&insertion) {
-                            ch.content = format!("{}{}", insertion, ch.content);
+                        let tmpl = if uncommitted {
+                            &untracked_text
+                        } else {
+                            overrides
+                                .and_then(|o| o.header.as_ref())
+                                .unwrap_or(&header_tmpl)
+                        };
+                        let html = minified(wrap_block(
+                            header_wrapper,
+                            &[],
+                            &header_style,
+                            &header_attrs,
+                            &render(tmpl),
+                        ));
+                        ch.content = upsert_marked_block(
+                            &ch.content,
+                            Placement::Header,
+                            &html,
+                            compact_output,
+                        );
+                    }
+
+                    let title_hidden =
+                        title_excluded(&exclude_titles, &ch.name, exclude_titles_ignore_case);
+                    let is_index = index_path.as_ref() == Some(path);
+                    let footer = match &changed_paths {
+                        _ if title_hidden || is_index => None,
+                        _ if uncommitted => Some((untracked_text.as_str(), "gitinfo-untracked")),
+                        Some(changed) if changed.contains(&path_str) => {
+                            Some((changed_tmpl.as_str(), "gitinfo-changed"))
+                        }
+                        Some(_) => None,
+                        None => Some((
+                            overrides
+                                .and_then(|o| o.footer.as_deref())
+                                .or_else(|| template_rules.template_for(&path.to_string_lossy()))
+                                .unwrap_or(&footer_tmpl),
+                            "",
+                        )),
+                    };
+                    if let (true, Some((tmpl, class))) = (show_footer, footer) {
+                        let stale = staleness_class(staleness_classes, stale_days, age_days);
+                        let body = match &self.footer_fn {
+                            Some(f) => f(&ChapterGitInfo {
+                                chapter_title: ch.name.clone(),
+                                chapter_number: chapter_number.clone(),
+                                path: path_str.clone(),
+                                hash: short_hash.clone(),
+                                long: long_hash.clone(),
+                                date: raw_date.clone(),
+                                days_since: age_days,
+                                branch: branch.clone(),
+                                tag: if has_tag { tag.clone() } else { String::new() },
+                                author: commit.author.clone(),
+                                committer: commit.committer.clone(),
+                                email: commit.email.clone(),
+                                subject: commit.subject.clone(),
+                                dirty: !dirty.is_empty(),
+                            }),
+                            None => render(tmpl),
+                        };
+                        let html = minified(wrap_block(
+                            footer_wrapper,
+                            &[class, stale],
+                            &footer_style,
+                            &footer_attrs,
+                            &body,
+                        ));
+                        ch.content = upsert_marked_block(
+                            &ch.content,
+                            Placement::Footer,
+                            &html,
+                            compact_output,
+                        );
+                        if validate_markdown && !footer_is_top_level(&ch.content) {
+                            logging::warn(format_args!(
+                                "footer in {path_str} is not a top-level block; check how the chapter ends"
+                            ));
                         }
                     }
 
-                    if show_footer {
-                        let style = style_block(&font_size, &align_footer, &margin_footer);
-                        let html  = wrap_block(false, &style, &render(&footer_tmpl));
-                        let needs_leading_blank = !ch.content.ends_with("\n\n");
-                        let prefix = if needs_leading_blank { "\n\n" } else { "\n" };
-                        if !ch.content.contains(&html) {
-                            ch.content.push_str(prefix);
-                            ch.content.push_str(&html);
-                            ch.content.push('\n');
+                    if let Some(meta) = metadata {
+                        if embed_meta {
+                            ch.content = embed_metadata(&ch.content, &meta);
+                        }
+                        if sidebar_data {
+                            sidebar_entries
+                                .borrow_mut()
+                                .insert(path.to_string_lossy().replace('\\', "/"), meta);
                         }
                     }
                 }
             });
         });
 
+        restore_line_endings(&mut book, &to_crlf);
+
+        if sidebar_data && let Err(e) = write_sidebar_data(&ctx.root, &sidebar_entries.into_inner())
+        {
+            if cfg.assets_strict.unwrap_or(false) {
+                return Err(e.context(format!("unable to write {SIDEBAR_DATA_PATH}")));
+            }
+            logging::warn(format_args!("unable to write {SIDEBAR_DATA_PATH}: {e}"));
+        }
+
         Ok(book)
     }
 
     fn supports_renderer(&self, renderer: &str) -> Result<bool, Error> {
-        Ok(renderer == "html")
+        Ok(self.renderers.iter().any(|r| r == renderer))
     }
 }
 
@@ -469,8 +1881,22 @@ impl Preprocessor for GitInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::git_in;
     use std::io::Write;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
+
+    fn commit_file(dir: &Path, name: &str, body: &str) -> String {
+        fs::write(dir.join(name), body).unwrap();
+        git_in(dir, &["add", name]);
+        git_in(dir, &["commit", "-q", "-m", name]);
+        git_in(dir, &["rev-parse", "HEAD"])
+    }
+
+    fn init_repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        git_in(dir.path(), &["init", "-q", "-b", "main"]);
+        dir
+    }
 
     fn inline_renderer(args: &[String]) -> String {
         format!(r#"<div class="R">{}</div>"#, args.join(","))
@@ -480,8 +1906,13 @@ mod tests {
     fn token_replaced_only_when_standalone_line() {
         let input = "before\n{% contributors alice bob %}\nafter\n";
 
-        let out =
-            replace_contributors_tokens(input, ContributorsSource::Inline, "", &inline_renderer);
+        let out = replace_contributors_tokens(
+            input,
+            ContributorsSource::Inline,
+            &String::new,
+            &inline_renderer,
+            false,
+        );
 
         assert!(out.contains(r#"<div class="R">alice,bob</div>"#));
         assert!(out.contains("before"));
@@ -493,8 +1924,13 @@ mod tests {
     fn token_not_replaced_inside_fenced_code_block() {
         let input = "```md\n{% contributors alice %}\n```\n";
 
-        let out =
-            replace_contributors_tokens(input, ContributorsSource::Inline, "", &inline_renderer);
+        let out = replace_contributors_tokens(
+            input,
+            ContributorsSource::Inline,
+            &String::new,
+            &inline_renderer,
+            false,
+        );
 
         assert!(out.contains("{% contributors alice %}"));
         assert!(!out.contains(r#"<div class="R">"#));
@@ -504,8 +1940,13 @@ mod tests {
     fn token_not_replaced_inside_indented_code_block() {
         let input = "    {% contributors alice %}\n";
 
-        let out =
-            replace_contributors_tokens(input, ContributorsSource::Inline, "", &inline_renderer);
+        let out = replace_contributors_tokens(
+            input,
+            ContributorsSource::Inline,
+            &String::new,
+            &inline_renderer,
+            false,
+        );
 
         assert!(out.contains("{% contributors alice %}"));
         assert!(!out.contains(r#"<div class="R">"#));
@@ -515,8 +1956,13 @@ mod tests {
     fn token_not_replaced_inside_table_cell_or_inline_text() {
         let input = "| Value | Desc |\n| --- | --- |\n| inline | token `{% contributors %}` |\n";
 
-        let out =
-            replace_contributors_tokens(input, ContributorsSource::Inline, "", &inline_renderer);
+        let out = replace_contributors_tokens(
+            input,
+            ContributorsSource::Inline,
+            &String::new,
+            &inline_renderer,
+            false,
+        );
 
         assert!(out.contains("`{% contributors %}`"));
         assert!(!out.contains(r#"<div class="R">"#));
@@ -529,8 +1975,9 @@ mod tests {
         let out = replace_contributors_tokens(
             input,
             ContributorsSource::Git,
-            r#"<div id="GLOBAL"></div>"#,
+            &|| r#"<div id="GLOBAL"></div>"#.to_string(),
             &inline_renderer,
+            false,
         );
 
         assert!(out.contains(r#"<div id="GLOBAL"></div>"#));
@@ -544,11 +1991,2166 @@ mod tests {
         writeln!(f, "alice").unwrap();
         writeln!(f, "- bob").unwrap();
         writeln!(f, "* carol").unwrap();
-        writeln!(f, "").unwrap();
+        writeln!(f).unwrap();
         writeln!(f, "   ").unwrap();
 
         let users = parse_contributors_file(f.path());
+        let names: Vec<&str> = users.iter().map(|(n, _)| n.as_str()).collect();
+
+        assert_eq!(names, vec!["alice", "bob", "carol"]);
+        assert!(users.iter().all(|(_, e)| e.is_none()));
+    }
+
+    #[test]
+    fn parse_contributors_file_splits_name_and_email() {
+        let mut f = NamedTempFile::new().unwrap();
+        writeln!(f, "- Jane Doe <jane@x.com>").unwrap();
+        writeln!(f, "bob <bob@example.org>").unwrap();
+        writeln!(f, "carol").unwrap();
+        writeln!(f, "<only@email>").unwrap();
+
+        let users = parse_contributors_file(f.path());
+
+        assert_eq!(
+            users,
+            vec![
+                ("Jane Doe".to_string(), Some("jane@x.com".to_string())),
+                ("bob".to_string(), Some("bob@example.org".to_string())),
+                ("carol".to_string(), None),
+                ("<only@email>".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_contributors_env_splits_on_commas_and_newlines() {
+        let users = parse_contributors_env(" jane, john\nalice\r\n,, \nbob ");
+        assert_eq!(users, vec!["jane", "john", "alice", "bob"]);
+        assert!(parse_contributors_env("").is_empty());
+        assert!(parse_contributors_env(" , \n").is_empty());
+    }
+
+    #[test]
+    fn tag_scope_repo_shares_one_tag_across_chapters() {
+        let repo = init_repo();
+        let first = commit_file(repo.path(), "a.md", "a");
+        git_in(repo.path(), &["tag", "v1.0.0"]);
+        let second = commit_file(repo.path(), "b.md", "b");
+        git_in(repo.path(), &["tag", "v2.0.0"]);
+
+        let cache = RefCell::new(HashMap::new());
+        let a = chapter_tag(
+            TagScope::Repo,
+            "v2.0.0",
+            &first,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+        let b = chapter_tag(
+            TagScope::Repo,
+            "v2.0.0",
+            &second,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+
+        assert_eq!(a, "v2.0.0");
+        assert_eq!(b, "v2.0.0");
+        assert!(cache.borrow().is_empty());
+    }
+
+    #[test]
+    fn tag_scope_chapter_uses_nearest_tag_per_commit() {
+        let repo = init_repo();
+        let first = commit_file(repo.path(), "a.md", "a");
+        git_in(repo.path(), &["tag", "v1.0.0"]);
+        let second = commit_file(repo.path(), "b.md", "b");
+        git_in(repo.path(), &["tag", "v2.0.0"]);
+
+        let cache = RefCell::new(HashMap::new());
+        let a = chapter_tag(
+            TagScope::Chapter,
+            "v2.0.0",
+            &first,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+        let b = chapter_tag(
+            TagScope::Chapter,
+            "v2.0.0",
+            &second,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+
+        assert_eq!(a, "v1.0.0");
+        assert_eq!(b, "v2.0.0");
+        assert_eq!(cache.borrow().len(), 2);
+    }
+
+    #[test]
+    fn tag_scope_chapter_memoizes_by_commit() {
+        let repo = init_repo();
+        let first = commit_file(repo.path(), "a.md", "a");
+
+        let cache = RefCell::new(HashMap::new());
+        cache
+            .borrow_mut()
+            .insert(first.clone(), "cached".to_string());
+
+        let a = chapter_tag(
+            TagScope::Chapter,
+            "v2.0.0",
+            &first,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+        assert_eq!(a, "cached");
+    }
+
+    #[test]
+    fn tag_scope_chapter_reports_untagged_commit() {
+        let repo = init_repo();
+        let first = commit_file(repo.path(), "a.md", "a");
+
+        let cache = RefCell::new(HashMap::new());
+        let a = chapter_tag(
+            TagScope::Chapter,
+            "v2.0.0",
+            &first,
+            &cache,
+            &git::SystemGit::default(),
+            repo.path(),
+        );
+        assert_eq!(a, "No tags found");
+    }
+
+    fn ctx_for(root: &Path, toml_str: &str) -> PreprocessorContext {
+        let mut config = mdbook_preprocessor::config::Config::default();
+        let parsed: toml::Value = toml::from_str(toml_str).unwrap();
+        config.set("preprocessor.gitinfo", parsed).unwrap();
+        PreprocessorContext::new(root.to_path_buf(), config, "html".to_string())
+    }
+
+    fn chapter_contents(book: &Book) -> Vec<String> {
+        book.chapters().map(|ch| ch.content.clone()).collect()
+    }
+
+    /// A flat book with one chapter per `(path, content)` pair, titled after
+    /// the capitalised file stem (`intro.md` becomes "Intro").
+    fn book_of(files: &[(&str, &str)]) -> Book {
+        use mdbook_preprocessor::book::{BookItem, Chapter};
+
+        let mut book = Book::new();
+        for (path, content) in files {
+            let stem = Path::new(path).file_stem().unwrap().to_string_lossy();
+            let mut chars = stem.chars();
+            let title: String = chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default();
+            let ch = Chapter::new(&title, content.to_string(), *path, vec![]);
+            book.push_item(BookItem::Chapter(ch));
+        }
+        book
+    }
 
-        assert_eq!(users, vec!["alice", "bob", "carol"]);
+    /// Run the preprocessor over `book` rooted at `root`, returning each
+    /// chapter's rendered content.
+    fn run_book(root: &Path, toml: &str, book: Book) -> Vec<String> {
+        let out = GitInfo::new().run(&ctx_for(root, toml), book).unwrap();
+        chapter_contents(&out)
+    }
+
+    /// Commit `files` under `src/` of a fresh repository and render them.
+    fn render_chapters(toml: &str, files: &[(&str, &str)]) -> Vec<String> {
+        let repo = init_repo();
+        for (path, content) in files {
+            let rel = format!("src/{path}");
+            fs::create_dir_all(repo.path().join(&rel).parent().unwrap()).unwrap();
+            commit_file(repo.path(), &rel, content);
+        }
+        run_book(repo.path(), toml, book_of(files))
+    }
+
+    const INTRO: &[(&str, &str)] = &[("intro.md", "# Intro\n")];
+    const CONTRIBUTORS: &[(&str, &str)] = &[("intro.md", "{% contributors %}\n")];
+
+    /// Like [`render_chapters`], returning only the first chapter.
+    fn render_chapter(toml: &str, files: &[(&str, &str)]) -> String {
+        render_chapters(toml, files).swap_remove(0)
+    }
+
+    /// Render one chapter with `body` as its content, returning the result.
+    fn render_body(body: &str, toml: &str) -> String {
+        render_chapter(&format!("header = true\n{toml}"), &[("intro.md", body)])
+    }
+
+    #[test]
+    fn minify_shrinks_injected_blocks() {
+        let toml = r#"
+            template = "Updated   {{date}}\n    commit {{hash}}"
+            contributors = true
+            contributors-source = "inline"
+        "#;
+        let body = "# Intro\n\n{% contributors alice bob %}\n";
+        let default = render_body(body, toml);
+        let minified = render_body(body, &format!("{toml}\nminify = true"));
+
+        assert!(minified.len() < default.len(), "{minified}");
+        assert!(default.contains("margin:0 0 2em 0;"), "{default}");
+        assert!(minified.contains("margin:0 0 2em;"), "{minified}");
+        assert!(minified.contains("Updated "), "{minified}");
+        assert!(minified.contains("alice") && minified.contains("bob"));
+        for block in ["<footer", "<header", "gitinfo-contributors"] {
+            let html = minified.split(block).nth(1).unwrap();
+            let line = html.lines().next().unwrap();
+            assert!(!line.contains("  "), "{line}");
+        }
+    }
+
+    #[test]
+    fn empty_separator_leaves_no_stray_spacing() {
+        let default = render_body("# Intro\n", "separator = \"\"");
+        let footer = default.split("<footer").nth(1).unwrap();
+        assert!(footer.contains(" commit: "), "{footer}");
+        assert!(!default.contains("  "), "{default}");
+
+        let custom = render_body(
+            "# Intro\n",
+            "separator = \"\"\ntemplate = \"branch {{branch}} {{sep}} rev {{hash}}\"",
+        );
+        assert!(custom.contains("branch main rev "), "{custom}");
+        assert!(!custom.contains("  "), "{custom}");
+    }
+
+    fn has_bare_lf(s: &str) -> bool {
+        s.replace("\r\n", "").contains('\n')
+    }
+
+    #[test]
+    fn line_endings_are_preserved_by_default() {
+        let crlf = render_body("# Intro\r\n\r\nText\r\n", "");
+        assert!(crlf.contains("<footer"));
+        assert!(!has_bare_lf(&crlf), "{crlf:?}");
+
+        let lf = render_body("# Intro\n\nText\n", "");
+        assert!(lf.contains("<footer"));
+        assert!(!lf.contains('\r'), "{lf:?}");
+    }
+
+    #[test]
+    fn line_ending_can_be_forced() {
+        let lf = render_body("# Intro\r\n\r\nText\r\n", r#"line-ending = "lf""#);
+        assert!(!lf.contains('\r'), "{lf:?}");
+
+        let crlf = render_body("# Intro\n\nText\n", r#"line-ending = "crlf""#);
+        assert!(!has_bare_lf(&crlf), "{crlf:?}");
+        assert!(crlf.contains("# Intro\r\n"));
+    }
+
+    #[test]
+    fn upsert_marked_block_replaces_previous_footer() {
+        let first = upsert_marked_block(
+            "# Title\n",
+            Placement::Footer,
+            "<footer>one</footer>",
+            false,
+        );
+        let second = upsert_marked_block(&first, Placement::Footer, "<footer>two</footer>", false);
+
+        assert!(!second.contains("one"));
+        assert_eq!(second.matches("<footer>").count(), 1);
+        assert_eq!(second, first.replace("one", "two"));
+    }
+
+    #[test]
+    fn upsert_marked_block_replaces_previous_header() {
+        let first = upsert_marked_block(
+            "# Title\n",
+            Placement::Header,
+            "<header>one</header>",
+            false,
+        );
+        let second = upsert_marked_block(&first, Placement::Header, "<header>one</header>", false);
+
+        assert_eq!(first, second);
+        assert!(first.starts_with("<!-- gitinfo:header -->"));
+        assert!(first.ends_with("# Title\n"));
+    }
+
+    #[test]
+    fn compact_token_reuses_existing_blank_lines() {
+        let input = "before\n\n{% contributors %}\n\nafter\n";
+        let render = |compact| {
+            replace_contributors_tokens(
+                input,
+                ContributorsSource::Git,
+                &|| "<div>C</div>".to_string(),
+                &inline_renderer,
+                compact,
+            )
+        };
+
+        assert_eq!(render(false), "before\n\n\n<div>C</div>\n\n\nafter\n");
+        assert_eq!(render(true), "before\n\n<div>C</div>\n\nafter\n");
+    }
+
+    #[test]
+    fn compact_token_still_separates_html_from_adjacent_text() {
+        let input = "before\n{% contributors %}\nafter\n";
+        let out = replace_contributors_tokens(
+            input,
+            ContributorsSource::Git,
+            &|| "<div>C</div>".to_string(),
+            &inline_renderer,
+            true,
+        );
+        assert_eq!(out, "before\n\n<div>C</div>\n\nafter\n");
+
+        let at_edges = replace_contributors_tokens(
+            "{% contributors %}\n",
+            ContributorsSource::Git,
+            &|| "<div>C</div>".to_string(),
+            &inline_renderer,
+            true,
+        );
+        assert_eq!(at_edges, "<div>C</div>\n");
+    }
+
+    #[test]
+    fn token_before_rust_fence_leaves_code_block_untouched() {
+        let code = "```rust\nfn main() {\n    let x = 1;\n    assert_eq!(x, 1);\n}\n```\n";
+        let nested = "````markdown\n```rust\n{% contributors %}\n```\n````\n";
+        let input = format!("Intro\n{{% contributors %}}\n{code}{nested}");
+        for compact in [false, true] {
+            let out = replace_contributors_tokens(
+                &input,
+                ContributorsSource::Git,
+                &|| "<div>C</div>".to_string(),
+                &inline_renderer,
+                compact,
+            );
+            assert!(out.contains(&format!("<div>C</div>\n\n{code}")), "{out}");
+            assert!(out.ends_with(nested), "{out}");
+            assert_eq!(out.matches("<div>C</div>").count(), 1, "{out}");
+
+            let page = upsert_marked_block(&out, Placement::Footer, "<footer>f</footer>", compact);
+            assert!(page.contains(&format!("\n\n{code}")), "{page}");
+            assert!(page.contains(nested), "{page}");
+            assert!(page.ends_with("<!-- /gitinfo:footer -->\n"), "{page}");
+        }
+    }
+
+    #[test]
+    fn footer_closes_open_fence_and_skips_quoted_markers() {
+        let open = "```rust\nlet x = 1;\n";
+        let page = upsert_marked_block(open, Placement::Footer, "<footer>f</footer>", true);
+        assert!(
+            page.starts_with("```rust\nlet x = 1;\n```\n\n<!-- gitinfo:footer -->"),
+            "{page}"
+        );
+
+        let quoted = "```html\n<!-- gitinfo:footer -->\nold\n<!-- /gitinfo:footer -->\n```\n";
+        let page = upsert_marked_block(quoted, Placement::Footer, "<footer>f</footer>", false);
+        assert!(page.starts_with(quoted), "{page}");
+        assert!(
+            page.ends_with("<footer>f</footer>\n<!-- /gitinfo:footer -->\n"),
+            "{page}"
+        );
+    }
+
+    #[test]
+    fn compact_footer_and_header_keep_one_blank_line() {
+        let footer = "<footer>f</footer>";
+        let marked = "<!-- gitinfo:footer -->\n<footer>f</footer>\n<!-- /gitinfo:footer -->\n";
+
+        assert_eq!(
+            upsert_marked_block("text\n\n", Placement::Footer, footer, false),
+            format!("text\n\n\n{marked}")
+        );
+        assert_eq!(
+            upsert_marked_block("text\n\n", Placement::Footer, footer, true),
+            format!("text\n\n{marked}")
+        );
+        assert_eq!(
+            upsert_marked_block("text\n", Placement::Footer, footer, true),
+            format!("text\n\n{marked}")
+        );
+        assert_eq!(
+            upsert_marked_block("text", Placement::Footer, footer, true),
+            format!("text\n\n{marked}")
+        );
+
+        let header = "<header>h</header>";
+        let default = upsert_marked_block("\n# Title\n", Placement::Header, header, false);
+        let compact = upsert_marked_block("\n# Title\n", Placement::Header, header, true);
+        assert!(default.ends_with("-->\n\n\n# Title\n"));
+        assert!(compact.ends_with("-->\n\n# Title\n"));
+    }
+
+    #[test]
+    fn run_twice_yields_identical_output() {
+        use mdbook_preprocessor::book::{BookItem, Chapter};
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        commit_file(repo.path(), "src/nested.md", "# Nested\n");
+
+        let mut intro = Chapter::new("Intro", "# Intro\n".to_string(), "intro.md", vec![]);
+        intro.sub_items.push(BookItem::Chapter(Chapter::new(
+            "Nested",
+            "# Nested\n".to_string(),
+            "nested.md",
+            vec!["Intro".to_string()],
+        )));
+        let mut book = Book::new();
+        book.push_item(intro);
+
+        let ctx = ctx_for(repo.path(), "header = true\nfooter = true");
+        let once = GitInfo::new().run(&ctx, book).unwrap();
+        let twice = GitInfo::new().run(&ctx, once.clone()).unwrap();
+
+        assert_eq!(chapter_contents(&once), chapter_contents(&twice));
+        for content in chapter_contents(&twice) {
+            assert_eq!(content.matches("<footer").count(), 1);
+            assert_eq!(content.matches("<header").count(), 1);
+        }
+    }
+
+    #[test]
+    fn footer_exposes_chapter_title_and_number() {
+        use mdbook_preprocessor::book::{BookItem, SectionNumber};
+
+        let files = [("preface.md", "# Preface\n"), ("setup.md", "# Setup\n")];
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        for (path, content) in files {
+            commit_file(repo.path(), &format!("src/{path}"), content);
+        }
+
+        let mut book = book_of(&files);
+        if let BookItem::Chapter(setup) = &mut book.items[1] {
+            setup.number = Some(SectionNumber::new(vec![3]));
+        }
+        let toml = r#"template = "End of Chapter {{chapter-number}}: {{chapter-title}}""#;
+        let contents = run_book(repo.path(), toml, book);
+
+        assert!(contents[0].contains("End of Chapter : Preface"));
+        assert!(contents[1].contains("End of Chapter 3: Setup"));
+    }
+
+    #[test]
+    fn skip_index_drops_footer_of_landing_page_only() {
+        use mdbook_preprocessor::book::{BookItem, Chapter};
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src/guide")).unwrap();
+        for file in ["src/intro.md", "src/setup.md", "src/guide/README.md"] {
+            commit_file(repo.path(), file, "# Page\n");
+        }
+        let chapter = |name: &str, path: &str| {
+            BookItem::Chapter(Chapter::new(name, "# Page\n".to_string(), path, vec![]))
+        };
+        let render = |items: Vec<BookItem>, toml: &str| {
+            run_book(repo.path(), toml, Book { items })
+                .iter()
+                .map(|c| c.contains("gitinfo:footer"))
+                .collect::<Vec<_>>()
+        };
+        let book = || {
+            vec![
+                chapter("Intro", "intro.md"),
+                chapter("Setup", "setup.md"),
+                chapter("Guide", "guide/README.md"),
+            ]
+        };
+
+        assert_eq!(render(book(), "skip-index = true"), [false, true, true]);
+        assert_eq!(render(book(), "skip-index = false"), [true, true, true]);
+
+        // A top-level README is the landing page wherever it sits.
+        commit_file(repo.path(), "src/README.md", "# Page\n");
+        let mut items = book();
+        items.push(chapter("Home", "README.md"));
+        assert_eq!(
+            render(items, "skip-index = true"),
+            [true, true, true, false]
+        );
+    }
+
+    fn github_links() -> RepoLinks<'static> {
+        RepoLinks {
+            base: "https://github.com/o/r",
+            forge: Some(Forge::Github),
+            templates: UrlTemplates::default(),
+        }
+    }
+
+    #[test]
+    fn primary_link_commit_points_hash_at_commit() {
+        let url = hash_link_url(
+            PrimaryLink::Commit,
+            &github_links(),
+            "abc123",
+            Some("v1.0.0"),
+        );
+        assert_eq!(url, "https://github.com/o/r/commit/abc123");
+    }
+
+    #[test]
+    fn primary_link_tag_points_hash_at_release() {
+        let url = hash_link_url(PrimaryLink::Tag, &github_links(), "abc123", Some("v1.0.0"));
+        assert_eq!(url, "https://github.com/o/r/releases/tag/v1.0.0");
+    }
+
+    #[test]
+    fn primary_link_tag_falls_back_to_commit_without_tag() {
+        let url = hash_link_url(PrimaryLink::Tag, &github_links(), "abc123", None);
+        assert_eq!(url, "https://github.com/o/r/commit/abc123");
+    }
+
+    #[test]
+    fn changed_since_tag_only_marks_changed_chapters() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/stable.md", "# Stable\n");
+        commit_file(repo.path(), "src/moving.md", "# Moving\n");
+        git_in(repo.path(), &["tag", "v1.0.0"]);
+        commit_file(repo.path(), "src/moving.md", "# Moving\n\nmore\n");
+
+        let book = book_of(&[("stable.md", "# Stable\n"), ("moving.md", "# Moving\n")]);
+        let contents = run_book(repo.path(), "changed-since-tag = true", book);
+
+        assert!(!contents[0].contains("<footer"));
+        assert!(contents[1].contains(r#"class="gitinfo-footer gitinfo-changed""#));
+        assert!(contents[1].contains("updated since v1.0.0"));
+    }
+
+    #[test]
+    fn template_rules_select_footer_by_path() {
+        let contents = render_chapters(
+            r#"
+            template = "default footer"
+            template-rules = [{ pattern = "api/**", template = "API reference" }]
+            "#,
+            &[
+                ("api/client.md", "# Client\n"),
+                ("tutorial.md", "# Tutorial\n"),
+            ],
+        );
+
+        assert!(contents[0].contains("API reference"));
+        assert!(!contents[0].contains("default footer"));
+        assert!(contents[1].contains("default footer"));
+        assert!(!contents[1].contains("API reference"));
+    }
+
+    fn render_committed_and_new(toml: &str) -> Vec<String> {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/old.md", "# Old\n");
+        fs::write(repo.path().join("src/new.md"), "# New\n").unwrap();
+
+        let toml = format!("template = \"{{{{date}}}}{{{{sep}}}}{{{{hash}}}}\"\n{toml}");
+        let book = book_of(&[("old.md", "# Old\n"), ("new.md", "# New\n")]);
+        run_book(repo.path(), &toml, book)
+    }
+
+    #[test]
+    fn sidebar_data_maps_chapter_paths_to_metadata() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src/guide")).unwrap();
+        let hash = commit_file(repo.path(), "src/guide/setup.md", "# Setup\n");
+        git_in(repo.path(), &["tag", "v1.0"]);
+
+        let book = book_of(&[("guide/setup.md", "# Setup\n")]);
+        run_book(repo.path(), "sidebar-data = true", book);
+
+        let json = fs::read_to_string(repo.path().join(SIDEBAR_DATA_PATH)).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let entry = &data["guide/setup.md"];
+        assert_eq!(entry["title"], "Setup");
+        assert_eq!(entry["long"], hash.as_str());
+        assert!(hash.starts_with(entry["hash"].as_str().unwrap()));
+        assert_eq!(entry["author"], "tester");
+        assert_eq!(entry["subject"], "src/guide/setup.md");
+        assert_eq!(entry["branch"], "main");
+        assert_eq!(entry["tag"], "v1.0");
+        assert!(entry["date"].as_str().is_some_and(|d| !d.is_empty()));
+        assert_eq!(data.as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sidebar_data_is_off_by_default() {
+        let repo = init_repo();
+        let ctx = ctx_for(repo.path(), "");
+        GitInfo::new().run(&ctx, Book::new()).unwrap();
+        assert!(!repo.path().join(SIDEBAR_DATA_PATH).exists());
+    }
+
+    #[test]
+    fn embed_metadata_puts_parseable_json_at_chapter_top() {
+        use crate::metadata::METADATA_MARKER;
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        let hash = commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let toml = "embed-metadata = true\nheader = true";
+        let content = &run_book(repo.path(), toml, book_of(INTRO))[0];
+
+        let json = content
+            .strip_prefix(METADATA_MARKER)
+            .and_then(|rest| rest.split_once(" -->"))
+            .map(|(json, _)| json)
+            .unwrap_or_else(|| panic!("no metadata block at top:\n{content}"));
+        let meta: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(meta["title"], "Intro");
+        assert_eq!(meta["long"], hash.as_str());
+        assert_eq!(meta["branch"], "main");
+        assert_eq!(meta["subject"], "src/intro.md");
+        assert_eq!(content.matches(METADATA_MARKER).count(), 1);
+        assert!(!repo.path().join(SIDEBAR_DATA_PATH).exists());
+    }
+
+    #[test]
+    fn dirty_marker_follows_working_tree_state() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let render = |toml: &str| {
+            let toml = format!("template = \"[{{{{dirty}}}}]\"\n{toml}");
+            run_book(repo.path(), &toml, book_of(INTRO)).swap_remove(0)
+        };
+
+        assert!(render("").contains("[]"));
+
+        fs::write(repo.path().join("src/intro.md"), "# Intro\nedited\n").unwrap();
+        assert!(render("").contains("[*]"));
+        assert!(render(r#"dirty-marker = " (modified)""#).contains("[ (modified)]"));
+
+        git_in(repo.path(), &["checkout", "--", "src/intro.md"]);
+        assert!(render("").contains("[]"));
+    }
+
+    #[test]
+    fn uncommitted_chapter_renders_untracked_text() {
+        let contents = render_committed_and_new("");
+        assert!(!contents[0].contains("uncommitted"));
+        assert!(contents[0].contains(" • "));
+        assert!(contents[1].contains(r#"class="gitinfo-footer gitinfo-untracked""#));
+        assert!(contents[1].contains(">uncommitted</footer>"));
+        assert!(!contents[1].contains(" • "));
+    }
+
+    #[test]
+    fn untracked_text_is_configurable() {
+        let contents = render_committed_and_new(r#"untracked-text = "draft""#);
+        assert!(contents[1].contains(">draft</footer>"));
+    }
+
+    #[test]
+    fn skip_untracked_omits_footer_for_untracked_chapters() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/tracked.md", "# Tracked\n");
+        fs::write(repo.path().join("src/generated.md"), "# Generated\n").unwrap();
+
+        let book = book_of(&[
+            ("tracked.md", "# Tracked\n"),
+            ("generated.md", "# Generated\n"),
+        ]);
+        let contents = run_book(repo.path(), "skip-untracked = true", book);
+
+        assert!(contents[0].contains("<footer"));
+        assert_eq!(contents[1], "# Generated\n");
+    }
+
+    #[test]
+    fn pin_ref_reads_metadata_at_the_pinned_commit() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        let pinned = commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        let latest = commit_file(repo.path(), "src/intro.md", "# Intro\n\nmore\n");
+
+        let contents = run_book(repo.path(), r#"template = "{{long}}""#, book_of(INTRO));
+        assert!(contents[0].contains(&latest));
+
+        let toml = format!("template = \"{{{{long}}}}\"\npin-ref = \"{pinned}\"");
+        let content = &run_book(repo.path(), &toml, book_of(INTRO))[0];
+        assert!(content.contains(&pinned));
+        assert!(!content.contains(&latest));
+    }
+
+    #[test]
+    fn pin_ref_must_exist() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let ctx = ctx_for(repo.path(), r#"pin-ref = "does-not-exist""#);
+        let err = GitInfo::new().run(&ctx, Book::new()).unwrap_err();
+        assert!(err.to_string().contains("does-not-exist"));
+    }
+
+    fn names(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("user{i}")).collect()
+    }
+
+    #[test]
+    fn split_contributors_caps_hidden_list() {
+        let users = names(10);
+        let (visible, hidden, overflow) = split_contributors(&users, 3, Some(4));
+        assert_eq!(visible, &users[..3]);
+        assert_eq!(hidden, &users[3..7]);
+        assert_eq!(overflow, 3);
+    }
+
+    #[test]
+    fn split_contributors_without_cap_lists_everyone() {
+        let users = names(10);
+        let (visible, hidden, overflow) = split_contributors(&users, 3, None);
+        assert_eq!(visible.len(), 3);
+        assert_eq!(hidden.len(), 7);
+        assert_eq!(overflow, 0);
+    }
+
+    #[test]
+    fn split_contributors_short_list_has_no_overflow() {
+        let users = names(2);
+        let (visible, hidden, overflow) = split_contributors(&users, 3, Some(0));
+        assert_eq!(visible.len(), 2);
+        assert!(hidden.is_empty());
+        assert_eq!(overflow, 0);
+    }
+
+    /// A view with the bundled template and default options.
+    fn plain_view(excluded: &BTreeSet<String>) -> ContributorsView<'_> {
+        ContributorsView {
+            title: "Contributors",
+            message: None,
+            excluded,
+            max_visible: 24,
+            max_hidden: None,
+            options: ContributorsOptions::default(),
+            template_file: None,
+            template: OnceCell::new(),
+            minify: false,
+        }
+    }
+
+    #[test]
+    fn contributors_view_summarises_overflow() {
+        let excluded = BTreeSet::new();
+        let view = ContributorsView {
+            max_visible: 2,
+            max_hidden: Some(1),
+            ..plain_view(&excluded)
+        };
+        let html = view.render(names(6));
+
+        assert!(html.contains("user2"));
+        assert!(!html.contains("user3"));
+        assert!(html.contains("and 3 others"));
+    }
+
+    #[test]
+    fn contributors_template_file_replaces_bundled_template() {
+        let repo = init_repo();
+        fs::write(
+            repo.path().join("people.hbs"),
+            "<ul class=\"brand\" title=\"{{title}}\">{{#each visible}}<li>{{this}}</li>{{/each}}\
+             {{#each hidden}}<li class=\"more\">{{this}}</li>{{/each}}</ul>",
+        )
+        .unwrap();
+        fs::write(repo.path().join("broken.hbs"), "{{#each visible}}").unwrap();
+        let render = |file: &str| {
+            let book = book_of(&[("intro.md", "{% contributors alice bob %}\n")]);
+            let toml = format!(
+                "contributors = true\ncontributors-source = \"inline\"\n\
+                 contributors-max-visible = 1\ncontributors-template = \"{file}\""
+            );
+            run_book(repo.path(), &toml, book).swap_remove(0)
+        };
+
+        let custom = render("people.hbs");
+        assert!(
+            custom.contains(
+                r#"<ul class="brand" title="Contributors"><li>alice</li><li class="more">bob</li></ul>"#
+            ),
+            "{custom}"
+        );
+        for fallback in ["missing.hbs", "broken.hbs"] {
+            let content = render(fallback);
+            assert!(content.contains("gitinfo-contributors"), "{content}");
+            assert!(!content.contains("brand"));
+        }
+    }
+
+    #[test]
+    fn contributors_template_is_compiled_lazily_once() {
+        let excluded = BTreeSet::new();
+        let view = plain_view(&excluded);
+        assert!(view.template.get().is_none());
+
+        let first = view.render(names(2));
+        let compiled = view.template.get().map(|t| t as *const _);
+        assert!(matches!(view.template.get(), Some(Ok(_))));
+
+        assert_eq!(view.render(names(2)), first);
+        assert_eq!(view.template.get().map(|t| t as *const _), compiled);
+    }
+
+    #[test]
+    fn custom_format_is_exposed_as_placeholder() {
+        let content = render_chapter(
+            r#"
+            template = "by {{custom}}"
+            custom-format = "%an <%ae>"
+            "#,
+            INTRO,
+        );
+
+        assert!(content.contains("by tester &lt;tester@example.com&gt;"));
+    }
+
+    #[test]
+    fn custom_format_rejects_multi_line_output() {
+        assert!(validate_custom_format("%an <%ae>").is_ok());
+        assert!(validate_custom_format("%an%n%ae").is_err());
+        assert!(validate_custom_format("%an\n%ae").is_err());
+        assert!(validate_custom_format("%s%x0a%b").is_err());
+        assert!(validate_custom_format("%s%x0A").is_err());
+        assert!(validate_custom_format("%s%x0D").is_err());
+        assert!(validate_custom_format("%b").is_err());
+        assert!(validate_custom_format("%B").is_err());
+        assert!(validate_custom_format("%+b").is_err());
+        assert!(validate_custom_format("%N").is_err());
+        assert!(validate_custom_format("100%% by %an").is_ok());
+        assert!(validate_custom_format("%ad %aN").is_ok());
+    }
+
+    #[test]
+    fn title_excluded_matches_exact_title() {
+        let titles = vec!["Changelog".to_string(), "License".to_string()];
+        assert!(title_excluded(&titles, "Changelog", false));
+        assert!(!title_excluded(&titles, "changelog", false));
+        assert!(!title_excluded(&titles, "Changelog 2", false));
+        assert!(!title_excluded(&[], "Changelog", false));
+    }
+
+    #[test]
+    fn title_excluded_can_ignore_case() {
+        let titles = vec!["Changelog".to_string(), "Überblick".to_string()];
+        assert!(title_excluded(&titles, "CHANGELOG", true));
+        assert!(title_excluded(&titles, "überblick", true));
+        assert!(!title_excluded(&titles, "Intro", true));
+    }
+
+    #[test]
+    fn exclude_titles_suppresses_footer() {
+        let contents = render_chapters(
+            r#"exclude-titles = ["Changelog"]"#,
+            &[("intro.md", "# Intro\n"), ("changelog.md", "# Changelog\n")],
+        );
+
+        assert!(contents[0].contains("<footer"));
+        assert!(!contents[1].contains("<footer"));
+    }
+
+    #[test]
+    fn footer_print_writes_print_rules_to_theme_css() {
+        let repo = init_repo();
+        fs::write(repo.path().join("book.toml"), "[book]\n").unwrap();
+
+        let ctx = ctx_for(repo.path(), r#"footer-print = "first""#);
+        GitInfo::new().run(&ctx, Book::new()).unwrap();
+
+        let css = fs::read_to_string(repo.path().join("theme/gitinfo.css")).unwrap();
+        assert!(css.ends_with(print_footer_css(FooterPrint::First)));
+        let toml = fs::read_to_string(repo.path().join("book.toml")).unwrap();
+        assert!(toml.contains("theme/gitinfo.css"));
+    }
+
+    #[test]
+    fn emit_semantic_wraps_date_in_time_element() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        let raw = git_in(repo.path(), &["log", "-1", "--format=%cI"]);
+        let iso = chrono::DateTime::parse_from_rfc3339(&raw)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+        let toml = r#"
+            template = "{{date}}"
+            emit-semantic = true
+            timezone = "utc"
+            "#;
+        let content = &run_book(repo.path(), toml, book_of(INTRO))[0];
+
+        assert!(content.contains(&format!(r#"<time datetime="{iso}">"#)));
+        assert!(content.contains("</time>"));
+    }
+
+    fn render_links(toml: &str) -> String {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        git_in(repo.path(), &["tag", "v1.0"]);
+        git_in(
+            repo.path(),
+            &["remote", "add", "origin", "https://example.com/o/r.git"],
+        );
+
+        let toml =
+            format!("template = \"H[{{{{hash}}}}] B[{{{{branch}}}}] T[{{{{tag}}}}]\"\n{toml}");
+        run_book(repo.path(), &toml, book_of(INTRO)).swap_remove(0)
+    }
+
+    #[test]
+    fn source_and_blame_placeholders_link_chapter_file_with_hyperlink() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src/guide")).unwrap();
+        commit_file(repo.path(), "src/guide/setup.md", "# Setup\n");
+        git_in(
+            repo.path(),
+            &["remote", "add", "origin", "https://example.com/o/r.git"],
+        );
+        let render = |toml: &str| {
+            let toml = format!("template = \"S[{{{{source}}}}] B[{{{{blame}}}}]\"\n{toml}");
+            let book = book_of(&[("guide/setup.md", "# Setup\n")]);
+            run_book(repo.path(), &toml, book).swap_remove(0)
+        };
+
+        let linked = render("hyperlink = true");
+        let base = resolve_repo_base(&git::SystemGit::default(), repo.path()).unwrap();
+        assert!(
+            linked.contains(&format!("S[{base}/blob/main/src/guide/setup.md]")),
+            "{linked}"
+        );
+        assert!(
+            linked.contains(&format!("B[{base}/blame/main/src/guide/setup.md]")),
+            "{linked}"
+        );
+        assert!(render("").contains("S[] B[]"));
+    }
+
+    #[test]
+    fn missing_tag_falls_back_per_setting() {
+        let show = |f| missing_tag_display(f, "untagged", "main", "abc1234");
+        assert_eq!(show(NoTagFallback::Text), "untagged");
+        assert_eq!(show(NoTagFallback::Branch), "main");
+        assert_eq!(show(NoTagFallback::Hash), "abc1234");
+    }
+
+    fn render_untagged(toml: &str) -> String {
+        render_chapter(
+            &format!("template = \"T[{{{{tag}}}}] H[{{{{hash}}}}]\"\n{toml}"),
+            INTRO,
+        )
+    }
+
+    #[test]
+    fn no_tag_defaults_to_dash() {
+        assert!(render_untagged("").contains("T[-]"));
+    }
+
+    #[test]
+    fn tag_default_in_template_replaces_missing_tag() {
+        let out = render_untagged(r#"message.footer = "T[{{tag|untagged}}] A[{{author|anon}}]""#);
+        assert!(out.contains("T[untagged] A[tester]"), "{out}");
+    }
+
+    #[test]
+    fn no_tag_text_replaces_dash() {
+        assert!(render_untagged(r#"no-tag-text = "unreleased""#).contains("T[unreleased]"));
+    }
+
+    #[test]
+    fn no_tag_fallback_branch_shows_branch() {
+        assert!(render_untagged(r#"no-tag-fallback = "branch""#).contains("T[main]"));
+    }
+
+    #[test]
+    fn no_tag_fallback_hash_shows_hash() {
+        let content = render_untagged(r#"no-tag-fallback = "hash""#);
+        let tag = &content[content.find("T[").unwrap() + 2..content.find("] H[").unwrap()];
+        assert!(content.contains(&format!("H[{tag}]")));
+        assert_eq!(tag.len(), 7);
+    }
+
+    #[test]
+    fn link_hash_alone_leaves_branch_and_tag_plain() {
+        let content = render_links("link-hash = true");
+        assert!(content.contains("H[<a href="));
+        assert!(content.contains("B[main]"));
+        assert!(content.contains("T[v1.0]"));
+    }
+
+    #[test]
+    fn branch_badge_stays_inside_branch_link() {
+        let content = render_links("branch-badge = true\nlink-branch = true");
+        assert!(content.contains("B[<a href="));
+        assert!(content.contains(r#"/tree/main"><span class="gitinfo-badge"#));
+        assert!(content.contains("main</span></a>]"));
+    }
+
+    #[test]
+    fn link_toggles_override_hyperlink() {
+        let content = render_links("hyperlink = true\nlink-hash = false");
+        assert!(!content.contains("H[<a"));
+        assert!(content.contains("B[<a href="));
+        assert!(content.contains("T[<a href="));
+    }
+
+    #[test]
+    fn link_branch_and_tag_without_hyperlink() {
+        let content = render_links("link-branch = true\nlink-tag = true");
+        assert!(!content.contains("H[<a"));
+        assert!(content.contains("B[<a href="));
+        assert!(content.contains("T[<a href="));
+    }
+
+    /// Answers every Git call with a fixed result.
+    struct CannedGit(Option<&'static str>);
+
+    impl git::GitRunner for CannedGit {
+        fn output(&self, _args: &[&str], _dir: &Path) -> Result<String, Error> {
+            self.0
+                .map(str::to_string)
+                .ok_or_else(|| Error::msg("no output"))
+        }
+    }
+
+    /// Delegates to the `git` binary, logging every argument vector.
+    #[derive(Clone, Default)]
+    struct LoggingGit(std::rc::Rc<RefCell<Vec<Vec<String>>>>);
+
+    impl git::GitRunner for LoggingGit {
+        fn output(&self, args: &[&str], dir: &Path) -> Result<String, Error> {
+            self.0
+                .borrow_mut()
+                .push(args.iter().map(|a| a.to_string()).collect());
+            git::SystemGit::default().output(args, dir)
+        }
+    }
+
+    #[test]
+    fn injected_runner_serves_chapter_queries() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        let hash = commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let ctx = ctx_for(repo.path(), r#"template = "[{{long}}]""#);
+        let log = LoggingGit::default();
+        let out = GitInfo::with_runner(log.clone())
+            .run(&ctx, book_of(INTRO))
+            .unwrap();
+
+        assert!(chapter_contents(&out)[0].contains(&format!("[{hash}]")));
+        let calls = log.0.borrow();
+        assert!(
+            calls
+                .iter()
+                .any(|c| c.last().map(String::as_str) == Some("src/intro.md"))
+        );
+    }
+
+    #[cfg(feature = "gix")]
+    #[test]
+    fn gix_backend_renders_like_the_shell_backend() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        git_in(repo.path(), &["tag", "v1.0"]);
+        commit_file(repo.path(), "src/setup.md", "# Setup\n");
+
+        let files = [("intro.md", "# Intro\n"), ("setup.md", "# Setup\n")];
+        let render = |backend: &str| {
+            let toml = format!(
+                "template = \"{{{{hash}}}} {{{{long}}}} {{{{date}}}} {{{{author}}}} \
+                 {{{{subject}}}} [{{{{ref}}}}]\"\nbackend = \"{backend}\""
+            );
+            run_book(repo.path(), &toml, book_of(&files))
+        };
+
+        let shell = render("shell");
+        assert!(shell[0].contains("[v1.0]"), "{}", shell[0]);
+        assert_eq!(render("gix"), shell);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn git_binary_setting_runs_the_configured_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        let hash = commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let bin = tempfile::TempDir::new().unwrap();
+        let wrapper = bin.path().join("git-wrapper");
+        let marker = bin.path().join("called");
+        fs::write(
+            &wrapper,
+            format!("#!/bin/sh\ntouch '{}'\nexec git \"$@\"\n", marker.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let toml = format!(
+            "template = \"[{{{{long}}}}]\"\ngit-binary = \"{}\"",
+            wrapper.display()
+        );
+        let content = &run_book(repo.path(), &toml, book_of(INTRO))[0];
+        assert!(content.contains(&format!("[{hash}]")));
+        assert!(marker.exists());
+    }
+
+    #[test]
+    fn commit_info_fields_are_escaped_at_render() {
+        let git = CannedGit(Some(
+            "abc\0abcdef\x002025-01-01T00:00:00Z\0A\0C\0a&b <x>\0S",
+        ));
+        let info = CommitCache::new(NeededFields::ALL, git::LogOptions::default()).get(
+            &git,
+            "HEAD",
+            "intro.md",
+            Path::new("."),
+        );
+        assert_eq!(info.short, "abc");
+        assert_eq!(info.subject, "S");
+        assert_eq!(escape_html(&info.email), "a&amp;b &lt;x&gt;");
+    }
+
+    #[test]
+    fn commit_info_is_empty_without_output() {
+        for git in [CannedGit(None), CannedGit(Some(""))] {
+            let info = CommitCache::new(NeededFields::ALL, git::LogOptions::default()).get(
+                &git,
+                "HEAD",
+                "new.md",
+                Path::new("."),
+            );
+            assert_eq!(info, git::CommitFields::default());
+        }
+    }
+
+    #[test]
+    fn needed_fields_follow_template_placeholders() {
+        let needed = NeededFields::analyse(["{{branch}}{{sep}}{{tag}}"], FieldFeatures::default());
+        assert_eq!(needed.format(), None);
+
+        let needed =
+            NeededFields::analyse(["{{hash}}", "{{author|unknown}}"], FieldFeatures::default());
+        assert_eq!(
+            needed.format().as_deref(),
+            Some("%h%x00%x00%x00%an%x00%x00%x00")
+        );
+        assert!(!needed.custom);
+    }
+
+    #[test]
+    fn needed_fields_include_feature_dependencies() {
+        let features = FieldFeatures {
+            link_hash: true,
+            chapter_tags: true,
+            tag_falls_back_to_hash: true,
+            staleness: true,
+        };
+        let needed = NeededFields::analyse(["{{tag}}"], features);
+        assert!(needed.short && needed.long && needed.date);
+        assert!(!needed.author && !needed.subject);
+    }
+
+    fn logged_run(toml: &str) -> Vec<Vec<String>> {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let log = LoggingGit::default();
+        GitInfo::with_runner(log.clone())
+            .run(&ctx_for(repo.path(), toml), book_of(INTRO))
+            .unwrap();
+        log.0.take()
+    }
+
+    #[test]
+    fn renamed_chapter_follows_history_by_default() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(
+            repo.path(),
+            "src/old.md",
+            "# Intro\nSome text that survives.\n",
+        );
+        git_in(repo.path(), &["mv", "src/old.md", "src/intro.md"]);
+        git_in(repo.path(), &["commit", "-q", "-m", "rename"]);
+        let renamed = git_in(repo.path(), &["rev-parse", "HEAD"]);
+
+        for (toml, follow) in [("", true), ("follow-renames = false", false)] {
+            let ctx = ctx_for(
+                repo.path(),
+                &format!("template = \"[{{{{long}}}}]\"\n{toml}"),
+            );
+            let log = LoggingGit::default();
+            let out = GitInfo::with_runner(log.clone())
+                .run(&ctx, book_of(INTRO))
+                .unwrap();
+
+            assert!(chapter_contents(&out)[0].contains(&format!("[{renamed}]")));
+            let logs = log.0.take();
+            let chapter_log = logs.iter().find(|c| c[0] == "log").unwrap();
+            assert_eq!(chapter_log.contains(&"--follow".to_string()), follow);
+        }
+    }
+
+    #[test]
+    fn constant_only_template_skips_log_calls() {
+        let calls = logged_run(r#"template = "{{branch}}""#);
+        assert!(calls.iter().all(|c| c[0] != "log"));
+    }
+
+    #[test]
+    fn contributors_only_skips_per_chapter_queries() {
+        let calls = logged_run(
+            r#"
+            header = false
+            footer = false
+            contributors = true
+            hyperlink = true
+            tag-scope = "chapter"
+            skip-untracked = true
+            template = "{{hash}} {{date}} {{dirty}} {{ref}}"
+            "#,
+        );
+        assert!(
+            calls
+                .iter()
+                .all(|c| !c.iter().any(|a| a.ends_with("intro.md"))),
+            "{calls:?}"
+        );
+    }
+
+    #[test]
+    fn unused_fields_are_not_queried() {
+        let calls = logged_run(
+            r#"
+            template = "{{hash}}"
+            custom-format = "%an"
+            "#,
+        );
+        let logs: Vec<_> = calls.iter().filter(|c| c[0] == "log").collect();
+        assert_eq!(logs.len(), 1);
+        let format = logs[0].iter().find(|a| a.starts_with("--format=")).unwrap();
+        assert!(format.contains("%h"));
+        assert!(!format.contains("%cI"));
+        assert!(!format.contains("%an"));
+    }
+
+    #[test]
+    fn commit_cache_runs_one_git_log_per_unique_path() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/a.md", "# A\n");
+        commit_file(repo.path(), "src/b.md", "# B\n");
+
+        // Three chapters, two distinct files.
+        let book = book_of(&[("a.md", ""), ("a.md", ""), ("b.md", "")]);
+        let ctx = ctx_for(
+            repo.path(),
+            r#"template = "{{hash}} {{long}} {{date}} {{author}} {{email}} {{subject}}""#,
+        );
+
+        let log = LoggingGit::default();
+        GitInfo::with_runner(log.clone()).run(&ctx, book).unwrap();
+
+        let log_calls = log.0.borrow().iter().filter(|c| c[0] == "log").count();
+        assert_eq!(log_calls, 2);
+    }
+
+    #[test]
+    fn subject_placeholder_is_truncated_and_escaped() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("src/intro.md"), "# Intro\n").unwrap();
+        git_in(repo.path(), &["add", "src/intro.md"]);
+        git_in(
+            repo.path(),
+            &[
+                "commit",
+                "-q",
+                "-m",
+                "Render <footer> & \"quotes\" correctly",
+            ],
+        );
+
+        let toml = r#"
+            template = "[{{subject}}]"
+            subject-max-len = 20
+            "#;
+        let content = &run_book(repo.path(), toml, book_of(INTRO))[0];
+
+        assert!(content.contains("[Render &lt;footer&gt; &amp; &quot;…]"));
+    }
+
+    /// A repo whose last commit touching `src/intro.md` is a merge that edits it.
+    fn render_after_merge(toml: &str) -> String {
+        let repo = init_repo();
+        let dir = repo.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        commit_file(dir, "src/intro.md", "v1\n");
+        git_in(dir, &["checkout", "-q", "-b", "feature"]);
+        commit_file(dir, "src/other.md", "other\n");
+        git_in(dir, &["checkout", "-q", "main"]);
+        fs::write(dir.join("src/intro.md"), "v2\n").unwrap();
+        git_in(dir, &["commit", "-q", "-am", "main edit"]);
+        git_in(dir, &["merge", "-q", "--no-ff", "--no-commit", "feature"]);
+        fs::write(dir.join("src/intro.md"), "v3\n").unwrap();
+        git_in(dir, &["add", "src/intro.md"]);
+        git_in(dir, &["commit", "-q", "-m", "Merge feature"]);
+
+        let toml = format!("template = \"[{{{{subject}}}}]\"\n{toml}");
+        run_book(dir, &toml, book_of(&[("intro.md", "v3\n")])).swap_remove(0)
+    }
+
+    #[test]
+    fn ignore_merges_reports_last_non_merge_commit() {
+        // `--follow` already leaves merge diffs out, so compare without it.
+        assert!(render_after_merge("follow-renames = false").contains("[Merge feature]"));
+        assert!(
+            render_after_merge("follow-renames = false\nignore-merges = true")
+                .contains("[main edit]")
+        );
+    }
+
+    #[test]
+    fn footer_colors_are_written_to_theme_css() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        let ctx = ctx_for(
+            repo.path(),
+            r##"
+            footer-color = "#444"
+            footer-color-dark = "#bbb"
+            "##,
+        );
+
+        GitInfo::new().run(&ctx, Book::new()).unwrap();
+        let css_path = repo.path().join("theme/gitinfo.css");
+        let first = fs::read_to_string(&css_path).unwrap();
+        assert!(first.contains(":root { --gitinfo-footer-color: #444; }"));
+        assert!(first.contains(".navy, .coal, .ayu { --gitinfo-footer-color: #bbb; }"));
+
+        let written = fs::metadata(&css_path).unwrap().modified().unwrap();
+        GitInfo::new().run(&ctx, Book::new()).unwrap();
+        assert_eq!(fs::read_to_string(&css_path).unwrap(), first);
+        assert_eq!(
+            fs::metadata(&css_path).unwrap().modified().unwrap(),
+            written
+        );
+    }
+
+    const ALL_LINKS: LinkKinds = LinkKinds {
+        hash: true,
+        tag: true,
+        branch: true,
+    };
+
+    #[test]
+    fn best_ref_prefers_tag_then_hash_then_branch() {
+        let links = github_links();
+        let base = Some(&links);
+        assert_eq!(
+            best_ref(
+                Some("v1.2.0"),
+                "abc1234",
+                "abc1234ff",
+                "main",
+                base,
+                ALL_LINKS
+            ),
+            r#"<a href="https://github.com/o/r/releases/tag/v1.2.0">v1.2.0</a>"#
+        );
+        assert_eq!(
+            best_ref(None, "abc1234", "abc1234ff", "main", base, ALL_LINKS),
+            r#"<a href="https://github.com/o/r/commit/abc1234ff">abc1234</a>"#
+        );
+        assert_eq!(best_ref(None, "", "", "main", None, ALL_LINKS), "main");
+        assert_eq!(best_ref(None, "", "", "", base, ALL_LINKS), "");
+    }
+
+    #[test]
+    fn best_ref_links_only_enabled_kinds() {
+        let links = github_links();
+        let base = Some(&links);
+        let hash_only = LinkKinds {
+            hash: true,
+            tag: false,
+            branch: false,
+        };
+        assert_eq!(
+            best_ref(
+                Some("v1.2.0"),
+                "abc1234",
+                "abc1234ff",
+                "main",
+                base,
+                hash_only
+            ),
+            "v1.2.0"
+        );
+        assert_eq!(
+            best_ref(None, "abc1234", "abc1234ff", "main", base, hash_only),
+            r#"<a href="https://github.com/o/r/commit/abc1234ff">abc1234</a>"#
+        );
+        assert_eq!(best_ref(None, "", "", "main", base, hash_only), "main");
+    }
+
+    #[test]
+    fn ref_placeholder_prefers_exact_tag() {
+        let tagged = render_links("message.footer = \"R[{{ref}}]\"");
+        assert!(tagged.contains("R[v1.0]"), "{tagged}");
+
+        let linked = render_links("message.footer = \"R[{{ref}}]\"\nhyperlink = true");
+        assert!(
+            linked.contains(r#"R[<a href="https://example.com/o/r/tags/v1.0">v1.0</a>]"#),
+            "{linked}"
+        );
+
+        let unlinked =
+            render_links("message.footer = \"R[{{ref}}]\"\nhyperlink = true\nlink-tag = false");
+        assert!(unlinked.contains("R[v1.0]"), "{unlinked}");
+
+        let tag_only = render_links("message.footer = \"R[{{ref}}]\"\nlink-tag = true");
+        assert!(
+            tag_only.contains(r#"R[<a href="https://example.com/o/r/tags/v1.0">v1.0</a>]"#),
+            "{tag_only}"
+        );
+    }
+
+    #[test]
+    fn ref_placeholder_falls_back_to_hash_then_branch() {
+        let contents = render_committed_and_new(
+            r#"
+            message.footer = "R[{{ref}}] H[{{hash}}]"
+            untracked-text = "R[{{ref}}]"
+            "#,
+        );
+        let hash = contents[0]
+            .split("H[")
+            .nth(1)
+            .unwrap()
+            .split(']')
+            .next()
+            .unwrap();
+        assert!(
+            contents[0].contains(&format!("R[{hash}]")),
+            "{}",
+            contents[0]
+        );
+        assert!(contents[1].contains("R[main]"), "{}", contents[1]);
+    }
+
+    #[test]
+    fn frontmatter_overrides_footer_for_one_chapter() {
+        let contents = render_chapters(
+            r#"template = "Global {{hash}}""#,
+            &[
+                (
+                    "a.md",
+                    "---\ngitinfo: { footer: \"Reviewed {{author}}\" }\n---\n# A\n",
+                ),
+                ("b.md", "# B\n"),
+                ("c.md", "---\ngitinfo: { footer: \"open }\n---\n# C\n"),
+            ],
+        );
+
+        assert!(contents[0].starts_with("# A\n"), "{}", contents[0]);
+        assert!(contents[0].contains(">Reviewed tester</footer>"));
+        assert!(contents[1].contains(">Global "));
+        // Malformed frontmatter is still stripped, with the global footer.
+        assert!(contents[2].starts_with("# C\n"), "{}", contents[2]);
+        assert!(contents[2].contains(">Global "));
+    }
+
+    #[test]
+    fn supports_configured_renderers() {
+        let pre = GitInfo::new();
+        assert!(pre.supports_renderer("html").unwrap());
+        assert!(!pre.supports_renderer("epub").unwrap());
+
+        let pre = GitInfo::new().with_renderers(vec!["html".into(), "epub".into()]);
+        assert!(pre.supports_renderer("epub").unwrap());
+    }
+
+    #[test]
+    fn non_html_renderer_gets_plain_blocks_and_no_css() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+
+        let html_ctx = ctx_for(repo.path(), "branch-badge = true");
+        let ctx = PreprocessorContext::new(
+            repo.path().to_path_buf(),
+            html_ctx.config.clone(),
+            "epub".to_string(),
+        );
+        let out = GitInfo::new().run(&ctx, book_of(INTRO)).unwrap();
+
+        let content = &chapter_contents(&out)[0];
+        assert!(
+            content.contains(r#"<footer class="gitinfo-footer">"#),
+            "{content}"
+        );
+        assert!(!repo.path().join("theme/gitinfo.css").exists());
+    }
+
+    #[test]
+    fn emit_version_tags_footer_with_crate_version() {
+        assert!(!render_links("").contains("data-gitinfo-version"));
+
+        let tagged = render_links("emit-version = true");
+        let attr = format!(r#"data-gitinfo-version="{}""#, env!("CARGO_PKG_VERSION"));
+        assert!(tagged.contains(&attr), "{tagged}");
+    }
+
+    #[test]
+    fn footer_fn_replaces_footer_template() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("src/intro.md"), "# Intro\n").unwrap();
+        git_in(repo.path(), &["add", "src/intro.md"]);
+        git_in(repo.path(), &["commit", "-q", "-m", "Add <intro>"]);
+
+        let ctx = ctx_for(
+            repo.path(),
+            r#"
+            footer = true
+            header = true
+            message.header = "head {{hash}}"
+            message.footer = "unused {{hash}}"
+            "#,
+        );
+        let out = GitInfo::new()
+            .with_footer_fn(|info| {
+                format!(
+                    "{} | {} | {} | {} | {}",
+                    info.chapter_title,
+                    info.path,
+                    info.subject,
+                    info.author,
+                    info.hash.len() >= 7
+                )
+            })
+            .run(&ctx, book_of(INTRO))
+            .unwrap();
+
+        let content = &chapter_contents(&out)[0];
+        assert!(content.contains("head "), "{content}");
+        assert!(
+            content.contains(">Intro | src/intro.md | Add <intro> | tester | true</footer>"),
+            "{content}"
+        );
+        assert!(!content.contains("unused"));
+    }
+
+    fn render_script_subject(toml: &str) -> String {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("src/intro.md"), "# Intro\n").unwrap();
+        git_in(repo.path(), &["add", "src/intro.md"]);
+        git_in(
+            repo.path(),
+            &["commit", "-q", "-m", "Fix <script>alert(1)</script>typo"],
+        );
+        run_book(repo.path(), toml, book_of(INTRO)).swap_remove(0)
+    }
+
+    #[test]
+    fn script_in_commit_subject_is_neutralized() {
+        let escaped = render_script_subject(r#"template = "[{{subject}}]""#);
+        assert!(escaped.contains("[Fix &lt;script&gt;alert(1)&lt;/script&gt;typo]"));
+
+        let raw = render_script_subject(
+            r#"
+            template = "[{{custom}}]"
+            custom-format = "%s"
+            "#,
+        );
+        assert!(
+            raw.contains("[Fix &lt;script&gt;alert(1)&lt;/script&gt;typo]"),
+            "{raw}"
+        );
+    }
+
+    #[test]
+    fn markup_in_branch_and_title_is_escaped() {
+        use mdbook_preprocessor::book::BookItem;
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        let branch = "<svg/onload=alert(1)>";
+        git_in(repo.path(), &["checkout", "-q", "-b", branch]);
+
+        let mut book = book_of(INTRO);
+        if let BookItem::Chapter(ch) = &mut book.items[0] {
+            ch.name = "<img src=x onerror=alert(1)>".to_string();
+        }
+        for sanitize in [true, false] {
+            let toml = format!(
+                "template = \"[{{{{branch}}}}] [{{{{chapter-title}}}}] [{{{{tag}}}}]\"\n\
+                 branch = \"{branch}\"\ntag = \"<b onmouseover=x>\"\nsanitize = {sanitize}"
+            );
+            let html = &run_book(repo.path(), &toml, book.clone())[0];
+            assert!(
+                html.contains(
+                    "[&lt;svg/onload=alert(1)&gt;] [&lt;img src=x onerror=alert(1)&gt;] \
+                     [&lt;b onmouseover=x&gt;]"
+                ),
+                "{html}"
+            );
+        }
+    }
+
+    #[test]
+    fn custom_stays_escaped_without_sanitize() {
+        let raw = render_script_subject(
+            r#"
+            template = "[{{custom}}]"
+            custom-format = "%s"
+            sanitize = false
+            "#,
+        );
+        assert!(raw.contains("[Fix &lt;script&gt;alert(1)&lt;/script&gt;typo]"));
+    }
+
+    #[test]
+    fn markdown_format_converts_template_but_not_values() {
+        let html = render_script_subject(
+            r#"
+            template = "**Updated** by _{{custom}}_"
+            custom-format = "%s"
+            format = "markdown"
+            sanitize = false
+            "#,
+        );
+        assert!(
+            html.contains("<strong>Updated</strong> by <em>Fix &lt;script&gt;"),
+            "{html}"
+        );
+
+        let plain = render_script_subject(r#"template = "**Updated**""#);
+        assert!(plain.contains("**Updated**"));
+    }
+
+    #[test]
+    fn wrapper_tag_and_class_name_are_configurable() {
+        let default = render_chapter("header = true\ntemplate = \"x\"", INTRO);
+        assert!(default.contains(r#"<header class="gitinfo-header" style="#));
+        assert!(default.contains(r#"<footer class="gitinfo-footer" style="#));
+
+        let custom = render_chapter(
+            r#"
+            header = true
+            template = "x"
+            wrapper-tag = "div"
+            class-name = { footer = "page-meta" }
+            "#,
+            INTRO,
+        );
+        assert!(
+            custom.contains(r#"<div class="gitinfo-header" style="#),
+            "{custom}"
+        );
+        assert!(
+            custom.contains(r#"<div class="page-meta" style="#),
+            "{custom}"
+        );
+        assert!(!custom.contains("<footer") && !custom.contains("<header"));
+
+        let bad = render_chapter("template = \"x\"\nwrapper-tag = \"div onclick=x\"", INTRO);
+        assert!(bad.contains(r#"<footer class="gitinfo-footer""#), "{bad}");
+    }
+
+    #[test]
+    fn email_placeholder_renders_author_email() {
+        let content = render_chapter(r#"template = "[{{email}}]""#, INTRO);
+        assert!(content.contains("[tester@example.com]"));
+    }
+
+    #[test]
+    fn include_and_exclude_filter_nested_chapters() {
+        use mdbook_preprocessor::book::{BookItem, Chapter};
+
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src/reference/drafts")).unwrap();
+        for f in ["index.md", "reference/api.md", "reference/drafts/wip.md"] {
+            commit_file(repo.path(), &format!("src/{f}"), "# Page\n");
+        }
+
+        let token = "# Page\n\n{% contributors %}\n";
+        let chapter = |name: &str, path: &str| Chapter::new(name, token.to_string(), path, vec![]);
+        let mut api = chapter("Api", "reference/api.md");
+        api.sub_items
+            .push(BookItem::Chapter(chapter("Wip", "reference/drafts/wip.md")));
+        let mut book = Book::new();
+        book.push_item(BookItem::Chapter(chapter("Index", "index.md")));
+        book.push_item(BookItem::Chapter(api));
+
+        let ctx = ctx_for(
+            repo.path(),
+            r#"
+            template = "[{{hash}}]"
+            contributors = true
+            include = ["reference/**", "index.md"]
+            exclude = ["index.md", "reference/drafts/**"]
+            "#,
+        );
+        let out = GitInfo::new().run(&ctx, book).unwrap();
+        let by_name: HashMap<_, _> = out
+            .chapters()
+            .map(|ch| (ch.name.clone(), ch.content.clone()))
+            .collect();
+
+        assert!(by_name["Api"].contains("gitinfo-footer"));
+        assert!(by_name["Api"].contains("gitinfo-contributors"));
+        for excluded in ["Index", "Wip"] {
+            let content = &by_name[excluded];
+            assert!(!content.contains("gitinfo-"), "{excluded}: {content}");
+            assert!(
+                !content.contains("{% contributors"),
+                "{excluded}: {content}"
+            );
+        }
+    }
+
+    /// Repository with the book nested in `book/`, as in a monorepo.
+    fn nested_book_repo() -> (TempDir, String) {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("book/src")).unwrap();
+        let hash = commit_file(repo.path(), "book/src/intro.md", "# Intro\n");
+        (repo, hash)
+    }
+
+    fn render_nested(book_root: &Path, toml: &str) -> String {
+        run_book(book_root, toml, book_of(INTRO)).swap_remove(0)
+    }
+
+    #[test]
+    fn nested_book_uses_detected_git_root() {
+        let (repo, hash) = nested_book_repo();
+        let content = render_nested(&repo.path().join("book"), r#"template = "[{{long}}]""#);
+        assert!(content.contains(&format!("[{hash}]")));
+    }
+
+    #[test]
+    fn nested_book_uses_configured_git_root() {
+        let (repo, hash) = nested_book_repo();
+        let content = render_nested(
+            &repo.path().join("book"),
+            r#"
+            template = "[{{long}}]"
+            git-root = ".."
+            "#,
+        );
+        assert!(content.contains(&format!("[{hash}]")));
+    }
+
+    #[test]
+    fn git_root_resolves_against_book_root() {
+        let book = Path::new("/work/repo/book");
+        assert_eq!(
+            resolve_git_root(&git::SystemGit::default(), Some(".."), book),
+            Path::new("/work/repo/book/..")
+        );
+        assert_eq!(
+            resolve_git_root(&git::SystemGit::default(), Some("/work/repo"), book),
+            Path::new("/work/repo")
+        );
+    }
+
+    #[test]
+    fn book_prefix_is_relative_inside_git_root() {
+        let (repo, _) = nested_book_repo();
+        assert_eq!(
+            book_prefix(repo.path(), &repo.path().join("book")),
+            Path::new("book")
+        );
+        assert_eq!(book_prefix(repo.path(), repo.path()), Path::new(""));
+    }
+
+    #[test]
+    fn author_and_committer_are_html_escaped() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("src/intro.md"), "# Intro\n").unwrap();
+        git_in(repo.path(), &["add", "src/intro.md"]);
+        git_in(
+            repo.path(),
+            &["-c", "user.name=Tom & Jerry", "commit", "-q", "-m", "intro"],
+        );
+
+        let toml = r#"template = "{{author}}|{{committer}}""#;
+        let content = &run_book(repo.path(), toml, book_of(INTRO))[0];
+
+        assert!(content.contains("Tom &amp; Jerry|Tom &amp; Jerry"));
+    }
+
+    #[test]
+    fn date_style_relative_matches_relative_placeholder() {
+        let content = &render_chapter(
+            r#"
+            template = "[{{date}}|{{relative}}]"
+            date-style = "relative"
+            "#,
+            INTRO,
+        );
+
+        let start = content.find('[').unwrap() + 1;
+        let end = content.find(']').unwrap();
+        let (date, relative) = content[start..end].split_once('|').unwrap();
+        assert!(relative.ends_with("ago") || relative == "just now");
+        assert_eq!(date, relative);
+    }
+
+    #[test]
+    fn contributors_title_substitutes_placeholders() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        commit_file(repo.path(), "src/intro.md", "# Intro\n");
+        git_in(
+            repo.path(),
+            &[
+                "remote",
+                "add",
+                "origin",
+                "https://github.com/o/handbook.git",
+            ],
+        );
+
+        let toml = r#"
+            contributors = true
+            contributors-title = "Contributors to {{repo-name}} & {{branch}}"
+            "#;
+        let book = book_of(&[("intro.md", "# Intro\n\n{% contributors %}\n")]);
+        let content = &run_book(repo.path(), toml, book)[0];
+        assert!(
+            content.contains("<h2>Contributors to handbook &amp; main</h2>"),
+            "{content}"
+        );
+    }
+
+    #[test]
+    fn exclude_self_drops_local_git_identity() {
+        let repo = init_repo();
+        git_in(repo.path(), &["config", "user.name", "carol"]);
+        let render = |extra: &str| {
+            let book = book_of(&[("intro.md", "{% contributors alice carol %}\n")]);
+            let toml = format!("contributors = true\ncontributors-source = \"inline\"\n{extra}");
+            run_book(repo.path(), &toml, book).swap_remove(0)
+        };
+
+        assert!(render("").contains("github.com/carol"));
+        let content = render("exclude-self = true");
+        assert!(content.contains("github.com/alice"), "{content}");
+        assert!(!content.contains("github.com/carol"), "{content}");
+    }
+
+    #[test]
+    fn chapter_scope_lists_only_authors_of_the_page() {
+        let repo = init_repo();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        for (author, file) in [
+            ("alice", "src/a.md"),
+            ("alice", "src/b.md"),
+            ("bob", "src/b.md"),
+            ("carol", "src/other.md"),
+        ] {
+            fs::write(repo.path().join(file), author).unwrap();
+            git_in(repo.path(), &["add", file]);
+            let ident = format!("{author} <{author}@example.com>");
+            git_in(
+                repo.path(),
+                &["commit", "-q", "--author", &ident, "-m", file],
+            );
+        }
+        let render = |extra: &str| {
+            let token = "{% contributors %}\n";
+            let book = book_of(&[("a.md", token), ("b.md", token)]);
+            let toml = format!("contributors = true\ncontributors-show-counts = true\n{extra}");
+            run_book(repo.path(), &toml, book)
+        };
+        let names = |content: &str| {
+            ["alice", "bob", "carol"]
+                .into_iter()
+                .filter(|n| content.contains(&format!(r#"alt="Contributor {n}""#)))
+                .collect::<Vec<_>>()
+        };
+
+        let book = render("");
+        assert_eq!(names(&book[0]), ["alice", "bob", "carol"]);
+        assert_eq!(names(&book[1]), ["alice", "bob", "carol"]);
+
+        let pages = render("contributors-scope = \"chapter\"");
+        assert_eq!(names(&pages[0]), ["alice"], "{}", pages[0]);
+        assert_eq!(names(&pages[1]), ["alice", "bob"], "{}", pages[1]);
+        assert!(pages[0].contains(r#"<span class="contributor-count">(1)</span>"#));
+        assert!(!pages[0].contains("(2)"));
+    }
+
+    #[test]
+    fn git_contributors_follow_mailmap() {
+        let repo = init_repo();
+        for (i, ident) in ["alice <alice@example.com>", "alice-old <alice@old.example>"]
+            .iter()
+            .enumerate()
+        {
+            let name = format!("f{i}.md");
+            fs::write(repo.path().join(&name), "x").unwrap();
+            git_in(repo.path(), &["add", &name]);
+            git_in(
+                repo.path(),
+                &["commit", "-q", "--author", ident, "-m", &name],
+            );
+        }
+        let render = |extra: &str| {
+            let toml = format!("contributors = true\ncontributors-show-counts = true\n{extra}");
+            run_book(repo.path(), &toml, book_of(CONTRIBUTORS)).swap_remove(0)
+        };
+        let mapping = "alice <alice@example.com> <alice@old.example>\n";
+
+        let split = render("");
+        assert!(split.contains("github.com/alice-old"), "{split}");
+
+        fs::write(repo.path().join("people.mailmap"), mapping).unwrap();
+        let content = render("mailmap-file = \"people.mailmap\"");
+        assert!(!content.contains("alice-old"), "{content}");
+        assert_eq!(
+            content.matches("alt=\"Contributor alice\"").count(),
+            1,
+            "{content}"
+        );
+        assert!(content.contains(r#"<span class="contributor-count">(2)</span>"#));
+
+        fs::write(repo.path().join(".mailmap"), mapping).unwrap();
+        let content = render("contributors-sort = \"first-seen\"");
+        assert!(!content.contains("alice-old"), "{content}");
+        assert!(content.contains(r#"<span class="contributor-count">(2)</span>"#));
+    }
+
+    #[test]
+    fn coauthors_join_git_contributors_when_enabled() {
+        let repo = init_repo();
+        fs::write(repo.path().join("pair.md"), "x").unwrap();
+        git_in(repo.path(), &["add", "pair.md"]);
+        git_in(
+            repo.path(),
+            &[
+                "commit",
+                "-q",
+                "--author",
+                "alice <alice@example.com>",
+                "-m",
+                "Pair on intro\n\n\
+                 Co-authored-by: carol <carol@example.com>\n\
+                 Co-authored-by: alice <alice@example.com>\n\
+                 Co-authored-by: dave <dave@example.com>",
+            ],
+        );
+        let render = |extra: &str| {
+            let toml = format!(
+                "contributors = true\ncontributors-show-counts = true\n\
+                 contributors-exclude = [\"dave\"]\n{extra}"
+            );
+            run_book(repo.path(), &toml, book_of(CONTRIBUTORS)).swap_remove(0)
+        };
+
+        let plain = render("");
+        assert!(plain.contains(r#"alt="Contributor alice""#), "{plain}");
+        assert!(!plain.contains("carol"), "{plain}");
+
+        let content = render("contributors-include-coauthors = true");
+        assert_eq!(content.matches(r#"alt="Contributor alice""#).count(), 1);
+        assert!(content.contains(r#"<span class="contributor-count">(2)</span>"#));
+        assert!(content.contains(r#"alt="Contributor carol""#), "{content}");
+        assert!(!content.contains("dave"), "{content}");
+
+        let truncated =
+            render("contributors-include-coauthors = true\ncontributors-max-visible = 1");
+        let row = truncated.split("contributors-more").next().unwrap();
+        assert!(row.contains(r#"alt="Contributor alice""#), "{truncated}");
+        assert!(!row.contains("carol"), "{truncated}");
+    }
+
+    #[test]
+    fn repeated_contributors_are_listed_once() {
+        let content = render_chapter(
+            "contributors = true\ncontributors-source = \"inline\"\ncontributors-max-visible = 2",
+            &[("intro.md", "{% contributors bob alice bob %}\n")],
+        );
+        assert_eq!(
+            content.matches("alt=\"Contributor bob\"").count(),
+            1,
+            "{content}"
+        );
+        assert!(content.contains("github.com/alice"), "{content}");
+        assert!(!content.contains("contributors-more"), "{content}");
+    }
+
+    #[test]
+    fn contributor_counts_sort_before_truncation() {
+        let repo = init_repo();
+        for (author, n) in [("alice", 1), ("bob", 3)] {
+            for i in 0..n {
+                let name = format!("{author}{i}.md");
+                fs::write(repo.path().join(&name), "x").unwrap();
+                git_in(repo.path(), &["add", &name]);
+                let user = format!("user.name={author}");
+                git_in(repo.path(), &["-c", &user, "commit", "-q", "-m", &name]);
+            }
+        }
+        let render = |extra: &str| {
+            let toml = format!("contributors = true\ncontributors-max-visible = 1\n{extra}");
+            run_book(repo.path(), &toml, book_of(CONTRIBUTORS)).swap_remove(0)
+        };
+
+        let content = render("contributors-show-counts = true");
+        let row = content.split("contributors-more").next().unwrap();
+        assert!(row.contains("github.com/bob"), "{content}");
+        assert!(row.contains(r#"<span class="contributor-count">(3)</span>"#));
+        assert!(content.contains(r#"<span class="contributor-count">(1)</span>"#));
+
+        let plain = render("");
+        assert!(!plain.contains("contributor-count"));
+        let row = plain.split("contributors-more").next().unwrap();
+        assert!(row.contains("github.com/alice"), "{plain}");
+    }
+
+    #[test]
+    fn inline_contributors_link_profiles_with_hyperlink() {
+        let repo = init_repo();
+        git_in(
+            repo.path(),
+            &[
+                "remote",
+                "add",
+                "origin",
+                "https://gitlab.example.com/g/p.git",
+            ],
+        );
+        let render = |extra: &str| {
+            let book = book_of(&[("intro.md", "{% contributors alice %}\n")]);
+            let toml = format!("contributors = true\ncontributors-source = \"inline\"\n{extra}");
+            run_book(repo.path(), &toml, book).swap_remove(0)
+        };
+
+        let linked = render("hyperlink = true");
+        assert!(
+            linked.contains(r#"class="contributor-profile""#),
+            "{linked}"
+        );
+        assert!(linked.contains("/alice\">@alice</a>"));
+
+        let avatars = render("");
+        assert!(avatars.contains(r#"<img src="https://github.com/alice.png""#));
+        assert!(!avatars.contains("contributor-profile"));
+    }
+
+    #[test]
+    fn missing_required_contributors_file_errors() {
+        let repo = init_repo();
+        let toml = r#"
+            contributors = true
+            contributors-source = "file"
+            contributors-file = "CREDITS.md"
+        "#;
+
+        let ctx = ctx_for(repo.path(), toml);
+        assert!(GitInfo::new().run(&ctx, Book::new()).is_ok());
+
+        let ctx = ctx_for(
+            repo.path(),
+            &format!("{toml}\ncontributors-file-required = true"),
+        );
+        let err = GitInfo::new().run(&ctx, Book::new()).unwrap_err();
+        assert!(err.to_string().contains("CREDITS.md"));
+
+        fs::write(repo.path().join("CREDITS.md"), "alice\n").unwrap();
+        assert!(GitInfo::new().run(&ctx, Book::new()).is_ok());
+    }
+
+    #[test]
+    fn avatar_size_flows_into_generated_css() {
+        let repo = init_repo();
+        fs::write(repo.path().join("book.toml"), "[book]\n").unwrap();
+
+        let ctx = ctx_for(
+            repo.path(),
+            r#"
+            contributors = true
+            contributors-source = "inline"
+            contributors-avatar-size = "48px"
+            contributors-avatar-gap = "12px"
+            "#,
+        );
+        GitInfo::new().run(&ctx, Book::new()).unwrap();
+
+        let css = fs::read_to_string(repo.path().join("theme/gitinfo.css")).unwrap();
+        assert!(css.starts_with(GITINFO_CSS));
+        assert!(css.contains("--gitinfo-avatar-size: 48px;"));
+        assert!(css.contains("--gitinfo-avatar-gap: 12px;"));
+    }
+
+    #[test]
+    fn footer_after_list_is_top_level_block() {
+        for compact in [false, true] {
+            let content = upsert_marked_block(
+                "# List\n\n- one\n- two",
+                Placement::Footer,
+                "<footer>f</footer>",
+                compact,
+            );
+            assert!(footer_is_top_level(&content), "{content:?}");
+        }
+    }
+
+    #[test]
+    fn footer_nested_in_list_item_is_flagged() {
+        let nested = "- one\n\n  <!-- gitinfo:footer -->\n  <footer>f</footer>\n  <!-- /gitinfo:footer -->\n";
+        assert!(!footer_is_top_level(nested));
+
+        let quoted = "> quote\n> <!-- gitinfo:footer -->\n";
+        assert!(!footer_is_top_level(quoted));
+
+        let inline = "text <!-- gitinfo:footer --> more\n";
+        assert!(!footer_is_top_level(inline));
+
+        assert!(footer_is_top_level("# No footer\n"));
+    }
+
+    #[test]
+    fn staleness_class_only_past_threshold() {
+        assert_eq!(staleness_class(true, 30, Some(31)), "gitinfo-stale");
+        assert_eq!(staleness_class(true, 30, Some(30)), "");
+        assert_eq!(staleness_class(true, 30, Some(2)), "");
+        assert_eq!(staleness_class(true, 30, None), "");
+    }
+
+    #[test]
+    fn staleness_class_off_by_default() {
+        assert_eq!(staleness_class(false, 30, Some(400)), "");
     }
 }
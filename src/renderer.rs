@@ -1,8 +1,12 @@
+use crate::config::{ContributorsEmails, FooterPrint};
+use crate::logging;
 use handlebars::Handlebars;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 const CONTRIBUTORS_TEMPLATE: &str = include_str!("../templates/contributor.hbs");
 pub const GITINFO_CSS: &str = include_str!("../templates/gitinfo.css");
+pub const GITINFO_JS: &str = include_str!("../templates/gitinfo.js");
 
 #[derive(Serialize)]
 struct ContributorsCtx<'a> {
@@ -11,26 +15,661 @@ struct ContributorsCtx<'a> {
     message: Option<String>,
     usernames_visible: &'a [String],
     usernames_hidden: &'a [String],
+    // Short names of the two lists for `contributors-template` files.
+    visible: &'a [String],
+    hidden: &'a [String],
     hidden_count: usize,
+    overflow_count: usize,
+    separator: &'a str,
+    more_label: String,
+    // Name -> e-mail, only for listed contributors and only when shown.
+    emails: BTreeMap<&'a str, &'a str>,
+    email_link: bool,
+    // Name -> commit count, only for listed contributors with a known count.
+    counts: BTreeMap<&'a str, usize>,
+    // Text profile links replace the GitHub avatars when set.
+    profile_links: bool,
+    // Name -> escaped `@name` profile link, or the plain name when unlinkable.
+    profiles: BTreeMap<&'a str, String>,
+    #[serde(flatten)]
+    meta: RepoMeta<'a>,
+}
+
+/// Repository-level metadata exposed to the contributors template as
+/// `{{hash}}`, `{{long}}`, `{{date}}`, `{{branch}}` and `{{tag}}`.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct RepoMeta<'a> {
+    pub hash: &'a str,
+    pub long: &'a str,
+    pub date: &'a str,
+    pub branch: &'a str,
+    pub tag: &'a str,
+}
+
+static NO_EMAILS: BTreeMap<String, String> = BTreeMap::new();
+static NO_COUNTS: BTreeMap<String, usize> = BTreeMap::new();
+
+/// Presentation settings for [`render_contributors_hbs`].
+#[derive(Debug, Clone, Copy)]
+pub struct ContributorsOptions<'a> {
+    /// Text placed between contributor entries.
+    pub separator: &'a str,
+    /// Label of the "Show all" expander; `{n}` is replaced by the number of
+    /// contributors not shown inline (listed hidden plus overflow).
+    pub more_text: &'a str,
+    /// Render nothing at all (not even the title) when there are no contributors.
+    pub hide_if_empty: bool,
+    /// Known e-mail addresses keyed by contributor name.
+    pub emails: &'a BTreeMap<String, String>,
+    /// Whether e-mails are hidden, shown as text or rendered as `mailto:` links.
+    pub email_mode: ContributorsEmails,
+    /// Commit counts keyed by contributor name, shown as `Name (42)`
+    /// (`contributors-show-counts`). Empty to show none.
+    pub counts: &'a BTreeMap<String, usize>,
+    /// Forge web root (e.g. `https://gitlab.example.com`) to link usernames
+    /// to as `@name` profile links instead of showing GitHub avatars.
+    pub profile_base: Option<&'a str>,
+    /// Repository-level metadata available to the template.
+    pub meta: RepoMeta<'a>,
+}
+
+impl Default for ContributorsOptions<'_> {
+    fn default() -> Self {
+        ContributorsOptions {
+            separator: "",
+            more_text: "{n}+",
+            hide_if_empty: true,
+            emails: &NO_EMAILS,
+            email_mode: ContributorsEmails::Hide,
+            counts: &NO_COUNTS,
+            profile_base: None,
+            meta: RepoMeta::default(),
+        }
+    }
+}
+
+/// Raw Git metadata for one chapter, passed to a footer function registered
+/// on the preprocessor in place of the footer template.
+///
+/// Unlike [`TemplateValues`], nothing is HTML-escaped, linked or formatted.
+/// `hash` is empty for files without commits.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChapterGitInfo {
+    pub chapter_title: String,
+    pub chapter_number: String,
+    /// Chapter source path relative to the Git root.
+    pub path: String,
+    pub hash: String,
+    pub long: String,
+    /// Committer date as printed by Git (`%cI`).
+    pub date: String,
+    pub days_since: Option<i64>,
+    pub branch: String,
+    /// Tag for the chapter; empty when none was found.
+    pub tag: String,
+    pub author: String,
+    pub committer: String,
+    pub email: String,
+    pub subject: String,
+    pub dirty: bool,
+}
+
+/// Values substituted into message templates by [`render_template`].
+///
+/// Fields left at their default render as an empty string.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TemplateValues<'a> {
+    /// `{{hash}}` — short commit hash (may already be a link)
+    pub hash: &'a str,
+    /// `{{long}}` — full commit hash
+    pub long: &'a str,
+    /// `{{tag}}` — resolved tag (may already be a link)
+    pub tag: &'a str,
+    /// `{{date}}` — formatted commit date/time
+    pub date: &'a str,
+    /// `{{sep}}` — configured separator
+    pub sep: &'a str,
+    /// `{{branch}}` — branch name (may already be a link)
+    pub branch: &'a str,
+    /// `{{chapter-title}}` — the chapter's name
+    pub chapter_title: &'a str,
+    /// `{{chapter-number}}` — section number such as `3` or `2.1`
+    pub chapter_number: &'a str,
+    /// `{{days-since}}` — whole days since the commit
+    pub days_since: &'a str,
+    /// `{{custom}}` — output of the user's `custom-format` Git format string
+    pub custom: &'a str,
+    /// `{{author}}` — author name of the commit (HTML-escaped)
+    pub author: &'a str,
+    /// `{{committer}}` — committer name of the commit (HTML-escaped)
+    pub committer: &'a str,
+    /// `{{email}}` — author e-mail of the commit (HTML-escaped)
+    pub email: &'a str,
+    /// `{{subject}}` — subject line of the commit (truncated, HTML-escaped)
+    pub subject: &'a str,
+    /// `{{build-date}}` — build time (`SOURCE_DATE_EPOCH` when set), formatted like `{{date}}`
+    pub build_date: &'a str,
+    /// `{{dirty}}` — `dirty-marker` when the chapter has uncommitted edits, else empty
+    pub dirty: &'a str,
+    /// `{{age}}` / `{{relative}}` — relative age of the commit such as `3 days ago`
+    pub age: &'a str,
+    /// `{{ref}}` — exact tag of the commit, else short hash, else branch
+    pub reference: &'a str,
+    /// `{{repo-name}}` — repository name from the remote URL or work-tree directory
+    pub repo_name: &'a str,
+    /// `{{source}}` — URL of the chapter file on the forge (`hyperlink` only)
+    pub source: &'a str,
+    /// `{{blame}}` — URL of the chapter file's blame view (`hyperlink` only)
+    pub blame: &'a str,
+    /// `{{contributors}}` — number of contributors ([`crate::build_summary`] only)
+    pub contributors: &'a str,
+    /// `tag` holds the `no-tag-fallback` text rather than a real tag, so
+    /// `{{tag|…}}` uses its default.
+    pub no_tag: bool,
+}
+
+impl TemplateValues<'_> {
+    /// Value for the placeholder `key`, or `None` if the key is not known.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match key {
+            "hash" => Some(self.hash),
+            "long" => Some(self.long),
+            "tag" => Some(self.tag),
+            "date" => Some(self.date),
+            "sep" => Some(self.sep),
+            "branch" => Some(self.branch),
+            "chapter-title" => Some(self.chapter_title),
+            "chapter-number" => Some(self.chapter_number),
+            "days-since" => Some(self.days_since),
+            "custom" => Some(self.custom),
+            "age" | "relative" => Some(self.age),
+            "author" => Some(self.author),
+            "committer" => Some(self.committer),
+            "email" => Some(self.email),
+            "subject" => Some(self.subject),
+            "dirty" => Some(self.dirty),
+            "build-date" => Some(self.build_date),
+            "ref" => Some(self.reference),
+            "repo-name" => Some(self.repo_name),
+            "source" => Some(self.source),
+            "blame" => Some(self.blame),
+            "contributors" => Some(self.contributors),
+            _ => None,
+        }
+    }
 }
 
 /// Render string template with placeholders.
-pub fn render_template(
-    template: &str,
-    hash: &str,
-    long_hash: &str,
-    tag: &str,
-    date: &str,
-    sep: &str,
-    branch: &str,
-) -> String {
-    template
-        .replace("{{hash}}", hash)
-        .replace("{{long}}", long_hash)
-        .replace("{{tag}}", tag)
-        .replace("{{date}}", date)
-        .replace("{{sep}}", sep)
-        .replace("{{branch}}", branch)
+///
+/// The template is scanned once, so text coming from a substituted value is
+/// never itself treated as a placeholder. Unknown placeholders are left as-is,
+/// and `\{{hash}}` renders the literal text `{{hash}}`.
+///
+/// `{{key|fallback}}` renders `fallback` when the value is empty (or, for
+/// `tag`, when no tag was found).
+pub fn render_template(template: &str, values: &TemplateValues) -> String {
+    substitute(template, values, |v, out| out.push_str(v))
+}
+
+/// [`render_template`] with every substituted value passed through
+/// [`sanitize_html`] (`sanitize = true`). The template itself is trusted.
+pub fn render_template_sanitized(template: &str, values: &TemplateValues) -> String {
+    substitute(template, values, |v, out| out.push_str(&sanitize_html(v)))
+}
+
+fn substitute(template: &str, values: &TemplateValues, emit: impl Fn(&str, &mut String)) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        if let Some(text) = rest[..start].strip_suffix('\\') {
+            // `\{{` is a literal `{{`: drop the backslash, keep the braces.
+            out.push_str(text);
+            out.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let (key, default) = match after[..end].split_once('|') {
+            Some((key, default)) => (key, Some(default)),
+            None => (&after[..end], None),
+        };
+        match values.get(key) {
+            Some(v) => {
+                rest = &after[end + 2..];
+                match default {
+                    // The default is template text, so it is trusted as-is.
+                    Some(d) if v.is_empty() || (key == "tag" && values.no_tag) => out.push_str(d),
+                    // An empty `{{sep}}` between spaces leaves a single space.
+                    None if key == "sep" && v.is_empty() && out.ends_with([' ', '\t']) => {
+                        rest = rest.trim_start_matches([' ', '\t']);
+                    }
+                    _ => emit(v, &mut out),
+                }
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Reduce an HTML fragment to the markup the preprocessor itself builds.
+///
+/// `<script>` elements are removed with their content. Other tags are kept
+/// only if they are in [`SAFE_TAGS`], with attributes limited to
+/// [`SAFE_ATTRS`] and `href` limited to relative, `http(s):` and `mailto:`
+/// URLs; any other tag is escaped so it shows as text.
+pub fn sanitize_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let tag_src = &rest[lt..];
+        let Some(gt) = tag_end(tag_src) else {
+            // Unterminated tag: keep it as text.
+            out.push_str(&escape_html(tag_src));
+            return out;
+        };
+        let tag = &tag_src[..=gt];
+        rest = &tag_src[gt + 1..];
+
+        let name = tag_name(tag);
+        if name.eq_ignore_ascii_case("script") && !tag.starts_with("</") {
+            // Drop everything up to and including the closing tag.
+            let lower = rest.to_ascii_lowercase();
+            rest = match lower.find("</script") {
+                Some(close) => {
+                    let after = &rest[close..];
+                    tag_end(after).map_or("", |e| &after[e + 1..])
+                }
+                None => "",
+            };
+            continue;
+        }
+        if name.eq_ignore_ascii_case("script") {
+            continue;
+        }
+        match clean_tag(tag) {
+            Some(clean) => out.push_str(&clean),
+            None => out.push_str(&escape_html(tag)),
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Elements [`sanitize_html`] keeps: the links, badges and dates the
+/// preprocessor emits, plus basic inline formatting.
+const SAFE_TAGS: &[&str] = &[
+    "a", "span", "time", "b", "i", "em", "strong", "code", "small", "br",
+];
+
+/// Attributes [`sanitize_html`] keeps on [`SAFE_TAGS`].
+const SAFE_ATTRS: &[&str] = &["href", "class", "title", "datetime"];
+
+/// Index of the `>` closing the tag at the start of `s`, skipping quoted values.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> &str {
+    let body = tag.trim_start_matches('<').trim_start_matches('/');
+    let end = body
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(body.len());
+    &body[..end]
+}
+
+/// Rebuild `tag` with only [`SAFE_ATTRS`] and safe URLs, every value
+/// double-quoted. `None` when the element is not in [`SAFE_TAGS`].
+fn clean_tag(tag: &str) -> Option<String> {
+    let name = tag_name(tag);
+    if !SAFE_TAGS.iter().any(|t| t.eq_ignore_ascii_case(name)) {
+        return None;
+    }
+    let name = name.to_ascii_lowercase();
+    if tag.starts_with("</") {
+        return Some(format!("</{name}>"));
+    }
+    let inner = tag[1 + name.len()..tag.len() - 1].trim_end();
+    let (attrs, self_closing) = match inner.strip_suffix('/') {
+        Some(a) => (a, true),
+        None => (inner, false),
+    };
+
+    // A `/` between attributes separates them like whitespace.
+    let is_sep = |c: char| c.is_whitespace() || c == '/';
+    let mut out = format!("<{name}");
+    let mut rest = attrs.trim_start_matches(is_sep);
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| is_sep(c) || c == '=')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+        let mut value = None;
+        if let Some(v) = rest.strip_prefix('=') {
+            let v = v.trim_start();
+            let (val, remaining) = match v.chars().next() {
+                Some(q @ ('"' | '\'')) => match v[1..].find(q) {
+                    Some(e) => (&v[1..e + 1], &v[e + 2..]),
+                    None => (&v[1..], ""),
+                },
+                _ => {
+                    let e = v.find(char::is_whitespace).unwrap_or(v.len());
+                    (&v[..e], &v[e..])
+                }
+            };
+            value = Some(val);
+            rest = remaining;
+        }
+        rest = rest.trim_start_matches(is_sep);
+
+        let key = key.to_ascii_lowercase();
+        let allowed =
+            SAFE_ATTRS.contains(&key.as_str()) && (key != "href" || value.is_some_and(is_safe_url));
+        if !allowed {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(&key);
+        if let Some(v) = value {
+            out.push_str(&format!(r#"="{}""#, v.replace('"', "&quot;")));
+        }
+    }
+    if self_closing {
+        out.push_str(" /");
+    }
+    out.push('>');
+    Some(out)
+}
+
+/// Whether `url` (an attribute value, possibly entity-encoded) is relative
+/// or uses the `http`, `https` or `mailto` scheme. Browsers ignore tabs,
+/// newlines and leading control characters in schemes, so those are removed
+/// before checking.
+fn is_safe_url(url: &str) -> bool {
+    let decoded: String = decode_entities(url)
+        .chars()
+        .filter(|c| !c.is_ascii_control() && !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match decoded.find([':', '/', '?', '#']) {
+        Some(i) if decoded[i..].starts_with(':') => {
+            matches!(&decoded[..i], "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Decode numeric character references (`&#106;`, `&#x6A;`, with or without
+/// the `;`) and the named references that can hide a URL scheme.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp + 1..];
+        let lower = rest.to_ascii_lowercase();
+        let numeric = lower.strip_prefix('#').map(|n| match n.strip_prefix('x') {
+            Some(hex) => (hex, 16, 2),
+            None => (n, 10, 1),
+        });
+        if let Some((digits, radix, prefix)) = numeric {
+            let len = digits
+                .find(|c: char| !c.is_digit(radix))
+                .unwrap_or(digits.len());
+            if let Some(c) = u32::from_str_radix(&digits[..len], radix)
+                .ok()
+                .and_then(char::from_u32)
+            {
+                out.push(c);
+                rest = &rest[prefix + len..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+                continue;
+            }
+        }
+        let named = [
+            ("colon;", ':'),
+            ("tab;", '\t'),
+            ("newline;", '\n'),
+            ("sol;", '/'),
+        ];
+        match named.iter().find(|(n, _)| lower.starts_with(n)) {
+            Some((n, c)) => {
+                out.push(*c);
+                rest = &rest[n.len()..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Escape `&`, `<`, `>` and `"` so plain text (e.g. a Git author name) can be
+/// inserted into HTML without altering the markup.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Branch name as a pill badge (`branch-badge = true`), with a
+/// `gitinfo-badge-<forge>` class selecting the forge colour.
+pub fn branch_badge(branch: &str, forge: Option<&str>) -> String {
+    let class = match forge {
+        Some(f) => format!("gitinfo-badge gitinfo-badge-{f}"),
+        None => "gitinfo-badge".to_string(),
+    };
+    format!(r#"<span class="{class}">{branch}</span>"#)
+}
+
+/// Turn line breaks in rendered template text into `<br>` so a multi-line
+/// `template` keeps its lines inside the header/footer element.
+pub fn multiline_breaks(text: &str) -> String {
+    text.trim_end_matches(['\r', '\n'])
+        .replace("\r\n", "\n")
+        .replace('\n', "<br>")
+}
+
+/// Convert inline Markdown emphasis in template text to HTML
+/// (`format = "markdown"`): `**…**` → `<strong>`, `*…*` and `_…_` → `<em>`.
+///
+/// Runs on the template before substitution, so `{{…}}` placeholders (and
+/// the values they expand to) are never interpreted. `\*` and `\_` are kept
+/// as literal characters; `_` inside a word (`snake_case`) is not emphasis.
+pub fn inline_markdown(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut i = 0;
+    while i < template.len() {
+        let rest = &template[i..];
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            if escaped.starts_with("{{") {
+                out.push_str("\\{{");
+                i += 3;
+                continue;
+            }
+            if let Some(c @ ('*' | '_')) = escaped.chars().next() {
+                out.push(c);
+                i += 2;
+                continue;
+            }
+        }
+        if rest.starts_with("{{") {
+            let len = rest.find("}}").map_or(rest.len(), |end| end + 2);
+            out.push_str(&rest[..len]);
+            i += len;
+            continue;
+        }
+        let word_before = template[..i]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let marker = if rest.starts_with("**") {
+            Some(("**", "strong"))
+        } else if rest.starts_with('*') || (rest.starts_with('_') && !word_before) {
+            Some((&rest[..1], "em"))
+        } else {
+            None
+        };
+        if let Some((marker, tag)) = marker
+            && let Some(end) = closing_marker(&rest[marker.len()..], marker)
+        {
+            let inner = &rest[marker.len()..marker.len() + end];
+            out.push_str(&format!("<{tag}>{}</{tag}>", inline_markdown(inner)));
+            i += 2 * marker.len() + end;
+            continue;
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Offset of the `marker` closing an emphasis run in `s`, skipping
+/// placeholders and escaped characters. The run must not be empty or start
+/// with whitespace; a closing `_` must not be followed by a word character.
+fn closing_marker(s: &str, marker: &str) -> Option<usize> {
+    if s.is_empty() || s.starts_with(char::is_whitespace) || s.starts_with(marker) {
+        return None;
+    }
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            i += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        if rest.starts_with("{{") {
+            i += rest.find("}}").map_or(rest.len(), |end| end + 2);
+            continue;
+        }
+        if marker == "*" && rest.starts_with("**") {
+            // A nested `**strong**` run, not the end of this one.
+            i += 2;
+            continue;
+        }
+        if rest.starts_with(marker) {
+            let closes =
+                marker != "_" || !rest[1..].chars().next().is_some_and(char::is_alphanumeric);
+            if closes && !s[..i].ends_with(char::is_whitespace) {
+                return Some(i);
+            }
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+/// Shorten `text` to at most `max_chars` characters, ending in `…` when cut.
+/// `0` disables truncation.
+pub fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Wrap a formatted date in `<time datetime="…">` for machine-readable output.
+///
+/// The `datetime` attribute is the commit time (`%cI`) normalised to UTC.
+/// Unparseable input yields the formatted text unchanged.
+pub fn time_element(raw_rfc3339: &str, formatted: &str) -> String {
+    match chrono::DateTime::parse_from_rfc3339(raw_rfc3339.trim()) {
+        Ok(dt) => {
+            let iso = dt
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            format!(r#"<time datetime="{iso}">{formatted}</time>"#)
+        }
+        Err(_) => formatted.to_string(),
+    }
+}
+
+/// CSS custom properties sizing the contributor avatars, appended to
+/// [`GITINFO_CSS`]. Unset values keep the stylesheet defaults (`32px`/`8px`).
+pub fn contributors_css_vars(avatar_size: Option<&str>, gap: Option<&str>) -> String {
+    let mut decls = String::new();
+    if let Some(size) = avatar_size.map(str::trim).filter(|s| !s.is_empty()) {
+        decls.push_str(&format!(" --gitinfo-avatar-size: {size};"));
+    }
+    if let Some(gap) = gap.map(str::trim).filter(|s| !s.is_empty()) {
+        decls.push_str(&format!(" --gitinfo-avatar-gap: {gap};"));
+    }
+    if decls.is_empty() {
+        return String::new();
+    }
+    format!(".gitinfo-contributors {{{decls} }}\n")
+}
+
+/// Overrides of the footer text colour in [`GITINFO_CSS`] for light themes
+/// and for mdBook's dark themes (`navy`, `coal`, `ayu`).
+pub fn footer_color_css(light: Option<&str>, dark: Option<&str>) -> String {
+    let pick = |v: Option<&str>| {
+        v.map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let mut css = String::new();
+    if let Some(c) = pick(light) {
+        css.push_str(&format!(":root {{ --gitinfo-footer-color: {c}; }}\n"));
+    }
+    if let Some(c) = pick(dark) {
+        css.push_str(&format!(
+            ".navy, .coal, .ayu {{ --gitinfo-footer-color: {c}; }}\n"
+        ));
+    }
+    css
+}
+
+/// `@media print` rules hiding footers according to `mode`.
+///
+/// Footers carry an inline `display:block`, so the rules need `!important`.
+/// In `print.html` all chapters are siblings, which the selectors rely on.
+pub fn print_footer_css(mode: FooterPrint) -> &'static str {
+    match mode {
+        FooterPrint::Each => "",
+        FooterPrint::First => {
+            "@media print {\n  .gitinfo-footer ~ .gitinfo-footer { display: none !important; }\n}\n"
+        }
+        FooterPrint::Last => {
+            "@media print {\n  .gitinfo-footer:has(~ .gitinfo-footer) { display: none !important; }\n}\n"
+        }
+        FooterPrint::None => "@media print {\n  .gitinfo-footer { display: none !important; }\n}\n",
+    }
 }
 
 /// Build inline CSS style string.
@@ -46,48 +685,281 @@ pub fn style_block(font_size: &str, align: &str, margin: &[String; 4]) -> String
     )
 }
 
+/// Shorten an inline CSS declaration list (`minify = true`): whitespace
+/// around `:` and `;` and the trailing `;` go, and four-value
+/// `margin`/`padding` shorthands lose repeated sides (`2em 0 0 0` →
+/// `2em 0 0`).
+pub fn minify_css(css: &str) -> String {
+    css.split(';')
+        .filter_map(|decl| {
+            let (name, value) = decl.split_once(':')?;
+            let name = name.trim();
+            let mut sides: Vec<&str> = value.split_whitespace().collect();
+            if matches!(name, "margin" | "padding") && sides.len() == 4 && sides[3] == sides[1] {
+                sides.pop();
+                if sides[2] == sides[0] {
+                    sides.pop();
+                    if sides[1] == sides[0] {
+                        sides.pop();
+                    }
+                }
+            }
+            Some(format!("{name}:{}", sides.join(" ")))
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Collapse whitespace in injected HTML (`minify = true`): every run of
+/// spaces, tabs and line breaks becomes one space, which browsers render
+/// the same. HTML with `<pre>` or `<textarea>`, where whitespace matters,
+/// is returned unchanged.
+pub fn minify_html(html: &str) -> String {
+    if html.contains("<pre") || html.contains("<textarea") {
+        return html.to_string();
+    }
+    html.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Element and base class of a header/footer block (`wrapper-tag`/`class-name`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wrapper<'a> {
+    pub tag: &'a str,
+    pub class: &'a str,
+}
+
+impl Wrapper<'static> {
+    /// `<header class="gitinfo-header">`
+    pub const HEADER: Self = Wrapper {
+        tag: "header",
+        class: "gitinfo-header",
+    };
+    /// `<footer class="gitinfo-footer">`
+    pub const FOOTER: Self = Wrapper {
+        tag: "footer",
+        class: "gitinfo-footer",
+    };
+}
+
+/// Whether `tag` can be used as an element name: ASCII letters and digits,
+/// plus `-` for custom elements, starting with a letter.
+pub fn is_valid_tag_name(tag: &str) -> bool {
+    tag.starts_with(|c: char| c.is_ascii_alphabetic())
+        && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
 /// Wrap HTML into header/footer element.
-pub fn wrap_block(is_header: bool, style: &str, html: &str) -> String {
-    if is_header {
-        format!(
-            r#"<header class="gitinfo-header" style="{}">{}</header>"#,
-            style, html
-        )
+///
+/// `extra_classes` are appended after the wrapper's base class (by default
+/// `gitinfo-header`/`gitinfo-footer`); the class list is HTML-escaped.
+/// `attrs` (from `header-attrs`/`footer-attrs`) follow `style` with escaped values;
+/// names that are not plain attribute names, or that would repeat `class`/`style`, are skipped.
+pub fn wrap_block(
+    wrapper: Wrapper,
+    extra_classes: &[&str],
+    style: &str,
+    attrs: &BTreeMap<String, String>,
+    html: &str,
+) -> String {
+    let tag = wrapper.tag;
+    let mut class = wrapper.class.to_string();
+    for c in extra_classes.iter().filter(|c| !c.is_empty()) {
+        class.push(' ');
+        class.push_str(c);
+    }
+    let class = escape_html(&class);
+    let mut extra = String::new();
+    for (name, value) in attrs {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+        if !valid || name.eq_ignore_ascii_case("class") || name.eq_ignore_ascii_case("style") {
+            logging::warn(format_args!("ignoring {tag} attribute '{name}'"));
+            continue;
+        }
+        extra.push_str(&format!(r#" {name}="{}""#, escape_html(value)));
+    }
+    if style.is_empty() {
+        // Plain output for renderers without CSS support.
+        format!(r#"<{tag} class="{class}"{extra}>{html}</{tag}>"#)
     } else {
-        format!(
-            r#"<footer class="gitinfo-footer" style="{}">{}</footer>"#,
-            style, html
-        )
+        format!(r#"<{tag} class="{class}" style="{style}"{extra}>{html}</{tag}>"#)
     }
 }
 
+/// The contributors template compiled into a Handlebars registry.
+///
+/// Compiling is the costly part of rendering the block, so a caller that
+/// renders it several times (one per `{% contributors %}` token) builds this
+/// once and reuses it. Nothing else in the crate touches Handlebars.
+pub struct ContributorsTemplate {
+    hb: Handlebars<'static>,
+}
+
+impl ContributorsTemplate {
+    /// Compile the bundled `contributor.hbs`.
+    pub fn new() -> Result<Self, mdbook_preprocessor::errors::Error> {
+        Self::from_source(CONTRIBUTORS_TEMPLATE)
+    }
+
+    /// Compile a user-supplied Handlebars `template` in place of the bundled
+    /// one; it receives the same context (`title`, `message`, `visible`,
+    /// `hidden`, ...).
+    pub fn from_source(template: &str) -> Result<Self, mdbook_preprocessor::errors::Error> {
+        let mut hb = Handlebars::new();
+        hb.register_template_string("contributors", template)
+            .map_err(|e| {
+                mdbook_preprocessor::errors::Error::msg(format!("contributors template error: {e}"))
+            })?;
+        Ok(ContributorsTemplate { hb })
+    }
+
+    /// Render the contributors block.
+    ///
+    /// `usernames_hidden` are listed inside the "Show all" expander; a further
+    /// `overflow_count` contributors are only summarised as "and N others".
+    pub fn render(
+        &self,
+        title: &str,
+        contributors_message: Option<&str>,
+        usernames_visible: &[String],
+        usernames_hidden: &[String],
+        overflow_count: usize,
+        options: &ContributorsOptions,
+    ) -> Result<String, mdbook_preprocessor::errors::Error> {
+        if is_empty_block(usernames_visible, usernames_hidden, overflow_count, options) {
+            return Ok(String::new());
+        }
+
+        let emails = match options.email_mode {
+            ContributorsEmails::Hide => BTreeMap::new(),
+            ContributorsEmails::Show | ContributorsEmails::Link => usernames_visible
+                .iter()
+                .chain(usernames_hidden)
+                .filter_map(|u| options.emails.get(u).map(|e| (u.as_str(), e.as_str())))
+                .collect(),
+        };
+
+        let counts = usernames_visible
+            .iter()
+            .chain(usernames_hidden)
+            .filter_map(|u| options.counts.get(u).map(|c| (u.as_str(), *c)))
+            .collect();
+
+        let profiles = match options.profile_base {
+            Some(base) => usernames_visible
+                .iter()
+                .chain(usernames_hidden)
+                .map(|u| (u.as_str(), profile_link(base, u)))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        let hidden_count = usernames_hidden.len();
+        let more_count = hidden_count + overflow_count;
+        let ctx = ContributorsCtx {
+            title,
+            message: contributors_message
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            usernames_visible,
+            usernames_hidden,
+            visible: usernames_visible,
+            hidden: usernames_hidden,
+            hidden_count,
+            overflow_count,
+            separator: options.separator,
+            more_label: options.more_text.replace("{n}", &more_count.to_string()),
+            emails,
+            email_link: options.email_mode == ContributorsEmails::Link,
+            counts,
+            profile_links: options.profile_base.is_some(),
+            profiles,
+            meta: options.meta,
+        };
+
+        self.hb.render("contributors", &ctx).map_err(|e| {
+            mdbook_preprocessor::errors::Error::msg(format!("contributors render error: {e}"))
+        })
+    }
+}
+
+/// `@name` linked to the profile under forge root `base`. Names that are
+/// URLs or contain whitespace are not usernames and stay plain text.
+fn profile_link(base: &str, name: &str) -> String {
+    let linkable = !name.is_empty() && !name.contains("://") && !name.contains(char::is_whitespace);
+    if !linkable {
+        return format!(
+            r#"<span class="contributor-name">{}</span>"#,
+            escape_html(name)
+        );
+    }
+    let user = name.trim_start_matches('@');
+    format!(
+        r#"<a class="contributor-profile" href="{}/{}">@{}</a>"#,
+        base.trim_end_matches('/'),
+        escape_html(user),
+        escape_html(user)
+    )
+}
+
+/// Whether the block renders as nothing (`hide_if_empty` with no one to list).
+fn is_empty_block(
+    usernames_visible: &[String],
+    usernames_hidden: &[String],
+    overflow_count: usize,
+    options: &ContributorsOptions,
+) -> bool {
+    options.hide_if_empty
+        && usernames_visible.is_empty()
+        && usernames_hidden.is_empty()
+        && overflow_count == 0
+}
+
+/// Render the contributors block, compiling the template for this call.
+/// See [`ContributorsTemplate::render`].
 pub fn render_contributors_hbs(
     title: &str,
     contributors_message: Option<&str>,
     usernames_visible: &[String],
     usernames_hidden: &[String],
+    overflow_count: usize,
+    options: &ContributorsOptions,
 ) -> Result<String, mdbook_preprocessor::errors::Error> {
-    let mut hb = Handlebars::new();
-    hb.register_template_string("contributors", CONTRIBUTORS_TEMPLATE)
-        .map_err(|e| {
-            mdbook_preprocessor::errors::Error::msg(format!("contributors template error: {e}"))
-        })?;
-
-    let hidden_count = usernames_hidden.len();
-    let ctx = ContributorsCtx {
+    render_contributors_with(
+        CONTRIBUTORS_TEMPLATE,
         title,
-        message: contributors_message
-            .map(str::trim)
-            .filter(|s| !s.is_empty())
-            .map(|s| String::from(s.to_string())),
+        contributors_message,
         usernames_visible,
         usernames_hidden,
-        hidden_count,
-    };
+        overflow_count,
+        options,
+    )
+}
 
-    hb.render("contributors", &ctx).map_err(|e| {
-        mdbook_preprocessor::errors::Error::msg(format!("contributors render error: {e}"))
-    })
+/// Render the contributors block with an explicit Handlebars `template`.
+fn render_contributors_with(
+    template: &str,
+    title: &str,
+    contributors_message: Option<&str>,
+    usernames_visible: &[String],
+    usernames_hidden: &[String],
+    overflow_count: usize,
+    options: &ContributorsOptions,
+) -> Result<String, mdbook_preprocessor::errors::Error> {
+    if is_empty_block(usernames_visible, usernames_hidden, overflow_count, options) {
+        return Ok(String::new());
+    }
+    ContributorsTemplate::from_source(template)?.render(
+        title,
+        contributors_message,
+        usernames_visible,
+        usernames_hidden,
+        overflow_count,
+        options,
+    )
 }
 
 #[cfg(test)]
@@ -99,12 +971,15 @@ mod tests {
         let t = "{{date}}{{sep}}commit: {{hash}} ({{long}}) tag={{tag}} branch={{branch}}";
         let out = render_template(
             t,
-            "abc123",
-            "abc123def456",
-            "v1.2.3",
-            "2026-01-14 12:34:56",
-            " • ",
-            "main",
+            &TemplateValues {
+                hash: "abc123",
+                long: "abc123def456",
+                tag: "v1.2.3",
+                date: "2026-01-14 12:34:56",
+                sep: " • ",
+                branch: "main",
+                ..Default::default()
+            },
         );
 
         assert!(out.contains("2026-01-14 12:34:56"));
@@ -124,10 +999,93 @@ mod tests {
     #[test]
     fn render_template_leaves_unknown_placeholders_untouched() {
         let t = "x={{hash}} y={{unknown}}";
-        let out = render_template(t, "h", "lh", "t", "d", "|", "b");
+        let values = TemplateValues {
+            hash: "h",
+            long: "lh",
+            tag: "t",
+            date: "d",
+            sep: "|",
+            branch: "b",
+            ..Default::default()
+        };
+        let out = render_template(t, &values);
         assert_eq!(out, "x=h y={{unknown}}");
     }
 
+    #[test]
+    fn render_template_does_not_expand_placeholders_inside_values() {
+        let values = TemplateValues {
+            hash: "{{branch}}",
+            branch: "main",
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("{{hash}}/{{branch}}", &values),
+            "{{branch}}/main"
+        );
+    }
+
+    #[test]
+    fn render_template_collapses_spaces_around_empty_separator() {
+        let values = TemplateValues {
+            hash: "h",
+            date: "d",
+            ..Default::default()
+        };
+        for (template, expected) in [
+            ("{{date}} {{sep}} {{hash}}", "d h"),
+            ("{{date}}  {{sep}}\t{{hash}}", "d  h"),
+            ("{{date}}{{sep}}{{hash}}", "dh"),
+            ("{{date}} {{sep}}{{hash}}", "d h"),
+            ("{{sep}} {{hash}}", " h"),
+            ("{{date}} {{sep| / }} {{hash}}", "d  /  h"),
+        ] {
+            assert_eq!(render_template(template, &values), expected, "{template}");
+        }
+
+        let values = TemplateValues {
+            sep: " • ",
+            ..values
+        };
+        assert_eq!(
+            render_template("{{date}} {{sep}} {{hash}}", &values),
+            "d  •  h"
+        );
+    }
+
+    #[test]
+    fn render_template_keeps_unterminated_placeholder() {
+        let values = TemplateValues {
+            hash: "h",
+            ..Default::default()
+        };
+        assert_eq!(render_template("{{hash}} {{date", &values), "h {{date");
+    }
+
+    #[test]
+    fn render_template_exposes_chapter_title_and_number() {
+        let values = TemplateValues {
+            chapter_title: "Setup",
+            chapter_number: "3",
+            ..Default::default()
+        };
+        let out = render_template(
+            "End of Chapter {{chapter-number}}: {{chapter-title}}",
+            &values,
+        );
+        assert_eq!(out, "End of Chapter 3: Setup");
+    }
+
+    #[test]
+    fn render_template_chapter_number_empty_when_unnumbered() {
+        let values = TemplateValues {
+            chapter_title: "Preface",
+            ..Default::default()
+        };
+        let out = render_template("[{{chapter-number}}] {{chapter-title}}", &values);
+        assert_eq!(out, "[] Preface");
+    }
+
     #[test]
     fn style_block_formats_expected_css() {
         let margin = [
@@ -147,7 +1105,13 @@ mod tests {
 
     #[test]
     fn wrap_block_header() {
-        let out = wrap_block(true, "font-size:1em;", "hello");
+        let out = wrap_block(
+            Wrapper::HEADER,
+            &[],
+            "font-size:1em;",
+            &BTreeMap::new(),
+            "hello",
+        );
         assert_eq!(
             out,
             r#"<header class="gitinfo-header" style="font-size:1em;">hello</header>"#
@@ -156,13 +1120,362 @@ mod tests {
 
     #[test]
     fn wrap_block_footer() {
-        let out = wrap_block(false, "font-size:1em;", "hello");
+        let out = wrap_block(
+            Wrapper::FOOTER,
+            &[],
+            "font-size:1em;",
+            &BTreeMap::new(),
+            "hello",
+        );
         assert_eq!(
             out,
             r#"<footer class="gitinfo-footer" style="font-size:1em;">hello</footer>"#
         );
     }
 
+    #[test]
+    fn wrap_block_custom_wrapper() {
+        let wrapper = Wrapper {
+            tag: "div",
+            class: "page-meta",
+        };
+        let out = wrap_block(wrapper, &["gitinfo-changed"], "", &BTreeMap::new(), "hello");
+        assert_eq!(out, r#"<div class="page-meta gitinfo-changed">hello</div>"#);
+    }
+
+    #[test]
+    fn tag_names_are_validated() {
+        assert!(is_valid_tag_name("div"));
+        assert!(is_valid_tag_name("h2"));
+        assert!(is_valid_tag_name("page-meta"));
+        assert!(!is_valid_tag_name(""));
+        assert!(!is_valid_tag_name("2col"));
+        assert!(!is_valid_tag_name("div onclick=x"));
+        assert!(!is_valid_tag_name("div>"));
+    }
+
+    #[test]
+    fn wrap_block_emits_escaped_attributes() {
+        let attrs = BTreeMap::from([
+            ("role".to_string(), "contentinfo".to_string()),
+            ("data-section".to_string(), r#"meta "x" & <y>"#.to_string()),
+            ("onload x".to_string(), "bad".to_string()),
+            ("class".to_string(), "override".to_string()),
+        ]);
+        let out = wrap_block(Wrapper::FOOTER, &[], "font-size:1em;", &attrs, "hello");
+        assert_eq!(
+            out,
+            r#"<footer class="gitinfo-footer" style="font-size:1em;" data-section="meta &quot;x&quot; &amp; &lt;y&gt;" role="contentinfo">hello</footer>"#
+        );
+    }
+
+    #[test]
+    fn wrap_block_appends_extra_classes() {
+        let out = wrap_block(
+            Wrapper::FOOTER,
+            &["gitinfo-changed", ""],
+            "font-size:1em;",
+            &BTreeMap::new(),
+            "hello",
+        );
+        assert_eq!(
+            out,
+            r#"<footer class="gitinfo-footer gitinfo-changed" style="font-size:1em;">hello</footer>"#
+        );
+    }
+
+    #[test]
+    fn time_element_has_utc_datetime_attribute() {
+        let out = time_element("2024-01-01T14:00:00+02:00", "1 Jan 2024");
+        assert_eq!(
+            out,
+            r#"<time datetime="2024-01-01T12:00:00Z">1 Jan 2024</time>"#
+        );
+        assert!(chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").is_ok());
+    }
+
+    #[test]
+    fn escape_html_neutralises_markup() {
+        assert_eq!(escape_html("Jane Doe"), "Jane Doe");
+        assert_eq!(
+            escape_html(r#"<b>"A" & B</b>"#),
+            "&lt;b&gt;&quot;A&quot; &amp; B&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn minify_css_drops_spacing_and_repeated_sides() {
+        assert_eq!(
+            minify_css(&style_block(
+                "0.8em",
+                "center",
+                &margin(["2em", "0", "0", "0"])
+            )),
+            "font-size:0.8em;padding:4px;margin:2em 0 0;text-align:center;display:block"
+        );
+        assert_eq!(minify_css(" margin : 1em  1em 1em 1em ; "), "margin:1em");
+        assert_eq!(minify_css("padding:0 1em 0 1em"), "padding:0 1em");
+        assert_eq!(
+            minify_css("margin:1px 2px 3px 4px"),
+            "margin:1px 2px 3px 4px"
+        );
+    }
+
+    #[test]
+    fn minify_html_collapses_whitespace_outside_pre() {
+        assert_eq!(
+            minify_html("<div>\n    <a href=\"x\">a</a>\n\n    <a>b</a>\n</div>\n"),
+            r#"<div> <a href="x">a</a> <a>b</a> </div>"#
+        );
+        let pre = "<pre>\n  keep\n</pre>";
+        assert_eq!(minify_html(pre), pre);
+    }
+
+    #[test]
+    fn branch_badge_markup_and_forge_class() {
+        assert_eq!(
+            branch_badge("main", None),
+            r#"<span class="gitinfo-badge">main</span>"#
+        );
+        assert_eq!(
+            branch_badge("dev", Some("gitlab")),
+            r#"<span class="gitinfo-badge gitinfo-badge-gitlab">dev</span>"#
+        );
+        assert!(GITINFO_CSS.contains(".gitinfo-badge-github"));
+        assert!(GITINFO_CSS.contains(".gitinfo-badge-gitea"));
+        assert!(GITINFO_CSS.contains(".gitinfo-badge-azure"));
+    }
+
+    #[test]
+    fn footer_color_css_overrides_light_and_dark() {
+        assert_eq!(footer_color_css(None, Some(" ")), "");
+        assert_eq!(
+            footer_color_css(Some("#333"), Some("#ccc")),
+            ":root { --gitinfo-footer-color: #333; }\n\
+             .navy, .coal, .ayu { --gitinfo-footer-color: #ccc; }\n"
+        );
+        assert!(GITINFO_CSS.contains(".navy, .coal, .ayu"));
+    }
+
+    #[test]
+    fn inline_markdown_emphasis() {
+        assert_eq!(
+            inline_markdown("**Updated** {{date}} by _{{author}}_"),
+            "<strong>Updated</strong> {{date}} by <em>{{author}}</em>"
+        );
+        assert_eq!(
+            inline_markdown("*a **b** c*"),
+            "<em>a <strong>b</strong> c</em>"
+        );
+        assert_eq!(inline_markdown("snake_case_name"), "snake_case_name");
+        assert_eq!(
+            inline_markdown(r"\*not em\* 2 * 3 * 4"),
+            "*not em* 2 * 3 * 4"
+        );
+        assert_eq!(inline_markdown(r"\{{hash}} **"), r"\{{hash}} **");
+    }
+
+    #[test]
+    fn inline_markdown_leaves_values_alone() {
+        let values = TemplateValues {
+            subject: "fix **all** the_things_",
+            ..Default::default()
+        };
+        let out = render_template(&inline_markdown("**{{subject}}**"), &values);
+        assert_eq!(out, "<strong>fix **all** the_things_</strong>");
+    }
+
+    #[test]
+    fn multiline_breaks_two_line_template() {
+        let values = TemplateValues {
+            hash: "abc1234",
+            date: "2025-01-01",
+            ..Default::default()
+        };
+        let out = render_template("commit {{hash}}\nupdated {{date}}\n", &values);
+        assert_eq!(
+            multiline_breaks(&out),
+            "commit abc1234<br>updated 2025-01-01"
+        );
+        assert_eq!(multiline_breaks("a\r\nb"), "a<br>b");
+    }
+
+    #[test]
+    fn truncate_with_ellipsis_counts_characters() {
+        assert_eq!(truncate_with_ellipsis("short", 72), "short");
+        assert_eq!(truncate_with_ellipsis("exactly5", 8), "exactly5");
+        assert_eq!(truncate_with_ellipsis("Fix the parser", 8), "Fix the…");
+        assert_eq!(truncate_with_ellipsis("héllo wörld", 6), "héllo…");
+        assert_eq!(truncate_with_ellipsis("anything", 0), "anything");
+    }
+
+    #[test]
+    fn placeholder_defaults_apply_only_to_empty_values() {
+        let values = TemplateValues {
+            hash: "abc1234",
+            author: "",
+            tag: "v1.0",
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("{{hash|none}} {{author|unknown}} {{author}}.", &values),
+            "abc1234 unknown ."
+        );
+        assert_eq!(render_template("{{tag|untagged}}", &values), "v1.0");
+        assert_eq!(render_template("{{nope|x}}", &values), "{{nope|x}}");
+        assert_eq!(render_template("[{{author|}}]", &values), "[]");
+
+        let missing = TemplateValues {
+            tag: "-",
+            no_tag: true,
+            ..Default::default()
+        };
+        assert_eq!(render_template("{{tag}}", &missing), "-");
+        assert_eq!(render_template("{{tag|untagged}}", &missing), "untagged");
+    }
+
+    #[test]
+    fn escaped_placeholders_render_literally() {
+        let values = TemplateValues {
+            hash: "abc1234",
+            branch: "main",
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template(r"\{{hash}} is {{hash}} on \{{branch}}/{{branch}}", &values),
+            "{{hash}} is abc1234 on {{branch}}/main"
+        );
+        assert_eq!(render_template(r"a\b {{hash}}\", &values), r"a\b abc1234\");
+    }
+
+    #[test]
+    fn sanitize_html_removes_script_and_handlers() {
+        assert_eq!(sanitize_html("fix <script>alert(1)</script>bug"), "fix bug");
+        assert_eq!(
+            sanitize_html(r#"<span class=x onclick="alert(1)">y</span>"#),
+            r#"<span class="x">y</span>"#
+        );
+        assert_eq!(
+            sanitize_html(r#"<a href="javascript:alert(1)" title='t'>x</a>"#),
+            r#"<a title="t">x</a>"#
+        );
+        assert_eq!(sanitize_html("<SCRIPT src=evil.js></SCRIPT>ok"), "ok");
+        assert_eq!(sanitize_html("a < b"), "a &lt; b");
+    }
+
+    #[test]
+    fn sanitize_html_escapes_unknown_elements() {
+        assert_eq!(
+            sanitize_html(r#"<img src=x onerror="alert(1)">"#),
+            "&lt;img src=x onerror=&quot;alert(1)&quot;&gt;"
+        );
+        assert_eq!(
+            sanitize_html("<svg/onload=alert(1)>"),
+            "&lt;svg/onload=alert(1)&gt;"
+        );
+        assert_eq!(
+            sanitize_html(r#"<iframe srcdoc="<script>alert(1)</script>"></iframe>"#),
+            "&lt;iframe srcdoc=&quot;&lt;script&gt;alert(1)&lt;/script&gt;&quot;&gt;&lt;/iframe&gt;"
+        );
+    }
+
+    #[test]
+    fn sanitize_html_rejects_disguised_javascript_urls() {
+        for href in [
+            "&#106;avascript:alert(1)",
+            "&#x6A;avascript:alert(1)",
+            "&#106avascript:alert(1)",
+            "java\tscript:alert(1)",
+            "java&Tab;script:alert(1)",
+            " \x01javascript:alert(1)",
+            "javascript&colon;alert(1)",
+            "JaVaScRiPt:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+        ] {
+            let html = format!(r#"<a href="{href}">x</a>"#);
+            assert_eq!(sanitize_html(&html), "<a>x</a>", "{href:?}");
+        }
+        assert_eq!(
+            sanitize_html(r#"<a xlink:href="javascript:alert(1)">x</a>"#),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize_html("<a/onmouseover=alert(1) href=/docs>x</a>"),
+            r#"<a href="/docs">x</a>"#
+        );
+    }
+
+    #[test]
+    fn sanitize_html_keeps_links() {
+        let link = r#"<a href="https://github.com/o/r/commit/abc">abc</a>"#;
+        assert_eq!(sanitize_html(link), link);
+    }
+
+    #[test]
+    fn render_template_sanitized_only_touches_values() {
+        let values = TemplateValues {
+            subject: "<script>steal()</script>Update docs",
+            branch: r#"<a href="https://x/tree/main">main</a>"#,
+            ..Default::default()
+        };
+        let out = render_template_sanitized("<em>{{subject}}</em> on {{branch}}", &values);
+        assert_eq!(
+            out,
+            r#"<em>Update docs</em> on <a href="https://x/tree/main">main</a>"#
+        );
+    }
+
+    #[test]
+    fn render_template_exposes_author_and_committer() {
+        let values = TemplateValues {
+            author: "Ann",
+            committer: "Bot",
+            ..Default::default()
+        };
+        let out = render_template("by {{author}} via {{committer}}", &values);
+        assert_eq!(out, "by Ann via Bot");
+    }
+
+    #[test]
+    fn time_element_falls_back_to_text() {
+        assert_eq!(time_element("", "unknown"), "unknown");
+    }
+
+    #[test]
+    fn contributors_css_vars_only_for_configured_knobs() {
+        assert_eq!(contributors_css_vars(None, None), "");
+        assert_eq!(contributors_css_vars(Some(" "), None), "");
+        assert_eq!(
+            contributors_css_vars(Some("48px"), None),
+            ".gitinfo-contributors { --gitinfo-avatar-size: 48px; }\n"
+        );
+        assert_eq!(
+            contributors_css_vars(Some("2.5rem"), Some("4px")),
+            ".gitinfo-contributors { --gitinfo-avatar-size: 2.5rem; --gitinfo-avatar-gap: 4px; }\n"
+        );
+    }
+
+    #[test]
+    fn gitinfo_css_styles_contributor_links_and_avatars() {
+        assert!(GITINFO_CSS.contains(".gitinfo-contributors a"));
+        assert!(GITINFO_CSS.contains(".gitinfo-contributors img"));
+        assert!(GITINFO_CSS.contains("var(--gitinfo-avatar-size, 32px)"));
+        assert!(GITINFO_CSS.contains("var(--gitinfo-avatar-gap, 8px)"));
+    }
+
+    #[test]
+    fn print_footer_css_per_mode() {
+        assert_eq!(print_footer_css(FooterPrint::Each), "");
+        for mode in [FooterPrint::First, FooterPrint::Last, FooterPrint::None] {
+            let css = print_footer_css(mode);
+            assert!(css.starts_with("@media print {"), "{mode:?}");
+            assert!(css.contains("display: none !important;"), "{mode:?}");
+        }
+        assert!(print_footer_css(FooterPrint::First).contains(".gitinfo-footer ~ .gitinfo-footer"));
+        assert!(print_footer_css(FooterPrint::Last).contains(":has(~ .gitinfo-footer)"));
+        assert!(print_footer_css(FooterPrint::None).contains("  .gitinfo-footer {"));
+    }
+
     #[test]
     fn gitinfo_css_is_present_and_has_expected_selector() {
         // Guard: accidental empty file / wrong include path.
@@ -176,8 +1489,15 @@ mod tests {
         let visible = vec!["author1".to_string(), "author2".to_string()];
         let hidden = vec!["author3".to_string()];
 
-        let html = render_contributors_hbs("Contributors", None, &visible, &hidden)
-            .expect("contributors template should render");
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &visible,
+            &hidden,
+            0,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
 
         // Title
         assert!(html.contains("Contributors"));
@@ -196,12 +1516,444 @@ mod tests {
         let hidden: Vec<String> = vec![];
 
         let msg = Some("<em>Thanks!</em>");
-        let html = render_contributors_hbs("Contributors", msg, &visible, &hidden)
-            .expect("contributors template should render");
+        let html = render_contributors_hbs(
+            "Contributors",
+            msg,
+            &visible,
+            &hidden,
+            0,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
 
         // This test assumes the template uses triple-stash {{{message}}}
         // so that raw HTML is not escaped.
         assert!(html.contains("<em>Thanks!</em>"));
         assert!(!html.contains("&lt;em&gt;Thanks!&lt;/em&gt;"));
     }
+
+    #[test]
+    fn render_contributors_hbs_default_more_text_matches_legacy_chip() {
+        let visible = vec!["author1".to_string()];
+        let hidden = vec!["author2".to_string(), "author3".to_string()];
+
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &visible,
+            &hidden,
+            0,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
+
+        assert!(html.contains("2+"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_uses_custom_more_text() {
+        let visible = vec!["author1".to_string()];
+        let hidden = vec!["author2".to_string(), "author3".to_string()];
+
+        let options = ContributorsOptions {
+            more_text: "and {n} more",
+            ..Default::default()
+        };
+        let html = render_contributors_hbs("Contributors", None, &visible, &hidden, 0, &options)
+            .expect("contributors template should render");
+
+        assert!(html.contains("and 2 more"));
+        assert!(!html.contains("2+"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_shows_known_counts() {
+        let visible = vec!["alice".to_string(), "bob".to_string()];
+        let hidden = vec!["carol".to_string()];
+        let counts = BTreeMap::from([("alice".to_string(), 42), ("carol".to_string(), 3)]);
+        let options = ContributorsOptions {
+            counts: &counts,
+            ..Default::default()
+        };
+
+        let html = render_contributors_hbs("Contributors", None, &visible, &hidden, 0, &options)
+            .expect("contributors template should render");
+
+        assert!(html.contains(r#"<span class="contributor-count">(42)</span>"#));
+        assert!(html.contains(r#"<span class="contributor-count">(3)</span>"#));
+        assert_eq!(html.matches("contributor-count").count(), 2);
+
+        let plain = render_contributors_hbs(
+            "Contributors",
+            None,
+            &visible,
+            &hidden,
+            0,
+            &Default::default(),
+        )
+        .unwrap();
+        assert!(!plain.contains("contributor-count"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_links_profiles_on_forge_host() {
+        let visible = vec![
+            "alice".to_string(),
+            "https://example.com/bob".to_string(),
+            "Carol Smith".to_string(),
+        ];
+        let hidden = vec!["dave".to_string()];
+        for (host, expected) in [
+            ("https://github.com", "https://github.com/alice"),
+            (
+                "https://gitlab.example.com",
+                "https://gitlab.example.com/alice",
+            ),
+        ] {
+            let options = ContributorsOptions {
+                profile_base: Some(host),
+                ..Default::default()
+            };
+            let html =
+                render_contributors_hbs("Contributors", None, &visible, &hidden, 0, &options)
+                    .unwrap();
+
+            assert!(html.contains(&format!(
+                r#"<a class="contributor-profile" href="{expected}">@alice</a>"#
+            )));
+            assert!(html.contains(&format!(r#"href="{host}/dave">@dave</a>"#)));
+            assert!(
+                html.contains(r#"<span class="contributor-name">https://example.com/bob</span>"#)
+            );
+            assert!(html.contains(r#"<span class="contributor-name">Carol Smith</span>"#));
+            assert!(!html.contains("<img"));
+        }
+    }
+
+    #[test]
+    fn render_contributors_hbs_places_separator_between_entries_only() {
+        let visible = vec![
+            "author1".to_string(),
+            "author2".to_string(),
+            "author3".to_string(),
+        ];
+        let hidden: Vec<String> = vec![];
+        let options = ContributorsOptions {
+            separator: " | ",
+            ..Default::default()
+        };
+
+        let html = render_contributors_hbs("Contributors", None, &visible, &hidden, 0, &options)
+            .expect("contributors template should render");
+
+        assert_eq!(html.matches(" | ").count(), 2);
+    }
+
+    #[test]
+    fn render_contributors_hbs_empty_list_renders_nothing_by_default() {
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &[],
+            &[],
+            0,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
+
+        assert_eq!(html, "");
+    }
+
+    #[test]
+    fn render_contributors_hbs_empty_list_keeps_title_when_not_hidden() {
+        let options = ContributorsOptions {
+            hide_if_empty: false,
+            ..Default::default()
+        };
+        let html = render_contributors_hbs("Contributors", None, &[], &[], 0, &options)
+            .expect("contributors template should render");
+
+        assert!(html.contains("<h2>Contributors</h2>"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_summarises_overflow() {
+        let visible = vec!["author1".to_string()];
+        let hidden = vec!["author2".to_string()];
+
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &visible,
+            &hidden,
+            40,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
+
+        assert!(html.contains("author2"));
+        assert!(html.contains("and 40 others"));
+        assert!(html.contains("41+"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_without_overflow_has_no_summary() {
+        let visible = vec!["author1".to_string()];
+        let hidden = vec!["author2".to_string()];
+
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &visible,
+            &hidden,
+            0,
+            &ContributorsOptions::default(),
+        )
+        .expect("contributors template should render");
+
+        assert!(!html.contains("others"));
+    }
+
+    fn email_book() -> BTreeMap<String, String> {
+        BTreeMap::from([("Jane Doe".to_string(), "jane@x.com".to_string())])
+    }
+
+    #[test]
+    fn render_contributors_hbs_hides_emails_by_default() {
+        let emails = email_book();
+        let options = ContributorsOptions {
+            emails: &emails,
+            ..Default::default()
+        };
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &["Jane Doe".to_string()],
+            &[],
+            0,
+            &options,
+        )
+        .expect("contributors template should render");
+
+        assert!(!html.contains("jane@x.com"));
+    }
+
+    #[test]
+    fn render_contributors_hbs_shows_or_links_emails() {
+        let emails = email_book();
+        let visible = vec!["Jane Doe".to_string(), "bob".to_string()];
+        let render = |email_mode| {
+            let options = ContributorsOptions {
+                emails: &emails,
+                email_mode,
+                ..Default::default()
+            };
+            render_contributors_hbs("Contributors", None, &visible, &[], 0, &options)
+                .expect("contributors template should render")
+        };
+
+        let shown = render(ContributorsEmails::Show);
+        assert!(shown.contains(r#"<span class="contributor-email">jane@x.com</span>"#));
+        assert!(!shown.contains("mailto:"));
+
+        let linked = render(ContributorsEmails::Link);
+        assert!(
+            linked.contains(
+                r#"<a class="contributor-email" href="mailto:jane@x.com">jane@x.com</a>"#
+            )
+        );
+        assert_eq!(linked.matches("mailto:").count(), 1);
+    }
+
+    #[test]
+    fn contributors_template_can_use_repo_metadata() {
+        let visible = vec!["author1".to_string()];
+        let options = ContributorsOptions {
+            meta: RepoMeta {
+                hash: "abc123",
+                branch: "main",
+                tag: "v1.0.0",
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let html = render_contributors_with(
+            "Contributors as of {{branch}}@{{hash}} ({{tag}})",
+            "Contributors",
+            None,
+            &visible,
+            &[],
+            0,
+            &options,
+        )
+        .expect("contributors template should render");
+
+        assert_eq!(html, "Contributors as of main@abc123 (v1.0.0)");
+    }
+
+    // Snapshot matrix for the generated markup. Any change to the HTML/CSS the
+    // preprocessor emits shows up as a snapshot diff; review it with
+    // `cargo insta review` (or `INSTA_UPDATE=always cargo test`) and commit the
+    // updated files under `src/snapshots/`.
+
+    const LINK_BASE: &str = "https://github.com/o/r";
+
+    fn margin(trbl: [&str; 4]) -> [String; 4] {
+        trbl.map(str::to_string)
+    }
+
+    /// Render a complete header/footer block the way the preprocessor does.
+    fn block(is_header: bool, hyperlink: bool, align: &str, classes: &[&str]) -> String {
+        let (hash, branch, tag) = if hyperlink {
+            (
+                format!(r#"<a href="{LINK_BASE}/commit/abc123def456">abc123d</a>"#),
+                format!(r#"<a href="{LINK_BASE}/tree/main">main</a>"#),
+                format!(r#"<a href="{LINK_BASE}/releases/tag/v1.2.3">v1.2.3</a>"#),
+            )
+        } else {
+            (
+                "abc123d".to_string(),
+                "main".to_string(),
+                "v1.2.3".to_string(),
+            )
+        };
+        let values = TemplateValues {
+            hash: &hash,
+            long: "abc123def456",
+            tag: &tag,
+            date: "2026-01-14 12:34:56",
+            sep: " • ",
+            branch: &branch,
+            chapter_title: "Setup",
+            chapter_number: "3",
+            days_since: "12",
+            ..Default::default()
+        };
+        let m = if is_header {
+            margin(["0", "0", "2em", "0"])
+        } else {
+            margin(["2em", "0", "0", "0"])
+        };
+        let style = style_block("0.8em", align, &m);
+        let html = render_template(
+            "{{date}}{{sep}}branch: {{branch}}{{sep}}commit: {{hash}}{{sep}}tag: {{tag}}",
+            &values,
+        );
+        let wrapper = if is_header {
+            Wrapper::HEADER
+        } else {
+            Wrapper::FOOTER
+        };
+        wrap_block(wrapper, classes, &style, &BTreeMap::new(), &html)
+    }
+
+    #[test]
+    fn snapshot_blocks_plain_and_hyperlinked() {
+        for (placement, is_header) in [("header", true), ("footer", false)] {
+            for (variant, hyperlink) in [("plain", false), ("hyperlinked", true)] {
+                insta::assert_snapshot!(
+                    format!("block_{placement}_{variant}"),
+                    block(is_header, hyperlink, "center", &[])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn snapshot_footer_with_extra_classes() {
+        insta::assert_snapshot!(block(
+            false,
+            false,
+            "right",
+            &["gitinfo-changed", "gitinfo-stale"]
+        ));
+    }
+
+    #[test]
+    fn snapshot_style_block_alignments() {
+        let out: Vec<String> = ["left", "center", "right"]
+            .iter()
+            .map(|a| style_block("0.9em", a, &margin(["1em", "0", "0.5em", "0"])))
+            .collect();
+        insta::assert_snapshot!(out.join("\n"));
+    }
+
+    #[test]
+    fn snapshot_render_template_chapter_placeholders() {
+        let values = TemplateValues {
+            hash: "abc123d",
+            date: "2026-01-14",
+            sep: " | ",
+            chapter_title: "Setup",
+            chapter_number: "3",
+            days_since: "12",
+            ..Default::default()
+        };
+        insta::assert_snapshot!(render_template(
+            "Chapter {{chapter-number}}: {{chapter-title}}{{sep}}{{date}} ({{days-since}} days ago){{sep}}{{hash}} {{unknown}}",
+            &values
+        ));
+    }
+
+    fn users(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn snapshot_contributors_visible_only() {
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &users(&["author1", "author2"]),
+            &[],
+            0,
+            &ContributorsOptions::default(),
+        )
+        .unwrap();
+        insta::assert_snapshot!(html);
+    }
+
+    #[test]
+    fn snapshot_contributors_with_hidden_message_and_separator() {
+        let options = ContributorsOptions {
+            separator: " · ",
+            more_text: "and {n} more",
+            ..Default::default()
+        };
+        let html = render_contributors_hbs(
+            "Contributors",
+            Some("<em>Thanks!</em>"),
+            &users(&["author1", "author2"]),
+            &users(&["author3", "author4"]),
+            0,
+            &options,
+        )
+        .unwrap();
+        insta::assert_snapshot!(html);
+    }
+
+    #[test]
+    fn snapshot_contributors_with_overflow() {
+        let html = render_contributors_hbs(
+            "Contributors",
+            None,
+            &users(&["author1"]),
+            &users(&["author2"]),
+            98,
+            &ContributorsOptions::default(),
+        )
+        .unwrap();
+        insta::assert_snapshot!(html);
+    }
+
+    #[test]
+    fn snapshot_contributors_empty_with_title() {
+        let options = ContributorsOptions {
+            hide_if_empty: false,
+            ..Default::default()
+        };
+        let html = render_contributors_hbs("Contributors", None, &[], &[], 0, &options).unwrap();
+        insta::assert_snapshot!(html);
+    }
 }
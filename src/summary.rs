@@ -0,0 +1,143 @@
+//! Repository-level build summary.
+//!
+//! [`build_summary`] renders a small HTML block describing the build as a
+//! whole (HEAD commit, branch, tag, build date and contributor count), for
+//! use on an "About this build" page or anywhere else a library consumer
+//! wants it.
+
+use crate::config::GitInfoConfig;
+use crate::git::{self, GitRunner};
+use crate::renderer::{TemplateValues, render_template};
+use crate::timefmt::{build_now, format_commit_datetime};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const SUMMARY_TEMPLATE: &str = "commit: {{hash}}{{sep}}branch: {{branch}}{{sep}}tag: {{tag}}{{sep}}built: {{date}}{{sep}}contributors: {{contributors}}";
+
+/// Render an HTML summary of the repository at `dir`.
+///
+/// The summary honours the same settings as the per-chapter footer where
+/// they apply: `branch`, `tag`, `separator`, `date-format`, `time-format`,
+/// `timezone` and `contributors-exclude`. Values Git cannot provide render
+/// as `-`.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+/// use mdbook_gitinfo::{GitInfoConfig, build_summary};
+///
+/// let html = build_summary(&GitInfoConfig::default(), Path::new("."));
+/// std::fs::write("src/about-build.html", html).unwrap();
+/// ```
+pub fn build_summary(cfg: &GitInfoConfig, dir: &Path) -> String {
+    let or_dash = |s: String| {
+        if s.trim().is_empty() {
+            "-".to_string()
+        } else {
+            s
+        }
+    };
+
+    let runner = git::SystemGit::from_config(cfg.git_binary.as_deref());
+    let branch = cfg.branch.clone().unwrap_or_else(|| "main".to_string());
+    let short_hash = or_dash(
+        runner
+            .output(&["rev-parse", "--short", "HEAD"], dir)
+            .unwrap_or_default(),
+    );
+    let long_hash = or_dash(
+        runner
+            .output(&["rev-parse", "HEAD"], dir)
+            .unwrap_or_default(),
+    );
+
+    let tag = match cfg.tag.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        Some(t) => t.to_string(),
+        None => git::latest_tag_for_branch_with(&runner, &branch, dir),
+    };
+    let tag = if tag.contains("No tags found") {
+        "-".to_string()
+    } else {
+        tag
+    };
+
+    let built = format_commit_datetime(
+        &build_now(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref()).to_rfc3339(),
+        cfg.timezone.as_deref(),
+        cfg.date_format.as_deref().unwrap_or("%Y-%m-%d"),
+        cfg.time_format.as_deref().unwrap_or("%H:%M:%S"),
+        cfg.show_date.unwrap_or(true),
+        cfg.show_time.unwrap_or(true),
+    );
+
+    let excluded: BTreeSet<&str> = cfg
+        .contributors_exclude
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    let contributors = git::get_contributor_usernames_from_shortlog_with(&runner, dir)
+        .map(|users| {
+            users
+                .iter()
+                .filter(|u| !excluded.contains(u.as_str()))
+                .count()
+        })
+        .unwrap_or(0)
+        .to_string();
+
+    let separator = cfg.separator.as_deref().unwrap_or(" • ");
+    let values = TemplateValues {
+        hash: &short_hash,
+        long: &long_hash,
+        tag: &tag,
+        date: &built,
+        sep: separator,
+        branch: &branch,
+        contributors: &contributors,
+        ..Default::default()
+    };
+    let body = render_template(SUMMARY_TEMPLATE, &values);
+
+    format!(r#"<div class="gitinfo-summary">{}</div>"#, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::git_in;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn build_summary_reports_repo_level_metadata() {
+        let repo = TempDir::new().unwrap();
+        git_in(repo.path(), &["init", "-q", "-b", "main"]);
+        fs::write(repo.path().join("a.md"), "a").unwrap();
+        git_in(repo.path(), &["add", "a.md"]);
+        git_in(repo.path(), &["commit", "-q", "-m", "a"]);
+        git_in(repo.path(), &["tag", "v0.1.0"]);
+        let head = git_in(repo.path(), &["rev-parse", "--short", "HEAD"]);
+
+        let html = build_summary(&GitInfoConfig::default(), repo.path());
+
+        assert!(html.starts_with(r#"<div class="gitinfo-summary">"#));
+        assert!(html.contains(&format!("commit: {head}")));
+        assert!(html.contains("branch: main"));
+        assert!(html.contains("tag: v0.1.0"));
+        assert!(html.contains("contributors: 1"));
+        assert!(!html.contains("{{"));
+    }
+
+    #[test]
+    fn build_summary_falls_back_to_dashes_outside_a_repo() {
+        let dir = TempDir::new().unwrap();
+
+        let html = build_summary(&GitInfoConfig::default(), dir.path());
+
+        assert!(html.contains("commit: -"));
+        assert!(html.contains("tag: -"));
+        assert!(html.contains("contributors: 0"));
+    }
+}
@@ -0,0 +1,23 @@
+//! Helpers shared by the unit tests and, through `#[path]`, the integration
+//! tests.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Run `git` in `dir` as the `tester` identity and return trimmed `stdout`.
+/// Panics if Git fails.
+pub fn git_in(dir: &Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .args([
+            "-c",
+            "user.name=tester",
+            "-c",
+            "user.email=tester@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
+}
@@ -1,39 +1,91 @@
+use crate::logging;
 use mdbook_preprocessor::PreprocessorContext;
+use mdbook_preprocessor::errors::Error;
 use std::fs;
 use std::io;
 use toml_edit::{DocumentMut, Item, Value};
 
 const CSS_REL_PATH: &str = "theme/gitinfo.css";
+const JS_REL_PATH: &str = "theme/gitinfo.js";
+
+/// Write `theme/gitinfo.css` and register it in `book.toml`.
+///
+/// Failures are logged as warnings, or returned as errors when `strict`
+/// (`assets-strict = true`) so a read-only checkout doesn't silently lose styling.
+pub fn ensure_gitinfo_assets(
+    ctx: &PreprocessorContext,
+    css_contents: &str,
+    strict: bool,
+) -> Result<(), Error> {
+    ensure_asset(ctx, CSS_REL_PATH, "additional-css", css_contents, strict)
+}
 
-pub fn ensure_gitinfo_assets(ctx: &PreprocessorContext, css_contents: &str) {
-    if let Err(e) = ensure_css_file(ctx, css_contents) {
-        eprintln!(
-            "[mdbook-gitinfo] Warning: unable to write {}: {e}",
-            CSS_REL_PATH
-        );
+/// Write `theme/gitinfo.js` and register it under `additional-js` in
+/// `book.toml` (`inject-js = true`). Failures are handled as in
+/// [`ensure_gitinfo_assets`].
+pub fn ensure_gitinfo_js(
+    ctx: &PreprocessorContext,
+    js_contents: &str,
+    strict: bool,
+) -> Result<(), Error> {
+    ensure_asset(ctx, JS_REL_PATH, "additional-js", js_contents, strict)
+}
+
+fn ensure_asset(
+    ctx: &PreprocessorContext,
+    rel_path: &str,
+    key: &str,
+    contents: &str,
+    strict: bool,
+) -> Result<(), Error> {
+    let report = |msg: String| {
+        if strict {
+            Err(Error::msg(msg))
+        } else {
+            logging::warn(format_args!("{msg}"));
+            Ok(())
+        }
+    };
+
+    if let Err(e) = ensure_theme_file(ctx, rel_path, contents) {
+        report(format!("unable to write {rel_path}: {e}"))?;
     }
 
-    if let Err(e) = ensure_book_toml_additional_css(ctx) {
-        eprintln!("[mdbook-gitinfo] Warning: unable to update book.toml additional-css: {e}");
+    if let Err(e) = ensure_book_toml_asset(ctx, key, rel_path) {
+        report(format!("unable to update book.toml {key}: {e}"))?;
     }
+
+    Ok(())
 }
 
-fn ensure_css_file(ctx: &PreprocessorContext, css_contents: &str) -> io::Result<()> {
-    // Put CSS under the mdBook theme override directory at the repo root.
+fn ensure_theme_file(ctx: &PreprocessorContext, rel_path: &str, contents: &str) -> io::Result<()> {
+    // Put assets under the mdBook theme override directory at the repo root.
     // This avoids needing to modify the book source directory layout.
-    let theme_dir = ctx.root.join("theme");
-    fs::create_dir_all(&theme_dir)?;
-
-    let css_path = theme_dir.join("gitinfo.css");
+    let path = ctx.root.join(rel_path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
 
     // Idempotent write: only write if missing or different.
-    match fs::read_to_string(&css_path) {
-        Ok(existing) if existing == css_contents => Ok(()),
-        _ => fs::write(&css_path, css_contents),
+    match fs::read_to_string(&path) {
+        Ok(existing) if existing == contents => Ok(()),
+        _ => fs::write(&path, contents),
     }
 }
 
-fn ensure_book_toml_additional_css(ctx: &PreprocessorContext) -> io::Result<()> {
+/// Compare asset paths the way mdBook resolves them, so `./theme/gitinfo.css`
+/// or `theme\\gitinfo.css` count as the entry we would add.
+fn same_asset_path(a: &str, b: &str) -> bool {
+    fn norm(p: &str) -> String {
+        let p = p.trim().replace('\\', "/");
+        p.trim_start_matches("./").to_string()
+    }
+    norm(a) == norm(b)
+}
+
+/// Add `rel_path` to the `output.html.<key>` list (`additional-css` or
+/// `additional-js`) in `book.toml`.
+fn ensure_book_toml_asset(ctx: &PreprocessorContext, key: &str, rel_path: &str) -> io::Result<()> {
     let book_toml = ctx.root.join("book.toml");
 
     // If book.toml doesn't exist (rare), do nothing gracefully.
@@ -51,48 +103,50 @@ fn ensure_book_toml_additional_css(ctx: &PreprocessorContext) -> io::Result<()>
 
     // Ensure [output.html] table exists
     if doc.get("output").is_none() {
-        doc["output"] = toml_edit::table().into();
+        doc["output"] = toml_edit::table();
     }
     if doc["output"].get("html").is_none() {
-        doc["output"]["html"] = toml_edit::table().into();
+        doc["output"]["html"] = toml_edit::table();
     }
 
-    // Ensure output.html.additional-css is an array, then append if missing.
-    let item = doc["output"]["html"].get_mut("additional-css");
+    // Ensure output.html.<key> is an array, then append if missing.
+    let item = doc["output"]["html"].get_mut(key);
 
     match item {
         None | Some(Item::None) => {
             let mut arr = toml_edit::Array::default();
-            arr.push(Value::from(CSS_REL_PATH));
-            doc["output"]["html"]["additional-css"] = Item::Value(Value::Array(arr));
+            arr.push(Value::from(rel_path));
+            doc["output"]["html"][key] = Item::Value(Value::Array(arr));
         }
 
         Some(Item::Value(Value::Array(arr))) => {
-            let already = arr.iter().any(|v| v.as_str() == Some(CSS_REL_PATH));
+            let already = arr
+                .iter()
+                .any(|v| v.as_str().is_some_and(|p| same_asset_path(p, rel_path)));
             if !already {
-                arr.push(Value::from(CSS_REL_PATH));
+                arr.push(Value::from(rel_path));
             }
         }
 
         // Sometimes users set a single string instead of an array; normalize to array.
         Some(Item::Value(Value::String(s))) => {
             let existing = s.value().to_string();
-            let needs_css = existing != CSS_REL_PATH;
+            let needs_asset = !same_asset_path(&existing, rel_path);
 
             let mut arr = toml_edit::Array::default();
             arr.push(Value::from(existing));
-            if needs_css {
-                arr.push(Value::from(CSS_REL_PATH));
+            if needs_asset {
+                arr.push(Value::from(rel_path));
             }
 
-            doc["output"]["html"]["additional-css"] = Item::Value(Value::Array(arr));
+            doc["output"]["html"][key] = Item::Value(Value::Array(arr));
         }
 
         Some(other) => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!(
-                    "output.html.additional-css exists but is not a string or array (found: {:?})",
+                    "output.html.{key} exists but is not a string or array (found: {:?})",
                     other.type_name()
                 ),
             ));
@@ -120,7 +174,7 @@ mod tests {
     use tempfile::TempDir;
 
     fn ctx_in_dir(dir: &TempDir) -> PreprocessorContext {
-        let mut config = Config::default();
+        let config = Config::default();
         PreprocessorContext::new(dir.path().to_path_buf(), config, "html".to_string())
     }
 
@@ -129,7 +183,7 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let ctx = ctx_in_dir(&dir);
 
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let css_path = dir.path().join("theme/gitinfo.css");
         assert!(css_path.exists());
@@ -141,8 +195,8 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let ctx = ctx_in_dir(&dir);
 
-        ensure_gitinfo_assets(&ctx, "/* css */");
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let css_path = dir.path().join("theme/gitinfo.css");
         assert!(css_path.exists());
@@ -163,7 +217,7 @@ title = "Test"
         .unwrap();
 
         let ctx = ctx_in_dir(&dir);
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let book = fs::read_to_string(dir.path().join("book.toml")).unwrap();
         assert!(book.contains("additional-css"));
@@ -184,7 +238,7 @@ additional-css = ["theme/gitinfo.css"]
         .unwrap();
 
         let ctx = ctx_in_dir(&dir);
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let book = fs::read_to_string(dir.path().join("book.toml")).unwrap();
         let count = book.matches("theme/gitinfo.css").count();
@@ -205,7 +259,7 @@ additional-css = "custom.css"
         .unwrap();
 
         let ctx = ctx_in_dir(&dir);
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let book = fs::read_to_string(dir.path().join("book.toml")).unwrap();
         assert!(book.contains("custom.css"));
@@ -219,9 +273,103 @@ additional-css = "custom.css"
         let ctx = ctx_in_dir(&dir);
 
         // Should not panic or error
-        ensure_gitinfo_assets(&ctx, "/* css */");
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
 
         let css_path = dir.path().join("theme/gitinfo.css");
         assert!(css_path.exists());
     }
+
+    fn js_entries(book_toml: &str) -> String {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("book.toml"), book_toml).unwrap();
+        let ctx = ctx_in_dir(&dir);
+        ensure_gitinfo_js(&ctx, "// js", false).unwrap();
+        ensure_gitinfo_js(&ctx, "// js", false).unwrap();
+
+        let js = fs::read_to_string(dir.path().join("theme/gitinfo.js")).unwrap();
+        assert_eq!(js, "// js");
+        fs::read_to_string(dir.path().join("book.toml")).unwrap()
+    }
+
+    #[test]
+    fn injects_additional_js_when_missing() {
+        let book = js_entries("[book]\ntitle = \"Test\"\n");
+        assert!(
+            book.contains(r#"additional-js = ["theme/gitinfo.js"]"#),
+            "{book}"
+        );
+        assert!(!book.contains("additional-css"));
+    }
+
+    #[test]
+    fn appends_additional_js_to_array_once() {
+        let book =
+            js_entries("[output.html]\nadditional-js = [\"custom.js\", \"./theme/gitinfo.js\"]\n");
+        assert_eq!(book.matches("gitinfo.js").count(), 1);
+
+        let book = js_entries("[output.html]\nadditional-js = [\"custom.js\"]\n");
+        assert!(
+            book.contains(r#"additional-js = ["custom.js", "theme/gitinfo.js"]"#),
+            "{book}"
+        );
+    }
+
+    #[test]
+    fn normalizes_single_string_additional_js_to_array() {
+        let book = js_entries("[output.html]\nadditional-js = \"custom.js\"\n");
+        assert!(
+            book.contains(r#"additional-js = ["custom.js", "theme/gitinfo.js"]"#),
+            "{book}"
+        );
+    }
+
+    /// A file where `theme/` should be makes the CSS write fail, even as root.
+    fn blocked_theme_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("theme"), "not a directory").unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_failure_only_warns_by_default() {
+        let dir = blocked_theme_dir();
+        let ctx = ctx_in_dir(&dir);
+
+        assert!(ensure_gitinfo_assets(&ctx, "/* css */", false).is_ok());
+    }
+
+    #[test]
+    fn write_failure_errors_when_strict() {
+        let dir = blocked_theme_dir();
+        let ctx = ctx_in_dir(&dir);
+
+        let err = ensure_gitinfo_assets(&ctx, "/* css */", true).unwrap_err();
+        assert!(err.to_string().contains("theme/gitinfo.css"));
+    }
+
+    #[test]
+    fn does_not_re_add_equivalent_additional_css_entry() {
+        let dir = TempDir::new().unwrap();
+
+        fs::write(
+            dir.path().join("book.toml"),
+            r#"
+[output.html]
+additional-css = [
+    "custom.css",
+    "./theme/gitinfo.css",
+]
+"#,
+        )
+        .unwrap();
+
+        let ctx = ctx_in_dir(&dir);
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
+        let first = fs::read_to_string(dir.path().join("book.toml")).unwrap();
+        ensure_gitinfo_assets(&ctx, "/* css */", false).unwrap();
+        let second = fs::read_to_string(dir.path().join("book.toml")).unwrap();
+
+        assert_eq!(first.matches("gitinfo.css").count(), 1);
+        assert_eq!(first, second);
+    }
 }
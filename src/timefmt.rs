@@ -1,4 +1,7 @@
+use crate::config::AgeStrings;
+use crate::logging;
 use chrono::{DateTime, FixedOffset, Local, Utc};
+use chrono_tz::Tz;
 
 #[derive(Clone, Debug)]
 pub enum TzMode {
@@ -6,6 +9,7 @@ pub enum TzMode {
     Utc,
     Source,
     Fixed(FixedOffset),
+    Named(Tz),
 }
 
 impl TzMode {
@@ -33,39 +37,368 @@ impl TzMode {
                 {
                     Some(fo) => Self::Fixed(fo),
                     None => {
-                        eprintln!("[gitinfo] Warning: invalid fixed offset '{off}', using 'local'");
+                        logging::warn(format_args!("invalid fixed offset '{off}', using 'local'"));
                         Self::Local
                     }
                 }
             }
-            other => {
-                eprintln!("[gitinfo] Warning: unrecognised timezone '{other}', using 'local'");
-                Self::Local
+            // IANA names such as "America/New_York"
+            _ => match s.unwrap_or_default().trim().parse::<Tz>() {
+                Ok(tz) => Self::Named(tz),
+                Err(_) => {
+                    logging::warn(format_args!("unrecognised timezone '{raw}', using 'local'"));
+                    Self::Local
+                }
+            },
+        }
+    }
+}
+
+/// The build's "now": `SOURCE_DATE_EPOCH` (seconds since the Unix epoch) when
+/// given, for reproducible builds, otherwise the current time.
+///
+/// Resolve it once per run and pass it to every time computation, so
+/// `{{build-date}}`, `{{age}}` and `{{days-since}}` agree.
+pub fn build_now(source_date_epoch: Option<&str>) -> DateTime<Utc> {
+    let Some(raw) = source_date_epoch.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Utc::now();
+    };
+    match raw
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+    {
+        Some(now) => now,
+        None => {
+            logging::warn(format_args!(
+                "ignoring invalid SOURCE_DATE_EPOCH '{raw}', using the current time"
+            ));
+            Utc::now()
+        }
+    }
+}
+
+/// Parse a commit datetime string (RFC3339) as emitted by `git log --format=%cI`.
+pub fn parse_commit_datetime(raw_rfc3339: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(raw_rfc3339.trim()).ok()
+}
+
+/// Whether a chrono format string prints the zone (`%z`, `%:z`, `%::z`,
+/// `%#z`, `%Z`, ...). `%%` is a literal percent sign, not a specifier.
+pub fn has_zone_specifier(fmt: &str) -> bool {
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        loop {
+            match chars.next() {
+                Some(':' | '#' | '-' | '_' | '0') => continue,
+                Some('z' | 'Z') => return true,
+                _ => break,
             }
         }
     }
+    false
+}
+
+/// Date and time formats for `show_offset = true`: when neither prints the
+/// zone, ` %:z` is appended to the time format (or to the date format when
+/// the time is hidden), so commits made in different zones stay
+/// distinguishable under `timezone = "source"`.
+pub fn formats_with_offset(date_fmt: &str, time_fmt: &str, show_time: bool) -> (String, String) {
+    if has_zone_specifier(date_fmt) || has_zone_specifier(time_fmt) {
+        (date_fmt.to_string(), time_fmt.to_string())
+    } else if show_time {
+        (date_fmt.to_string(), format!("{time_fmt} %:z"))
+    } else {
+        (format!("{date_fmt} %:z"), time_fmt.to_string())
+    }
 }
 
 /// Format a commit datetime string (RFC3339) using a target timezone mode.
 /// Offset is applied but only printed if the user's format includes %z/%:z/%Z.
+/// `show_date` / `show_time` drop the respective component regardless of its format string.
 pub fn format_commit_datetime(
     raw_rfc3339: &str,
     tz_opt: Option<&str>,
     date_fmt: &str,
     time_fmt: &str,
+    show_date: bool,
+    show_time: bool,
 ) -> String {
-    let dt_src: DateTime<FixedOffset> = match DateTime::parse_from_rfc3339(raw_rfc3339) {
-        Ok(d) => d,
-        Err(_) => return "unknown".to_string(),
-    };
+    match parse_commit_datetime(raw_rfc3339) {
+        Some(dt) => format_datetime(dt, tz_opt, date_fmt, time_fmt, show_date, show_time),
+        None => "unknown".to_string(),
+    }
+}
+
+/// [`format_commit_datetime`] for an already parsed commit datetime.
+pub fn format_datetime(
+    dt_src: DateTime<FixedOffset>,
+    tz_opt: Option<&str>,
+    date_fmt: &str,
+    time_fmt: &str,
+    show_date: bool,
+    show_time: bool,
+) -> String {
+    let date_fmt = if show_date { date_fmt } else { "" };
+    let time_fmt = if show_time { time_fmt } else { "" };
+    let fmt = format!("{} {}", date_fmt, time_fmt).trim().to_string();
 
     let dt_fixed: DateTime<FixedOffset> = match TzMode::parse(tz_opt) {
         TzMode::Utc => dt_src.with_timezone(&Utc).fixed_offset(),
         TzMode::Source => dt_src,
         TzMode::Fixed(o) => dt_src.with_timezone(&o).fixed_offset(),
         TzMode::Local => dt_src.with_timezone(&Local).fixed_offset(),
+        // Format in the named zone itself so %Z yields its abbreviation (EST/EDT).
+        TzMode::Named(tz) => return dt_src.with_timezone(&tz).format(&fmt).to_string(),
     };
 
-    let fmt = format!("{} {}", date_fmt, time_fmt).trim().to_string();
     dt_fixed.format(&fmt).to_string()
 }
+
+/// Whole days between a commit datetime string (RFC3339) and `now`.
+///
+/// Negative deltas (commit dates in the future, e.g. from clock skew) are
+/// clamped to `0`. Returns `None` if the date cannot be parsed.
+pub fn days_since(raw_rfc3339: &str, now: DateTime<Utc>) -> Option<i64> {
+    let dt = parse_commit_datetime(raw_rfc3339)?;
+    Some((now - dt.with_timezone(&Utc)).num_days().max(0))
+}
+
+/// Human-readable age of a commit datetime string (RFC3339), e.g. `"3 days ago"`.
+///
+/// Units step from minutes to hours, days, months (30 days) and years
+/// (365 days). Each unit uses the matching `strings` template with `{n}`
+/// replaced by the count, or English when the template is unset. Future
+/// dates read as "just now". Returns `None` if the date cannot be parsed.
+pub fn relative_age(raw_rfc3339: &str, now: DateTime<Utc>, strings: &AgeStrings) -> Option<String> {
+    parse_commit_datetime(raw_rfc3339).map(|dt| relative_to(dt, now, strings))
+}
+
+/// [`relative_age`] for an already parsed commit datetime.
+pub fn relative_to(dt: DateTime<FixedOffset>, now: DateTime<Utc>, strings: &AgeStrings) -> String {
+    let delta = now - dt.with_timezone(&Utc);
+    let days = delta.num_days();
+
+    let (n, custom, unit) = if delta.num_minutes() < 1 {
+        return strings
+            .just_now
+            .clone()
+            .unwrap_or_else(|| "just now".to_string());
+    } else if delta.num_hours() < 1 {
+        (delta.num_minutes(), &strings.minutes, "minute")
+    } else if days < 1 {
+        (delta.num_hours(), &strings.hours, "hour")
+    } else if days < 30 {
+        (days, &strings.days, "day")
+    } else if days < 365 {
+        (days / 30, &strings.months, "month")
+    } else {
+        (days / 365, &strings.years, "year")
+    };
+
+    match custom {
+        Some(t) => t.replace("{n}", &n.to_string()),
+        None if n == 1 => format!("1 {unit} ago"),
+        None => format!("{n} {unit}s ago"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn formats_date_and_time_by_default() {
+        let out = format_commit_datetime(
+            "2026-01-01T09:30:00+00:00",
+            Some("utc"),
+            "%Y-%m-%d",
+            "%H:%M",
+            true,
+            true,
+        );
+        assert_eq!(out, "2026-01-01 09:30");
+    }
+
+    #[test]
+    fn show_time_false_renders_date_only() {
+        let out = format_commit_datetime(
+            "2026-01-01T09:30:00+00:00",
+            Some("utc"),
+            "%Y-%m-%d",
+            "%H:%M",
+            true,
+            false,
+        );
+        assert_eq!(out, "2026-01-01");
+    }
+
+    #[test]
+    fn show_date_false_renders_time_only() {
+        let out = format_commit_datetime(
+            "2026-01-01T09:30:00+00:00",
+            Some("utc"),
+            "%Y-%m-%d",
+            "%H:%M",
+            false,
+            true,
+        );
+        assert_eq!(out, "09:30");
+    }
+
+    #[test]
+    fn parses_iana_zone_names() {
+        assert!(matches!(
+            TzMode::parse(Some("America/New_York")),
+            TzMode::Named(chrono_tz::America::New_York)
+        ));
+        assert!(matches!(
+            TzMode::parse(Some(" Europe/London ")),
+            TzMode::Named(chrono_tz::Europe::London)
+        ));
+    }
+
+    #[test]
+    fn unknown_zone_name_falls_back_to_local() {
+        assert!(matches!(TzMode::parse(Some("Mars/Olympus")), TzMode::Local));
+    }
+
+    #[test]
+    fn named_zone_follows_daylight_saving() {
+        let fmt = |raw| {
+            format_commit_datetime(
+                raw,
+                Some("America/New_York"),
+                "%Y-%m-%d",
+                "%H:%M %Z",
+                true,
+                true,
+            )
+        };
+        assert_eq!(fmt("2024-01-15T17:00:00+00:00"), "2024-01-15 12:00 EST");
+        assert_eq!(fmt("2024-07-15T17:00:00+00:00"), "2024-07-15 13:00 EDT");
+    }
+
+    #[test]
+    fn relative_age_defaults_to_english() {
+        let now = utc("2026-01-11T12:00:00Z");
+        let en = AgeStrings::default();
+        let age = |raw| relative_age(raw, now, &en).unwrap();
+
+        assert_eq!(age("2026-01-11T11:59:30+00:00"), "just now");
+        assert_eq!(age("2026-01-11T11:55:00+00:00"), "5 minutes ago");
+        assert_eq!(age("2026-01-11T11:00:00+00:00"), "1 hour ago");
+        assert_eq!(age("2026-01-08T12:00:00+00:00"), "3 days ago");
+        assert_eq!(age("2025-11-01T12:00:00+00:00"), "2 months ago");
+        assert_eq!(age("2023-01-01T12:00:00+00:00"), "3 years ago");
+        assert_eq!(relative_age("nope", now, &en), None);
+    }
+
+    #[test]
+    fn relative_age_uses_localised_strings() {
+        let now = utc("2026-01-11T12:00:00Z");
+        let fr = AgeStrings {
+            just_now: Some("à l'instant".to_string()),
+            days: Some("il y a {n} jours".to_string()),
+            years: Some("il y a {n} ans".to_string()),
+            ..Default::default()
+        };
+        let age = |raw| relative_age(raw, now, &fr).unwrap();
+
+        assert_eq!(age("2026-01-11T12:00:00+00:00"), "à l'instant");
+        assert_eq!(age("2026-01-08T12:00:00+00:00"), "il y a 3 jours");
+        assert_eq!(age("2023-01-01T12:00:00+00:00"), "il y a 3 ans");
+        // Units without a translation fall back to English.
+        assert_eq!(age("2026-01-11T10:00:00+00:00"), "2 hours ago");
+    }
+
+    #[test]
+    fn build_now_pins_to_source_date_epoch() {
+        let now = build_now(Some("1700000000"));
+        assert_eq!(now, utc("2023-11-14T22:13:20Z"));
+
+        let strings = AgeStrings::default();
+        let commit = parse_commit_datetime("2023-11-10T12:00:00Z").unwrap();
+        assert_eq!(relative_to(commit, now, &strings), "4 days ago");
+        assert_eq!(days_since("2023-11-10T12:00:00Z", now), Some(4));
+    }
+
+    #[test]
+    fn build_now_ignores_invalid_epoch() {
+        let before = Utc::now();
+        assert!(build_now(Some("yesterday")) >= before);
+        assert!(build_now(None) >= before);
+    }
+
+    #[test]
+    fn zone_specifiers_are_recognised() {
+        for fmt in ["%z", "%H:%M %:z", "%::z", "%#z", "%Z", "%-z"] {
+            assert!(has_zone_specifier(fmt), "{fmt}");
+        }
+        for fmt in ["%H:%M:%S", "100%%z", "%Y-%m-%d", ""] {
+            assert!(!has_zone_specifier(fmt), "{fmt}");
+        }
+    }
+
+    #[test]
+    fn show_offset_appends_source_offset_only_when_missing() {
+        let dt = parse_commit_datetime("2025-03-01T09:30:00+02:00").unwrap();
+        let render = |date: &str, time: &str, show_time| {
+            let (date, time) = formats_with_offset(date, time, show_time);
+            format_datetime(dt, Some("source"), &date, &time, true, show_time)
+        };
+
+        assert_eq!(render("%Y-%m-%d", "%H:%M", true), "2025-03-01 09:30 +02:00");
+        assert_eq!(render("%Y-%m-%d", "%H:%M", false), "2025-03-01 +02:00");
+        // A format that already shows the zone is left alone.
+        assert_eq!(
+            render("%Y-%m-%d", "%H:%M %z", true),
+            "2025-03-01 09:30 +0200"
+        );
+    }
+
+    #[test]
+    fn one_parse_feeds_absolute_and_relative_forms() {
+        let dt = parse_commit_datetime("2025-03-01T09:30:00+02:00").unwrap();
+        let now = utc("2025-03-01T10:15:00Z");
+        assert_eq!(
+            format_datetime(dt, Some("source"), "%Y-%m-%d", "%H:%M", true, true),
+            "2025-03-01 09:30"
+        );
+        assert_eq!(relative_to(dt, now, &AgeStrings::default()), "2 hours ago");
+    }
+
+    #[test]
+    fn days_since_counts_whole_days() {
+        let now = utc("2026-01-11T12:00:00Z");
+        assert_eq!(days_since("2026-01-01T12:00:00+00:00", now), Some(10));
+        assert_eq!(days_since("2026-01-01T13:00:00+00:00", now), Some(9));
+    }
+
+    #[test]
+    fn days_since_respects_source_offset() {
+        let now = utc("2026-01-02T00:30:00Z");
+        // 2026-01-01T23:00:00-02:00 is 2026-01-02T01:00:00Z, so still in the future.
+        assert_eq!(days_since("2026-01-01T23:00:00-02:00", now), Some(0));
+    }
+
+    #[test]
+    fn days_since_clamps_future_dates_to_zero() {
+        let now = utc("2026-01-01T00:00:00Z");
+        assert_eq!(days_since("2026-03-01T00:00:00+00:00", now), Some(0));
+    }
+
+    #[test]
+    fn days_since_rejects_unparseable_dates() {
+        assert_eq!(days_since("", utc("2026-01-01T00:00:00Z")), None);
+    }
+}
@@ -0,0 +1,28 @@
+// mdbook-gitinfo: progressive enhancement for header/footer blocks (inject-js = true).
+// Adds a tooltip with the full local commit time and any data-gitinfo-* attributes.
+(function () {
+  "use strict";
+
+  function enhance(block) {
+    var lines = [];
+    var time = block.querySelector("time[datetime]");
+    if (time) {
+      var date = new Date(time.getAttribute("datetime"));
+      if (!isNaN(date.getTime())) {
+        lines.push(date.toLocaleString());
+      }
+    }
+    for (var i = 0; i < block.attributes.length; i++) {
+      var attr = block.attributes[i];
+      if (attr.name.indexOf("data-gitinfo-") === 0) {
+        lines.push(attr.name.slice("data-gitinfo-".length) + ": " + attr.value);
+      }
+    }
+    if (lines.length > 0 && !block.title) {
+      block.title = lines.join("\n");
+    }
+    block.classList.add("gitinfo-enhanced");
+  }
+
+  document.querySelectorAll(".gitinfo-header, .gitinfo-footer").forEach(enhance);
+})();
@@ -0,0 +1,68 @@
+//! Runs the `mdbook-gitinfo` binary the way mdBook does, with `--config-override`.
+
+use mdbook_preprocessor::PreprocessorContext;
+use mdbook_preprocessor::book::{Book, BookItem, Chapter};
+use mdbook_preprocessor::config::Config;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[path = "../src/test_support.rs"]
+mod test_support;
+use test_support::git_in;
+
+/// Feed `[ctx, book]` to the binary and return the rendered chapter.
+fn preprocess(root: &Path, book_toml: &str, args: &[&str]) -> String {
+    let mut config = Config::default();
+    let parsed: toml::Value = toml::from_str(book_toml).unwrap();
+    config.set("preprocessor.gitinfo", parsed).unwrap();
+    let ctx = PreprocessorContext::new(root.to_path_buf(), config, "html".to_string());
+
+    let mut book = Book::new();
+    let ch = Chapter::new("Intro", "# Intro\n".to_string(), "intro.md", vec![]);
+    book.push_item(BookItem::Chapter(ch));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mdbook-gitinfo"))
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = serde_json::to_vec(&(&ctx, &book)).unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "preprocessor failed");
+
+    let book: Book = serde_json::from_slice(&out.stdout).unwrap();
+    book.chapters().next().unwrap().content.clone()
+}
+
+#[test]
+fn config_override_replaces_template() {
+    let repo = tempfile::TempDir::new().unwrap();
+    let root = repo.path();
+    git_in(root, &["init", "-q", "-b", "main"]);
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/intro.md"), "# Intro\n").unwrap();
+    git_in(root, &["add", "src/intro.md"]);
+    git_in(root, &["commit", "-q", "-m", "intro"]);
+
+    let book_toml = r#"template = "from book.toml""#;
+    let plain = preprocess(root, book_toml, &[]);
+    assert!(plain.contains("from book.toml"), "{plain}");
+
+    let overridden = preprocess(
+        root,
+        book_toml,
+        &[
+            "--config-override",
+            r#"template = "overridden {{branch}}""#,
+            "--config-override",
+            "separator = ' | '",
+        ],
+    );
+    assert!(overridden.contains("overridden main"), "{overridden}");
+    assert!(!overridden.contains("from book.toml"));
+}